@@ -0,0 +1,397 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rusqlite::{Connection, OpenFlags};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use uuid::Uuid;
+
+// Collection file names inside a package. `collection.anki21b` is zstd-compressed and not supported;
+// packages and collections exported with "Support older Anki versions" also carry one of the others.
+const COLLECTION: &str = "collection.anki21";
+const OLDEST_COLLECTION: &str = "collection.anki2";
+const COMPRESSED_COLLECTION: &str = "collection.anki21b";
+
+// Anki separates note fields and nested deck names with the unit separator
+const FIELD_SEPARATOR: char = '\u{1f}';
+
+// Anki card types (`cards.type`)
+const CARD_TYPE_REVIEW: i64 = 2;
+const CARD_TYPE_RELEARNING: i64 = 3;
+
+// Cards in the (re)learning queue (`cards.queue`) are due at a timestamp in seconds rather than on a day number
+const QUEUE_LEARNING: i64 = 1;
+// Suspended cards, and cards buried by a sibling or by hand until the next day
+const QUEUE_SUSPENDED: i64 = -1;
+const QUEUE_SIBLING_BURIED: i64 = -2;
+const QUEUE_MANUALLY_BURIED: i64 = -3;
+
+/// A note read from an Anki package
+#[derive(Debug, Clone)]
+pub struct AnkiNote {
+    pub front: String,
+    pub back: String,
+    pub deck: Option<String>,
+    pub scheduling: Option<AnkiScheduling>,
+    pub suspended: bool,
+    pub buried: bool,
+}
+
+/// Review state of a note's first card, in the units of our SM-2 fields
+#[derive(Debug, Clone)]
+pub struct AnkiScheduling {
+    pub interval: i64,
+    pub ease_factor: f64,
+    pub next_review: DateTime<Utc>,
+    pub review_count: u32,
+    pub correct_count: u32,
+    pub lapses: u32,
+}
+
+/// Read every note from an `.apkg` or `.colpkg` file
+pub fn read_package(path: &Path) -> Result<Vec<AnkiNote>, Box<dyn std::error::Error>> {
+    let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+
+    let collection_name = if archive.by_name(COLLECTION).is_ok() {
+        COLLECTION
+    } else if archive.by_name(COMPRESSED_COLLECTION).is_ok() {
+        // Next to a compressed collection, `collection.anki2` only holds a placeholder note
+        return Err(
+            "This file uses the newest Anki format, which can't be imported. Export it again with \"Support older Anki versions\" enabled"
+                .into(),
+        );
+    } else if archive.by_name(OLDEST_COLLECTION).is_ok() {
+        OLDEST_COLLECTION
+    } else {
+        return Err("Not an Anki package: no collection found".into());
+    };
+
+    // SQLite needs a real file, so extract the collection next to the other temporary files
+    let collection_path = std::env::temp_dir().join(format!("anki-import-{}.sqlite", Uuid::new_v4()));
+    let result = extract(&mut archive, collection_name, &collection_path).and_then(|_| read_collection(&collection_path));
+    let _ = std::fs::remove_file(&collection_path);
+    result
+}
+
+fn extract(archive: &mut zip::ZipArchive<File>, name: &str, destination: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut entry = archive.by_name(name)?;
+    let mut contents = Vec::new();
+    entry.read_to_end(&mut contents)?;
+    File::create(destination)?.write_all(&contents)?;
+    Ok(())
+}
+
+fn read_collection(path: &Path) -> Result<Vec<AnkiNote>, Box<dyn std::error::Error>> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let collection_created: i64 = conn.query_row("SELECT crt FROM col", [], |row| row.get(0))?;
+    let collection_created = Utc
+        .timestamp_opt(collection_created, 0)
+        .single()
+        .ok_or("Invalid collection creation time")?;
+    let decks = read_decks(&conn)?;
+
+    // The first card of each note (lowest ordinal) carries the note's deck and scheduling.
+    // Cards in a filtered deck keep their home deck and due date in `odid` and `odue`.
+    let mut statement = conn.prepare(
+        "SELECT n.flds, CASE WHEN c.odid != 0 THEN c.odid ELSE c.did END, c.type, c.queue, c.ivl, c.factor,
+                CASE WHEN c.odue != 0 THEN c.odue ELSE c.due END, c.reps, c.lapses
+         FROM notes n
+         LEFT JOIN cards c ON c.nid = n.id AND c.ord = (SELECT MIN(ord) FROM cards WHERE nid = n.id)
+         ORDER BY n.id",
+    )?;
+    let rows = statement.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, Option<i64>>(1)?,
+            row.get::<_, Option<i64>>(2)?,
+            row.get::<_, Option<i64>>(3)?,
+            row.get::<_, Option<i64>>(4)?,
+            row.get::<_, Option<i64>>(5)?,
+            row.get::<_, Option<i64>>(6)?,
+            row.get::<_, Option<i64>>(7)?,
+            row.get::<_, Option<i64>>(8)?,
+        ))
+    })?;
+
+    let mut notes = Vec::new();
+    for row in rows {
+        let (fields, deck_id, card_type, queue, interval, factor, due, reps, lapses) = row?;
+        let mut fields = fields.split(FIELD_SEPARATOR);
+        let front = html_to_text(fields.next().unwrap_or_default());
+        let back = html_to_text(fields.next().unwrap_or_default());
+
+        let scheduling = match (card_type, interval, due) {
+            (Some(CARD_TYPE_REVIEW | CARD_TYPE_RELEARNING), Some(interval), Some(due)) if interval > 0 => {
+                let reps = reps.unwrap_or(0).max(0) as u32;
+                let lapses = lapses.unwrap_or(0).max(0) as u32;
+                Some(AnkiScheduling {
+                    interval,
+                    // Anki stores the ease factor in permille
+                    ease_factor: factor.filter(|f| *f > 0).map(|f| f as f64 / 1000.0).unwrap_or(2.5),
+                    next_review: due_date(collection_created, queue, due),
+                    review_count: reps,
+                    correct_count: reps.saturating_sub(lapses),
+                    lapses,
+                })
+            }
+            _ => None,
+        };
+
+        notes.push(AnkiNote {
+            front,
+            back,
+            deck: deck_id.and_then(|id| decks.get(&id).cloned()),
+            scheduling,
+            suspended: queue == Some(QUEUE_SUSPENDED),
+            buried: matches!(queue, Some(QUEUE_SIBLING_BURIED | QUEUE_MANUALLY_BURIED)),
+        });
+    }
+
+    Ok(notes)
+}
+
+/// Relearning cards are due at a timestamp, the others on a day counted from the collection's creation.
+/// Out of range values make the card due now.
+fn due_date(collection_created: DateTime<Utc>, queue: Option<i64>, due: i64) -> DateTime<Utc> {
+    let due_date = if queue == Some(QUEUE_LEARNING) {
+        Utc.timestamp_opt(due, 0).single()
+    } else {
+        Duration::try_days(due).and_then(|days| collection_created.checked_add_signed(days))
+    };
+    due_date.unwrap_or_else(Utc::now)
+}
+
+/// Deck names by id. Newer collections have a `decks` table, older ones a JSON column on `col`.
+/// The built-in "Default" deck is left out so its notes stay untagged.
+fn read_decks(conn: &Connection) -> Result<HashMap<i64, String>, Box<dyn std::error::Error>> {
+    let mut decks = HashMap::new();
+
+    let has_decks_table: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'decks'",
+        [],
+        |row| row.get(0),
+    )?;
+
+    if has_decks_table {
+        let mut statement = conn.prepare("SELECT id, name FROM decks")?;
+        let rows = statement.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+        for row in rows {
+            let (id, name) = row?;
+            decks.insert(id, name.replace(FIELD_SEPARATOR, "::"));
+        }
+    } else {
+        let json: String = conn.query_row("SELECT decks FROM col", [], |row| row.get(0))?;
+        let parsed: HashMap<String, serde_json::Value> = serde_json::from_str(&json)?;
+        for (id, deck) in parsed {
+            if let (Ok(id), Some(name)) = (id.parse::<i64>(), deck["name"].as_str()) {
+                decks.insert(id, name.to_string());
+            }
+        }
+    }
+
+    decks.retain(|_, name| name != "Default");
+    Ok(decks)
+}
+
+/// Convert Anki's HTML field content to plain text
+fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let tag = rest[start + 1..start + end].trim().to_ascii_lowercase();
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default();
+        if matches!(name, "br" | "div" | "p" | "li") && !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+    use zip::write::SimpleFileOptions;
+
+    /// Build a minimal legacy Anki package with one note per `(fields, deck, card type, queue, interval)`
+    pub(crate) fn create_test_package(dir: &Path, notes: &[(&str, &str, i64, i64, i64)]) -> PathBuf {
+        let collection_path = create_test_collection(dir, notes);
+
+        let package_path = dir.join("deck.apkg");
+        let mut writer = zip::ZipWriter::new(File::create(&package_path).unwrap());
+        writer.start_file("collection.anki2", SimpleFileOptions::default()).unwrap();
+        writer.write_all(&std::fs::read(&collection_path).unwrap()).unwrap();
+        writer.start_file("media", SimpleFileOptions::default()).unwrap();
+        writer.write_all(b"{}").unwrap();
+        writer.finish().unwrap();
+
+        package_path
+    }
+
+    /// The collection inside `create_test_package`. Cards in the learning queue are due in ten minutes.
+    fn create_test_collection(dir: &Path, notes: &[(&str, &str, i64, i64, i64)]) -> PathBuf {
+        let collection_path = dir.join("collection.anki2");
+        let conn = Connection::open(&collection_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE col (id INTEGER PRIMARY KEY, crt INTEGER NOT NULL, decks TEXT NOT NULL);
+             CREATE TABLE notes (id INTEGER PRIMARY KEY, flds TEXT NOT NULL);
+             CREATE TABLE cards (id INTEGER PRIMARY KEY, nid INTEGER, did INTEGER, ord INTEGER, type INTEGER,
+                                 queue INTEGER, ivl INTEGER, factor INTEGER, due INTEGER, reps INTEGER,
+                                 lapses INTEGER, odue INTEGER DEFAULT 0, odid INTEGER DEFAULT 0);",
+        )
+        .unwrap();
+
+        let decks = serde_json::json!({
+            "1": {"name": "Default"},
+            "2": {"name": "Languages::Spanish"},
+        });
+        conn.execute(
+            "INSERT INTO col (id, crt, decks) VALUES (1, ?1, ?2)",
+            rusqlite::params![Utc::now().timestamp() - 86400 * 10, decks.to_string()],
+        )
+        .unwrap();
+
+        for (index, (fields, deck, card_type, queue, interval)) in notes.iter().enumerate() {
+            let id = index as i64 + 1;
+            let deck_id = if *deck == "Default" { 1 } else { 2 };
+            let due = if *queue == QUEUE_LEARNING {
+                Utc::now().timestamp() + 600
+            } else {
+                15
+            };
+            conn.execute("INSERT INTO notes (id, flds) VALUES (?1, ?2)", rusqlite::params![id, fields])
+                .unwrap();
+            conn.execute(
+                "INSERT INTO cards (id, nid, did, ord, type, queue, ivl, factor, due, reps, lapses)
+                 VALUES (?1, ?1, ?2, 0, ?3, ?4, ?5, 2300, ?6, 5, 1)",
+                rusqlite::params![id, deck_id, card_type, queue, interval, due],
+            )
+            .unwrap();
+        }
+
+        collection_path
+    }
+
+    #[test]
+    fn test_read_package() {
+        let temp_dir = TempDir::new().unwrap();
+        let package = create_test_package(
+            temp_dir.path(),
+            &[
+                ("hola\u{1f}hello", "Languages::Spanish", 2, 2, 12),
+                ("<b>Capital</b> of France?\u{1f}Paris<br>City of light", "Default", 0, 0, 0),
+                ("gato\u{1f}cat", "Default", 2, QUEUE_SUSPENDED, 8),
+                ("perro\u{1f}dog", "Default", 2, QUEUE_SIBLING_BURIED, 8),
+                ("pez\u{1f}fish", "Default", 0, QUEUE_MANUALLY_BURIED, 0),
+            ],
+        );
+
+        let notes = read_package(&package).unwrap();
+        assert_eq!(notes.len(), 5);
+
+        assert_eq!(notes[0].front, "hola");
+        assert_eq!(notes[0].back, "hello");
+        assert_eq!(notes[0].deck, Some("Languages::Spanish".to_string()));
+        let scheduling = notes[0].scheduling.as_ref().unwrap();
+        assert_eq!(scheduling.interval, 12);
+        assert_eq!(scheduling.ease_factor, 2.3);
+        assert_eq!(scheduling.review_count, 5);
+        assert_eq!(scheduling.correct_count, 4);
+        assert_eq!(scheduling.lapses, 1);
+        assert!(scheduling.next_review > Utc::now());
+
+        assert_eq!(notes[1].front, "Capital of France?");
+        assert_eq!(notes[1].back, "Paris\nCity of light");
+        assert_eq!(notes[1].deck, None);
+        assert!(notes[1].scheduling.is_none());
+
+        // Suspended and buried cards keep their scheduling
+        let flags: Vec<(bool, bool)> = notes.iter().map(|note| (note.suspended, note.buried)).collect();
+        assert_eq!(
+            flags,
+            vec![(false, false), (false, false), (true, false), (false, true), (false, true)]
+        );
+        assert_eq!(notes[2].scheduling.as_ref().unwrap().interval, 8);
+        assert_eq!(notes[3].scheduling.as_ref().unwrap().interval, 8);
+    }
+
+    #[test]
+    fn test_read_package_relearning_card_due_at_timestamp() {
+        let temp_dir = TempDir::new().unwrap();
+        let package = create_test_package(temp_dir.path(), &[("hola\u{1f}hello", "Languages::Spanish", 3, QUEUE_LEARNING, 12)]);
+
+        let notes = read_package(&package).unwrap();
+        let next_review = notes[0].scheduling.as_ref().unwrap().next_review;
+        assert!(next_review > Utc::now());
+        assert!(next_review < Utc::now() + Duration::hours(1));
+    }
+
+    #[test]
+    fn test_read_collection_filtered_deck_and_overflowing_due() {
+        let temp_dir = TempDir::new().unwrap();
+        let collection = create_test_collection(
+            temp_dir.path(),
+            &[
+                ("hola\u{1f}hello", "Languages::Spanish", 2, 2, 12),
+                ("gato\u{1f}cat", "Default", 2, 2, 8),
+            ],
+        );
+        let conn = Connection::open(&collection).unwrap();
+        // The first card was moved to a filtered deck, the second has a due day far out of range
+        conn.execute("UPDATE cards SET did = 3, odid = 2, due = -100000, odue = 15 WHERE id = 1", [])
+            .unwrap();
+        conn.execute("UPDATE cards SET due = ?1 WHERE id = 2", [i64::MAX]).unwrap();
+        drop(conn);
+
+        let notes = read_collection(&collection).unwrap();
+        assert_eq!(notes[0].deck, Some("Languages::Spanish".to_string()));
+        assert!(notes[0].scheduling.as_ref().unwrap().next_review > Utc::now());
+
+        let next_review = notes[1].scheduling.as_ref().unwrap().next_review;
+        assert!((next_review - Utc::now()).num_seconds().abs() < 60);
+    }
+
+    #[test]
+    fn test_read_package_rejects_compressed_collection() {
+        let temp_dir = TempDir::new().unwrap();
+        let package_path = temp_dir.path().join("other.apkg");
+        let mut writer = zip::ZipWriter::new(File::create(&package_path).unwrap());
+        writer.start_file(COMPRESSED_COLLECTION, SimpleFileOptions::default()).unwrap();
+        writer.write_all(b"zstd data").unwrap();
+        writer.finish().unwrap();
+
+        let error = read_package(&package_path).unwrap_err();
+        assert!(error.to_string().contains("newest Anki format"));
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        assert_eq!(html_to_text("plain"), "plain");
+        assert_eq!(html_to_text("<div>one</div><div>two</div>"), "one\ntwo");
+        assert_eq!(html_to_text("a &amp; b&nbsp;&lt;c&gt;"), "a & b <c>");
+        assert_eq!(html_to_text("<img src=\"x.png\">caption"), "caption");
+    }
+}
@@ -0,0 +1,163 @@
+use crate::models::{AnswerTime, AnswerTimeReport, AppSettings, Card, CardAnswerTime, ReviewDifficulty, ReviewLog, TagAnswerTime};
+use std::collections::HashMap;
+
+/// Answers reported as taking longer than ten minutes were most likely left open; they count as
+/// taking ten minutes
+pub const MAX_ANSWER_MS: u64 = 10 * 60 * 1000;
+
+/// The answer to schedule for `difficulty`, given after `time_taken_ms`. With `slow_answer_seconds`
+/// set, a Good answer that took longer was a struggle and counts as Hard.
+pub fn effective_difficulty(difficulty: ReviewDifficulty, time_taken_ms: Option<u64>, settings: &AppSettings) -> ReviewDifficulty {
+    let slow = settings.slow_answer_seconds > 0 && time_taken_ms.is_some_and(|ms| ms > settings.slow_answer_seconds as u64 * 1000);
+    match difficulty {
+        ReviewDifficulty::Good if slow => ReviewDifficulty::Hard,
+        other => other,
+    }
+}
+
+impl AnswerTime {
+    /// `None` without any times
+    fn from_times(mut times: Vec<u64>) -> Option<Self> {
+        if times.is_empty() {
+            return None;
+        }
+        times.sort_unstable();
+
+        let total_ms: u64 = times.iter().sum();
+        let middle = times.len() / 2;
+        let median_ms = if times.len() % 2 == 0 {
+            (times[middle - 1] + times[middle]) / 2
+        } else {
+            times[middle]
+        };
+        Some(AnswerTime {
+            answers: times.len(),
+            total_ms,
+            average_ms: total_ms / times.len() as u64,
+            median_ms,
+        })
+    }
+}
+
+/// Answer times from the review history, per card and per tag. Cards that were deleted since are
+/// left out; untagged cards count towards "Uncategorized", as in the tag stats.
+pub fn answer_time_report(cards: &HashMap<String, Card>, log: &[ReviewLog]) -> AnswerTimeReport {
+    let mut by_card: HashMap<&str, Vec<u64>> = HashMap::new();
+    for entry in log.iter().filter(|entry| cards.contains_key(&entry.card_id)) {
+        if let Some(ms) = entry.time_taken_ms {
+            by_card.entry(entry.card_id.as_str()).or_default().push(ms);
+        }
+    }
+
+    let mut by_tag: HashMap<&str, Vec<u64>> = HashMap::new();
+    for (card_id, times) in &by_card {
+        let card = &cards[*card_id];
+        if card.tags.is_empty() {
+            by_tag.entry("Uncategorized").or_default().extend(times);
+        }
+        for tag in &card.tags {
+            by_tag.entry(tag.as_str()).or_default().extend(times);
+        }
+    }
+
+    let overall = AnswerTime::from_times(by_card.values().flatten().copied().collect());
+    let mut card_times: Vec<CardAnswerTime> = by_card
+        .into_iter()
+        .filter_map(|(card_id, times)| {
+            Some(CardAnswerTime {
+                card_id: card_id.to_string(),
+                front: cards[card_id].front.clone(),
+                time: AnswerTime::from_times(times)?,
+            })
+        })
+        .collect();
+    let mut tag_times: Vec<TagAnswerTime> = by_tag
+        .into_iter()
+        .filter_map(|(tag, times)| {
+            Some(TagAnswerTime {
+                tag: tag.to_string(),
+                time: AnswerTime::from_times(times)?,
+            })
+        })
+        .collect();
+
+    card_times.sort_by(|a, b| b.time.total_ms.cmp(&a.time.total_ms).then_with(|| a.card_id.cmp(&b.card_id)));
+    tag_times.sort_by(|a, b| b.time.total_ms.cmp(&a.time.total_ms).then_with(|| a.tag.cmp(&b.tag)));
+    AnswerTimeReport {
+        overall,
+        cards: card_times,
+        tags: tag_times,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn create_test_card(id: &str, tags: &[&str]) -> Card {
+        Card {
+            tags: tags.iter().map(|tag| tag.to_string()).collect::<BTreeSet<_>>(),
+            ..Card::new_for_test(id)
+        }
+    }
+
+    fn create_test_log(card_id: &str, time_taken_ms: Option<u64>) -> ReviewLog {
+        ReviewLog {
+            time_taken_ms,
+            ..ReviewLog::new_for_test(card_id)
+        }
+    }
+
+    #[test]
+    fn test_slow_good_answers_count_as_hard() {
+        let settings = AppSettings {
+            slow_answer_seconds: 20,
+            ..Default::default()
+        };
+
+        let effective = |difficulty, ms| effective_difficulty(difficulty, ms, &settings);
+        assert_eq!(effective(ReviewDifficulty::Good, Some(25_000)), ReviewDifficulty::Hard);
+        assert_eq!(effective(ReviewDifficulty::Good, Some(20_000)), ReviewDifficulty::Good);
+        assert_eq!(effective(ReviewDifficulty::Good, None), ReviewDifficulty::Good);
+        assert_eq!(effective(ReviewDifficulty::Easy, Some(60_000)), ReviewDifficulty::Easy);
+
+        // Off by default
+        let default = AppSettings::default();
+        assert_eq!(
+            effective_difficulty(ReviewDifficulty::Good, Some(60_000), &default),
+            ReviewDifficulty::Good
+        );
+    }
+
+    #[test]
+    fn test_answer_time_report() {
+        let cards = HashMap::from([
+            ("a".to_string(), create_test_card("a", &["Verbs"])),
+            ("b".to_string(), create_test_card("b", &["Verbs", "Nouns"])),
+            ("c".to_string(), create_test_card("c", &[])),
+        ]);
+        let log = vec![
+            create_test_log("a", Some(2_000)),
+            create_test_log("a", Some(4_000)),
+            create_test_log("a", Some(9_000)),
+            create_test_log("b", Some(30_000)),
+            create_test_log("c", None), // Not reported
+            create_test_log("deleted", Some(1_000)),
+        ];
+
+        let report = answer_time_report(&cards, &log);
+
+        let overall = report.overall.unwrap();
+        assert_eq!((overall.answers, overall.total_ms), (4, 45_000));
+        assert_eq!(overall.median_ms, 6_500);
+
+        let card_ids: Vec<&str> = report.cards.iter().map(|card| card.card_id.as_str()).collect();
+        assert_eq!(card_ids, vec!["b", "a"]);
+        assert_eq!(report.cards[1].time.average_ms, 5_000);
+        assert_eq!(report.cards[1].time.median_ms, 4_000);
+
+        let tags: Vec<(&str, u64)> = report.tags.iter().map(|tag| (tag.tag.as_str(), tag.time.total_ms)).collect();
+        assert_eq!(tags, vec![("Verbs", 45_000), ("Nouns", 30_000)]);
+    }
+}
@@ -0,0 +1,241 @@
+use crate::models::{AppSettings, BackupInfo, Card, DeckCollection, ReviewLog};
+use crate::storage::write_json_atomic;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever the archive layout changes; older archives must keep restoring
+pub const BACKUP_FORMAT_VERSION: u32 = 2;
+
+// Automatic backups are taken at most once per interval and only the newest few are kept
+pub const AUTO_BACKUPS_TO_KEEP: usize = 10;
+const AUTO_BACKUP_INTERVAL_HOURS: i64 = 24;
+
+const AUTO_BACKUP_PREFIX: &str = "auto-backup-";
+const MANUAL_BACKUP_PREFIX: &str = "backup-";
+const BACKUP_EXTENSION: &str = "json";
+
+/// Everything needed to bring a collection back exactly as it was: cards with their ids and
+/// scheduling state, settings, decks and the review history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupArchive {
+    pub format_version: u32,
+    pub created_at: DateTime<Utc>,
+    pub cards: Vec<Card>,
+    pub settings: AppSettings,
+    #[serde(default)]
+    pub review_log: Vec<ReviewLog>,
+    #[serde(default)] // Added in format version 2
+    pub decks: DeckCollection,
+}
+
+impl BackupArchive {
+    pub fn new(mut cards: Vec<Card>, settings: AppSettings, decks: DeckCollection, review_log: Vec<ReviewLog>) -> Self {
+        cards.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        BackupArchive {
+            format_version: BACKUP_FORMAT_VERSION,
+            created_at: Utc::now(),
+            cards,
+            settings,
+            review_log,
+            decks,
+        }
+    }
+}
+
+pub fn write_backup(path: &Path, archive: &BackupArchive) -> Result<BackupInfo, Box<dyn std::error::Error>> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_json_atomic(path, archive)?;
+    Ok(backup_info(path, archive))
+}
+
+pub fn read_backup(path: &Path) -> Result<BackupArchive, Box<dyn std::error::Error>> {
+    let contents = std::fs::read(path)?;
+    let archive: BackupArchive = serde_json::from_slice(&contents)?;
+
+    if archive.format_version > BACKUP_FORMAT_VERSION {
+        return Err(format!(
+            "Backup format version {} was created by a newer version of the app (supported up to {})",
+            archive.format_version, BACKUP_FORMAT_VERSION
+        )
+        .into());
+    }
+
+    Ok(archive)
+}
+
+/// Path for a new backup in `backup_dir`, named after the time it is taken
+pub fn new_backup_path(backup_dir: &Path, automatic: bool) -> PathBuf {
+    let prefix = if automatic { AUTO_BACKUP_PREFIX } else { MANUAL_BACKUP_PREFIX };
+    backup_dir.join(format!("{}{}.{}", prefix, Utc::now().format("%Y%m%d-%H%M%S%.3f"), BACKUP_EXTENSION))
+}
+
+/// Backups in `backup_dir`, newest first. Files that cannot be read are left out.
+pub fn list_backups(backup_dir: &Path) -> Result<Vec<BackupInfo>, Box<dyn std::error::Error>> {
+    if !backup_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    for entry in std::fs::read_dir(backup_dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(BACKUP_EXTENSION) {
+            continue;
+        }
+        match read_backup(&path) {
+            Ok(archive) => backups.push(backup_info(&path, &archive)),
+            Err(e) => log::warn!("Skipping unreadable backup {}: {}", path.display(), e),
+        }
+    }
+
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+    Ok(backups)
+}
+
+/// Whether the newest automatic backup is older than the backup interval
+pub fn auto_backup_due(backup_dir: &Path) -> Result<bool, Box<dyn std::error::Error>> {
+    let latest = list_backups(backup_dir)?.into_iter().find(|backup| backup.automatic);
+    Ok(match latest {
+        Some(backup) => Utc::now() - backup.created_at >= Duration::hours(AUTO_BACKUP_INTERVAL_HOURS),
+        None => true,
+    })
+}
+
+/// Delete all but the newest `keep` automatic backups. Manual backups are never removed.
+pub fn prune_auto_backups(backup_dir: &Path, keep: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let automatic = list_backups(backup_dir)?.into_iter().filter(|backup| backup.automatic);
+    for backup in automatic.skip(keep) {
+        std::fs::remove_file(&backup.path)?;
+    }
+    Ok(())
+}
+
+pub fn backup_info(path: &Path, archive: &BackupArchive) -> BackupInfo {
+    let file_name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    BackupInfo {
+        automatic: file_name.starts_with(AUTO_BACKUP_PREFIX),
+        file_name,
+        path: path.display().to_string(),
+        created_at: archive.created_at,
+        card_count: archive.cards.len(),
+        review_log_count: archive.review_log.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CardState;
+    use std::collections::BTreeSet;
+    use tempfile::TempDir;
+
+    fn create_test_card(id: &str) -> Card {
+        Card {
+            tags: BTreeSet::from(["Test".to_string()]),
+            last_reviewed: Some(Utc::now()),
+            next_review: Utc::now() + Duration::days(6),
+            interval: 6,
+            ease_factor: 2.2,
+            review_count: 3,
+            correct_count: 2,
+            leitner_box: 2,
+            exponential_factor: 1.8,
+            state: CardState::Review,
+            ..Card::new_for_test(id)
+        }
+    }
+
+    fn create_test_archive() -> BackupArchive {
+        let review_log = vec![ReviewLog {
+            previous_interval: 1,
+            new_interval: 6,
+            previous_ease_factor: 2.2,
+            new_ease_factor: 2.2,
+            ..ReviewLog::new_for_test("1")
+        }];
+        BackupArchive::new(
+            vec![create_test_card("1"), create_test_card("2")],
+            AppSettings::default(),
+            DeckCollection::default(),
+            review_log,
+        )
+    }
+
+    #[test]
+    fn test_write_and_read_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = new_backup_path(temp_dir.path(), false);
+        let archive = create_test_archive();
+
+        let info = write_backup(&path, &archive).unwrap();
+        assert!(!info.automatic);
+        assert_eq!(info.card_count, 2);
+        assert_eq!(info.review_log_count, 1);
+
+        let restored = read_backup(&path).unwrap();
+        assert_eq!(restored.format_version, BACKUP_FORMAT_VERSION);
+        assert_eq!(
+            serde_json::to_value(&restored.cards).unwrap(),
+            serde_json::to_value(&archive.cards).unwrap()
+        );
+        assert_eq!(restored.review_log[0].id, "log-1");
+    }
+
+    #[test]
+    fn test_read_version_1_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("backup-v1.json");
+        let mut value = serde_json::to_value(create_test_archive()).unwrap();
+        value["format_version"] = serde_json::json!(1);
+        value.as_object_mut().unwrap().remove("decks");
+        std::fs::write(&path, value.to_string()).unwrap();
+
+        let archive = read_backup(&path).unwrap();
+        assert_eq!(archive.cards.len(), 2);
+        assert!(archive.decks.decks.is_empty());
+    }
+
+    #[test]
+    fn test_read_backup_from_newer_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("future.json");
+        let mut archive = create_test_archive();
+        archive.format_version = BACKUP_FORMAT_VERSION + 1;
+        std::fs::write(&path, serde_json::to_string(&archive).unwrap()).unwrap();
+
+        let error = read_backup(&path).unwrap_err();
+        assert!(error.to_string().contains("newer version"));
+    }
+
+    #[test]
+    fn test_auto_backup_rotation() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(auto_backup_due(temp_dir.path()).unwrap());
+
+        let archive = create_test_archive();
+        write_backup(&new_backup_path(temp_dir.path(), false), &archive).unwrap();
+        for _ in 0..4 {
+            write_backup(&new_backup_path(temp_dir.path(), true), &archive).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert!(!auto_backup_due(temp_dir.path()).unwrap());
+
+        prune_auto_backups(temp_dir.path(), 2).unwrap();
+
+        let backups = list_backups(temp_dir.path()).unwrap();
+        assert_eq!(backups.iter().filter(|b| b.automatic).count(), 2);
+        assert_eq!(backups.iter().filter(|b| !b.automatic).count(), 1);
+    }
+
+    #[test]
+    fn test_list_backups_skips_unreadable_files() {
+        let temp_dir = TempDir::new().unwrap();
+        write_backup(&new_backup_path(temp_dir.path(), true), &create_test_archive()).unwrap();
+        std::fs::write(temp_dir.path().join("auto-backup-broken.json"), "{").unwrap();
+        std::fs::write(temp_dir.path().join("notes.txt"), "not a backup").unwrap();
+
+        assert_eq!(list_backups(temp_dir.path()).unwrap().len(), 1);
+    }
+}
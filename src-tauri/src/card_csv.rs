@@ -0,0 +1,301 @@
+use crate::models::Card;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+/// Every `Card` field, in the order they are exported
+pub const CARD_COLUMNS: [&str; 23] = [
+    "id",
+    "front",
+    "back",
+    "tags",
+    "deck_id",
+    "created_at",
+    "last_reviewed",
+    "next_review",
+    "interval",
+    "ease_factor",
+    "review_count",
+    "correct_count",
+    "lapses",
+    "last_answer_ms",
+    "leitner_box",
+    "exponential_factor",
+    "fsrs_stability",
+    "fsrs_difficulty",
+    "scheduler_state",
+    "state",
+    "learning_step",
+    "suspended",
+    "buried_until",
+];
+
+/// Optional `Card` fields that hold a number. An unset field has no value to tell its type from,
+/// and the other optional fields are texts or dates.
+const OPTIONAL_NUMBER_COLUMNS: [&str; 1] = ["last_answer_ms"];
+
+/// Use `delimiter` if given, otherwise tab for `.tsv`/`.tab` files and comma for everything else
+pub fn delimiter_for(path: &Path, delimiter: Option<char>) -> Result<u8, String> {
+    match delimiter {
+        Some(delimiter) if delimiter.is_ascii() => Ok(delimiter as u8),
+        Some(delimiter) => Err(format!("Unsupported delimiter '{}'", delimiter)),
+        None => {
+            let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+            if extension.eq_ignore_ascii_case("tsv") || extension.eq_ignore_ascii_case("tab") {
+                Ok(b'\t')
+            } else {
+                Ok(b',')
+            }
+        }
+    }
+}
+
+/// Write `cards` with a header row and one column per card field
+pub fn write_cards<W: Write>(writer: W, cards: &[Card], delimiter: u8) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = csv::WriterBuilder::new().delimiter(delimiter).from_writer(writer);
+    writer.write_record(CARD_COLUMNS)?;
+
+    for card in cards {
+        let value = serde_json::to_value(card)?;
+        let record: Vec<String> = CARD_COLUMNS.iter().map(|column| cell_from_value(&value[*column])).collect();
+        writer.write_record(&record)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read each row as a map of card field to cell. `columns` maps card fields to zero-based
+/// column indexes; without it, columns are matched to fields by their header names.
+pub fn read_rows(
+    path: &Path,
+    delimiter: u8,
+    has_headers: bool,
+    columns: Option<HashMap<String, usize>>,
+) -> Result<Vec<HashMap<String, String>>, Box<dyn std::error::Error>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(has_headers)
+        .flexible(true)
+        .from_path(path)?;
+
+    let columns: HashMap<String, usize> = match columns {
+        Some(columns) => columns.into_iter().map(|(field, index)| (column_name(&field), index)).collect(),
+        None if has_headers => reader
+            .headers()?
+            .iter()
+            .enumerate()
+            .map(|(index, header)| (column_name(header), index))
+            .filter(|(header, _)| CARD_COLUMNS.contains(&header.as_str()))
+            .collect(),
+        None => return Err("A column mapping is required for files without a header row".into()),
+    };
+
+    if let Some(field) = columns.keys().find(|field| !CARD_COLUMNS.contains(&field.as_str())) {
+        return Err(format!("Unknown card field '{}' in column mapping", field).into());
+    }
+    if !columns.contains_key("front") || !columns.contains_key("back") {
+        return Err("The front and back columns are required".into());
+    }
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let row = columns
+            .iter()
+            .filter_map(|(field, index)| record.get(*index).map(|cell| (field.clone(), cell.to_string())))
+            .collect();
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// Apply the cells of an imported row on top of `card`. Empty cells keep the card's value.
+pub fn apply_row(card: &Card, row: &HashMap<String, String>) -> Result<Card, String> {
+    let mut value = serde_json::to_value(card).map_err(|e| e.to_string())?;
+
+    for (field, cell) in row {
+        if cell.trim().is_empty() {
+            continue;
+        }
+
+        // The current value tells which JSON type the field expects
+        let parsed = match &value[field.as_str()] {
+            // A list is written as a JSON array; a plain cell is taken as a single entry
+            Value::Array(_) if !cell.trim_start().starts_with('[') => Value::Array(vec![Value::String(cell.clone())]),
+            Value::Number(_) | Value::Bool(_) | Value::Array(_) | Value::Object(_) => parse_cell(field, cell)?,
+            Value::Null if OPTIONAL_NUMBER_COLUMNS.contains(&field.as_str()) => parse_cell(field, cell)?,
+            Value::String(_) | Value::Null => Value::String(cell.clone()),
+        };
+        value[field.as_str()] = parsed;
+    }
+
+    serde_json::from_value(value).map_err(|e| format!("Invalid row: {}", e))
+}
+
+/// Card field for a header or mapped column name. Files exported before cards had
+/// multiple tags use a `tag` column.
+fn column_name(name: &str) -> String {
+    match name.trim().to_lowercase().as_str() {
+        "tag" => "tags".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn parse_cell(field: &str, cell: &str) -> Result<Value, String> {
+    serde_json::from_str(cell.trim()).map_err(|_| format!("Invalid value '{}' for {}", cell, field))
+}
+
+fn cell_from_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CardState;
+    use chrono::Utc;
+    use std::collections::BTreeSet;
+    use tempfile::TempDir;
+
+    fn create_test_card(id: &str) -> Card {
+        Card {
+            front: format!("Question, \"{}\"", id),
+            back: format!("Answer\n{}", id),
+            tags: BTreeSet::from(["Languages::Spanish".to_string(), "Verbs, irregular".to_string()]),
+            last_reviewed: Some(Utc::now()),
+            interval: 6,
+            ease_factor: 2.36,
+            review_count: 2,
+            correct_count: 1,
+            leitner_box: 3,
+            exponential_factor: 1.5,
+            fsrs_stability: 4.2,
+            fsrs_difficulty: 5.1,
+            state: CardState::Review,
+            ..Card::new_for_test(id)
+        }
+    }
+
+    #[test]
+    fn test_columns_cover_every_card_field() {
+        let value = serde_json::to_value(create_test_card("1")).unwrap();
+        let fields = value.as_object().unwrap();
+
+        assert_eq!(fields.len(), CARD_COLUMNS.len());
+        for column in CARD_COLUMNS {
+            assert!(fields.contains_key(column), "missing column {}", column);
+        }
+    }
+
+    #[test]
+    fn test_optional_number_columns() {
+        let unset = serde_json::to_value(Card::new_for_test("1")).unwrap();
+        let set = serde_json::to_value(Card {
+            deck_id: Some("deck-1".to_string()),
+            last_reviewed: Some(Utc::now()),
+            last_answer_ms: Some(4200),
+            buried_until: Some(Utc::now()),
+            ..Card::new_for_test("1")
+        })
+        .unwrap();
+
+        for column in CARD_COLUMNS.iter().filter(|column| unset[**column].is_null()) {
+            assert!(!set[*column].is_null(), "optional column {} not set", column);
+            assert_eq!(set[*column].is_number(), OPTIONAL_NUMBER_COLUMNS.contains(column), "{}", column);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("cards.tsv");
+        // Optional fields set on the cards but unset on the template must come back with their own type
+        let cards = vec![
+            Card {
+                deck_id: Some("123".to_string()),
+                last_answer_ms: Some(4200),
+                ..create_test_card("1")
+            },
+            Card {
+                deck_id: Some("null".to_string()),
+                ..create_test_card("3")
+            },
+            create_test_card("2"),
+        ];
+
+        let delimiter = delimiter_for(&path, None).unwrap();
+        assert_eq!(delimiter, b'\t');
+        write_cards(std::fs::File::create(&path).unwrap(), &cards, delimiter).unwrap();
+
+        let rows = read_rows(&path, delimiter, true, None).unwrap();
+        assert_eq!(rows.len(), 3);
+
+        let template = create_test_card("template");
+        assert!(template.deck_id.is_none() && template.last_answer_ms.is_none());
+        for (row, original) in rows.iter().zip(&cards) {
+            let imported = apply_row(&template, row).unwrap();
+            assert_eq!(serde_json::to_value(&imported).unwrap(), serde_json::to_value(original).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_read_rows_with_column_mapping() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("cards.csv");
+        std::fs::write(&path, "Spanish,hola,hello,3\nSpanish,gato,cat,\n").unwrap();
+
+        let columns = HashMap::from([
+            ("tag".to_string(), 0),
+            ("front".to_string(), 1),
+            ("back".to_string(), 2),
+            ("interval".to_string(), 3),
+        ]);
+        let rows = read_rows(&path, b',', false, Some(columns)).unwrap();
+        assert_eq!(rows.len(), 2);
+
+        let template = create_test_card("new");
+        let first = apply_row(&template, &rows[0]).unwrap();
+        assert_eq!(first.front, "hola");
+        assert_eq!(first.tags, BTreeSet::from(["Spanish".to_string()]));
+        assert_eq!(first.interval, 3);
+
+        // Empty cells keep the template value
+        let second = apply_row(&template, &rows[1]).unwrap();
+        assert_eq!(second.interval, template.interval);
+    }
+
+    #[test]
+    fn test_read_rows_with_legacy_tag_column() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("cards.csv");
+        std::fs::write(&path, "front,back,tag\nhola,hello,Spanish\n").unwrap();
+
+        let rows = read_rows(&path, b',', true, None).unwrap();
+        let imported = apply_row(&create_test_card("new"), &rows[0]).unwrap();
+        assert_eq!(imported.tags, BTreeSet::from(["Spanish".to_string()]));
+    }
+
+    #[test]
+    fn test_invalid_rows_and_mappings() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("cards.csv");
+        std::fs::write(&path, "front,back,interval\nq,a,soon\n").unwrap();
+
+        let rows = read_rows(&path, b',', true, None).unwrap();
+        let error = apply_row(&create_test_card("new"), &rows[0]).unwrap_err();
+        assert!(error.contains("interval"));
+
+        assert!(read_rows(&path, b',', false, None).is_err());
+        let unknown = HashMap::from([("front".to_string(), 0), ("colour".to_string(), 1)]);
+        assert!(read_rows(&path, b',', true, Some(unknown)).is_err());
+        let missing_back = HashMap::from([("front".to_string(), 0)]);
+        assert!(read_rows(&path, b',', true, Some(missing_back)).is_err());
+    }
+}
@@ -1,52 +1,309 @@
+use crate::anki_import;
+use crate::answer_time::{self, MAX_ANSWER_MS};
+use crate::backup::{self, BackupArchive};
+use crate::card_csv;
+use crate::clock::{Clock, SystemClock};
+use crate::daily_limits::{start_of_day, start_of_day_in, start_of_next_day, DailyLimits};
+use crate::decks;
+use crate::journal::{self, CardChange, Journal, Operation};
+use crate::load_balancer::ReviewLoad;
 use crate::models::{
-    AppSettings, BulkUpdateRequest, Card, CreateCardRequest, ReviewDifficulty, ReviewStats, SearchRequest, TagStats, UpdateCardRequest,
+    AnswerTimeReport, AppSettings, BackupInfo, BulkTagRequest, BulkUpdateRequest, Card, CardState, CreateCardRequest, CreateDeckRequest,
+    CsvExportRequest, CsvImportRequest, Deck, DeckCollection, DeckPreset, ForecastDay, ForecastRequest, ImportSummary, LeechAction,
+    MigrationPreview, MoveCardsRequest, PresetRequest, ReviewDifficulty, ReviewLog, ReviewStats, SearchRequest, SessionOrder,
+    SessionProgress, SessionSummary, SetDueRequest, ShiftDueRequest, SimulationRequest, SimulationResult, StorageRecoveryReport, TagMatch,
+    TagStats, UndoState, UpdateCardRequest,
 };
-use crate::spaced_repetition::SpacedRepetition;
+use crate::review_session::ReviewSession;
+use crate::simulator;
+use crate::spaced_repetition::{SpacedRepetition, LEECH_TAG, MAX_FORECAST_DAYS};
 use crate::storage::Storage;
-use chrono::Utc;
-use std::collections::HashMap;
-use std::sync::Mutex;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
 pub struct CardService {
     cards: Mutex<HashMap<String, Card>>,
     settings: Mutex<AppSettings>,
-    storage: Storage,
+    decks: Mutex<DeckCollection>,
+    storage: Box<dyn Storage>,
+    // Files that were damaged at startup, kept until the user has seen them
+    recovery_reports: Mutex<Vec<StorageRecoveryReport>>,
+    // Card mutations of this session that can be undone and redone
+    journal: Mutex<Journal>,
+    // Time used for scheduling; only tests and simulations replace the system clock
+    clock: Arc<dyn Clock>,
+    // The review session in progress, if any. Locked before any other lock.
+    session: Mutex<Option<ReviewSession>>,
 }
 
 impl CardService {
-    pub fn new(storage: Storage) -> Result<Self, Box<dyn std::error::Error>> {
-        let cards = storage.load_cards()?;
-        let settings = storage.load_settings().unwrap_or_default();
-        Ok(CardService {
+    pub fn new(storage: impl Storage + 'static) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_clock(storage, Arc::new(SystemClock))
+    }
+
+    /// Create a service that schedules cards by `clock` instead of the system time
+    pub fn with_clock(storage: impl Storage + 'static, clock: Arc<dyn Clock>) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut cards = storage.load_cards()?;
+        cards.values_mut().for_each(Card::infer_legacy_state);
+        // Damaged settings are salvaged and reported by the storage; any other failure must not
+        // let defaults overwrite the user's settings on the next save
+        let settings = storage.load_settings()?;
+        let decks = storage.load_decks()?;
+        let recovery_reports = storage.take_recovery_reports();
+        for report in &recovery_reports {
+            log::warn!(
+                "{} was corrupted ({}); recovered {} item(s), lost {}",
+                report.file,
+                report.error,
+                report.recovered_items,
+                report.lost_items
+            );
+        }
+
+        let service = CardService {
             cards: Mutex::new(cards),
             settings: Mutex::new(settings),
-            storage,
-        })
+            decks: Mutex::new(decks),
+            storage: Box::new(storage),
+            recovery_reports: Mutex::new(recovery_reports),
+            journal: Mutex::new(Journal::default()),
+            clock,
+            session: Mutex::new(None),
+        };
+
+        // A failed backup must not keep the app from starting
+        if let Err(e) = service.auto_backup() {
+            log::warn!("Automatic backup failed: {}", e);
+        }
+
+        Ok(service)
+    }
+
+    /// Get the data files that had to be recovered when the app started
+    pub fn get_storage_recovery_reports(&self) -> Result<Vec<StorageRecoveryReport>, String> {
+        let reports = self.recovery_reports.lock().map_err(|_| "Failed to lock recovery reports")?;
+        Ok(reports.clone())
+    }
+
+    /// Clear the recovery reports once the user has acknowledged them
+    pub fn dismiss_storage_recovery_reports(&self) -> Result<(), String> {
+        let mut reports = self.recovery_reports.lock().map_err(|_| "Failed to lock recovery reports")?;
+        reports.clear();
+        Ok(())
     }
 
     pub fn create_card(&self, request: CreateCardRequest) -> Result<Card, String> {
         let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        self.check_deck_exists(request.deck_id.as_deref())?;
+
+        let mut card = self.new_card(request.front, request.back, normalize_tags(request.tags));
+        card.deck_id = request.deck_id;
+        cards.insert(card.id.clone(), card.clone());
+        self.save_changed_cards(&cards, std::slice::from_ref(&card.id))?;
+        self.record_operation("Create card", vec![(card.id.clone(), None)], &cards, None)?;
+        Ok(card)
+    }
+
+    /// Import the notes of an Anki `.apkg` or `.colpkg` package. A note whose front matches an
+    /// existing card is merged into that card; empty notes and notes already present are skipped.
+    /// With `carry_scheduling`, newly imported cards keep their Anki interval, ease and due date, and
+    /// stay suspended or buried.
+    pub fn import_anki(&self, path: String, carry_scheduling: bool) -> Result<ImportSummary, String> {
+        let notes = anki_import::read_package(Path::new(&path)).map_err(|e| format!("Failed to read Anki package: {}", e))?;
+        // Anki unburies cards when its day rolls over, so buried cards come back at the start of the next day
+        let buried_until = start_of_next_day(self.clock.now());
+
+        let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let mut decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let deck_count = decks.decks.len();
+        let mut ids_by_front: HashMap<String, String> = cards.values().map(|card| (card.front.clone(), card.id.clone())).collect();
+        let mut summary = ImportSummary::default();
+        let mut changed_ids = Vec::new();
+        let mut previous_states = Vec::new();
+
+        for note in notes {
+            if note.front.is_empty() || note.back.is_empty() {
+                summary.skipped += 1;
+                continue;
+            }
+
+            // Notes go into a deck of the same name, created along with its parents when missing, and
+            // are tagged with the deck name as they were before decks existed
+            let deck_id = match note.deck.as_deref().map(|name| decks.ensure_deck(name)) {
+                Some(Ok(id)) => Some(id),
+                Some(Err(e)) => {
+                    log::warn!("Ignoring deck of note '{}': {}", note.front, e);
+                    None
+                }
+                None => None,
+            };
+
+            if let Some(card) = ids_by_front.get(&note.front).and_then(|id| cards.get_mut(id)) {
+                let deck_id = deck_id.or_else(|| card.deck_id.clone());
+                let has_deck_tag = note.deck.as_ref().map_or(true, |name| card.tags.contains(name));
+                if card.back == note.back && card.deck_id == deck_id && has_deck_tag {
+                    summary.skipped += 1;
+                } else {
+                    previous_states.push((card.id.clone(), Some(card.clone())));
+                    card.back = note.back;
+                    card.deck_id = deck_id;
+                    card.tags.extend(note.deck);
+                    changed_ids.push(card.id.clone());
+                    summary.merged += 1;
+                }
+                continue;
+            }
+
+            let mut card = self.new_card(note.front, note.back, note.deck.into_iter().collect());
+            card.deck_id = deck_id;
+            if let Some(scheduling) = note.scheduling.filter(|_| carry_scheduling) {
+                card.interval = scheduling.interval;
+                card.ease_factor = scheduling.ease_factor;
+                card.next_review = scheduling.next_review;
+                card.last_reviewed = Some(scheduling.next_review - Duration::days(scheduling.interval));
+                card.review_count = scheduling.review_count;
+                card.correct_count = scheduling.correct_count;
+                card.lapses = scheduling.lapses;
+                card.state = CardState::Review;
+            }
+            if carry_scheduling {
+                card.suspended = note.suspended;
+                card.buried_until = note.buried.then_some(buried_until);
+            }
+
+            ids_by_front.insert(card.front.clone(), card.id.clone());
+            changed_ids.push(card.id.clone());
+            previous_states.push((card.id.clone(), None));
+            cards.insert(card.id.clone(), card);
+            summary.imported += 1;
+        }
+
+        if decks.decks.len() != deck_count {
+            self.save_decks(&decks)?;
+        }
+        if !changed_ids.is_empty() {
+            self.save_changed_cards(&cards, &changed_ids)?;
+            let description = journal::describe("Import", changed_ids.len());
+            self.record_operation(&description, previous_states, &cards, None)?;
+        }
+
+        Ok(summary)
+    }
+
+    /// Import cards from a CSV or TSV file. Rows whose `id` matches an existing card update that card,
+    /// rows that repeat an existing front and back are skipped, and every other row becomes a new card.
+    pub fn import_csv(&self, request: CsvImportRequest) -> Result<ImportSummary, String> {
+        let path = Path::new(&request.path);
+        let delimiter = card_csv::delimiter_for(path, request.delimiter)?;
+        let rows = card_csv::read_rows(path, delimiter, request.has_headers, request.columns)
+            .map_err(|e| format!("Failed to read {}: {}", request.path, e))?;
+
+        let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let mut summary = ImportSummary::default();
+        let mut changed_ids = Vec::new();
+        let mut previous_states = Vec::new();
+
+        for (index, row) in rows.iter().enumerate() {
+            let existing = row.get("id").and_then(|id| cards.get(id.trim()));
+            let template = existing
+                .cloned()
+                .unwrap_or_else(|| self.new_card(String::new(), String::new(), BTreeSet::new()));
+
+            let mut card = match card_csv::apply_row(&template, row) {
+                Ok(mut card) if !card.front.is_empty() && !card.back.is_empty() && !card.id.is_empty() => {
+                    card.infer_legacy_state();
+                    card
+                }
+                Ok(_) => {
+                    log::warn!("Skipping row {}: front and back are required", index + 1);
+                    summary.skipped += 1;
+                    continue;
+                }
+                Err(e) => {
+                    log::warn!("Skipping row {}: {}", index + 1, e);
+                    summary.skipped += 1;
+                    continue;
+                }
+            };
+
+            // Deck ids from another collection mean nothing here
+            if card.deck_id.as_deref().is_some_and(|id| decks.deck(id).is_none()) {
+                log::warn!("Row {} refers to an unknown deck; importing it outside any deck", index + 1);
+                card.deck_id = None;
+            }
+
+            if existing.is_some() {
+                summary.merged += 1;
+            } else if cards.values().any(|c| c.front == card.front && c.back == card.back) {
+                summary.skipped += 1;
+                continue;
+            } else {
+                summary.imported += 1;
+            }
+
+            changed_ids.push(card.id.clone());
+            previous_states.push((card.id.clone(), cards.get(&card.id).cloned()));
+            cards.insert(card.id.clone(), card);
+        }
 
-        let card = Card {
+        if !changed_ids.is_empty() {
+            self.save_changed_cards(&cards, &changed_ids)?;
+            let description = journal::describe("Import", changed_ids.len());
+            self.record_operation(&description, previous_states, &cards, None)?;
+        }
+
+        Ok(summary)
+    }
+
+    /// Export cards with every field to a CSV or TSV file, returning the number of cards written
+    pub fn export_csv(&self, request: CsvExportRequest) -> Result<usize, String> {
+        let path = Path::new(&request.path);
+        let delimiter = card_csv::delimiter_for(path, request.delimiter)?;
+
+        let cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let mut selected: Vec<Card> = match &request.card_ids {
+            Some(ids) => ids.iter().filter_map(|id| cards.get(id).cloned()).collect(),
+            None => cards.values().cloned().collect(),
+        };
+        selected.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+
+        let file = std::fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", request.path, e))?;
+        card_csv::write_cards(file, &selected, delimiter).map_err(|e| format!("Failed to write {}: {}", request.path, e))?;
+
+        Ok(selected.len())
+    }
+
+    fn new_card(&self, front: String, back: String, tags: BTreeSet<String>) -> Card {
+        let now = self.clock.now();
+        Card {
             id: Uuid::new_v4().to_string(),
-            front: request.front,
-            back: request.back,
-            tag: request.tag,
-            created_at: Utc::now(),
+            front,
+            back,
+            tags,
+            deck_id: None,
+            created_at: now,
             last_reviewed: None,
-            next_review: Utc::now(), // Available immediately for first review
+            next_review: now, // Available immediately for first review
             interval: 0,
             ease_factor: 2.5, // SM-2 default
             review_count: 0,
             correct_count: 0,
+            lapses: 0,
+            last_answer_ms: None,
             leitner_box: 0,
             exponential_factor: 1.0,
-        };
-
-        cards.insert(card.id.clone(), card.clone());
-        self.save_cards(&cards)?;
-        Ok(card)
+            fsrs_stability: 0.0,
+            fsrs_difficulty: 0.0,
+            scheduler_state: BTreeMap::new(),
+            state: CardState::New,
+            learning_step: 0,
+            suspended: false,
+            buried_until: None,
+        }
     }
 
     pub fn get_cards(&self) -> Result<Vec<Card>, String> {
@@ -61,14 +318,18 @@ impl CardService {
 
     pub fn update_card(&self, id: String, request: UpdateCardRequest) -> Result<Card, String> {
         let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        self.check_deck_exists(request.deck_id.as_deref())?;
 
         if let Some(card) = cards.get_mut(&id) {
+            let previous = card.clone();
             card.front = request.front;
             card.back = request.back;
-            card.tag = request.tag;
+            card.tags = normalize_tags(request.tags);
+            card.deck_id = request.deck_id;
 
             let updated_card = card.clone();
-            self.save_cards(&cards)?;
+            self.save_changed_cards(&cards, std::slice::from_ref(&id))?;
+            self.record_operation("Edit card", vec![(id, Some(previous))], &cards, None)?;
             Ok(updated_card)
         } else {
             Err("Card not found".to_string())
@@ -78,51 +339,271 @@ impl CardService {
     pub fn delete_card(&self, id: String) -> Result<(), String> {
         let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
 
-        if cards.remove(&id).is_some() {
-            self.save_cards(&cards)?;
+        if let Some(previous) = cards.remove(&id) {
+            self.save_deleted_cards(&cards, std::slice::from_ref(&id))?;
+            self.record_operation("Delete card", vec![(id, Some(previous))], &cards, None)?;
             Ok(())
         } else {
             Err("Card not found".to_string())
         }
     }
 
+    /// Due cards that still fit in today's new-card and review limits
     pub fn get_due_cards(&self) -> Result<Vec<Card>, String> {
         let cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
-        Ok(SpacedRepetition::get_due_cards(&cards))
+        self.limited_due_cards(&cards)
     }
 
-    pub fn review_card(&self, id: String, difficulty: ReviewDifficulty) -> Result<Card, String> {
-        let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+    fn limited_due_cards(&self, cards: &HashMap<String, Card>) -> Result<Vec<Card>, String> {
+        let decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
         let settings = self.settings.lock().map_err(|_| "Failed to lock settings")?;
 
-        if let Some(card) = cards.get_mut(&id) {
-            let (new_interval, new_ease_factor, next_review, new_leitner_box, new_exponential_factor) =
-                SpacedRepetition::calculate_next_review(card, &difficulty, &settings);
-
-            card.last_reviewed = Some(Utc::now());
-            card.next_review = next_review;
-            card.interval = new_interval;
-            card.ease_factor = new_ease_factor;
-            card.leitner_box = new_leitner_box;
-            card.exponential_factor = new_exponential_factor;
-            card.review_count += 1;
-
-            // Increment correct count for Good and Easy responses
-            if matches!(difficulty, ReviewDifficulty::Good | ReviewDifficulty::Easy) {
-                card.correct_count += 1;
-            }
+        let now = self.clock.now();
+        let day_start = start_of_day(now);
+        let todays_log = self
+            .storage
+            .load_review_log_since(day_start)
+            .map_err(|e| format!("Failed to load review log: {}", e))?;
 
-            let updated_card = card.clone();
-            self.save_cards(&cards)?;
-            Ok(updated_card)
+        let limits = DailyLimits::new(&settings, &decks, cards, &todays_log);
+        Ok(limits.apply(SpacedRepetition::get_due_cards(cards, now), now))
+    }
+
+    /// Answer a card. `time_taken_ms`, when the frontend reports it, is kept on the card and in the
+    /// review history, and may schedule a slow Good answer as Hard. The history keeps the answer given.
+    pub fn review_card(&self, id: String, difficulty: ReviewDifficulty, time_taken_ms: Option<u64>) -> Result<Card, String> {
+        let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let global_settings = self.settings.lock().map_err(|_| "Failed to lock settings")?;
+
+        let deck_id = cards.get(&id).ok_or("Card not found")?.deck_id.clone();
+        let settings = decks.options_for(deck_id.as_deref(), &global_settings);
+        let time_taken_ms = time_taken_ms.map(|ms| ms.min(MAX_ANSWER_MS));
+        let scheduled_as = answer_time::effective_difficulty(difficulty.clone(), time_taken_ms, settings);
+        let now = self.clock.now();
+        // Only load balancing looks at the due dates of the other cards. Suspended and buried cards
+        // are not reviewed on their due date, so they add no load.
+        let load = if settings.load_balancing {
+            ReviewLoad::from_cards(
+                cards
+                    .values()
+                    .filter(|card| card.id != id && !card.suspended && !card.is_buried(now)),
+            )
         } else {
-            Err("Card not found".to_string())
+            ReviewLoad::default()
+        };
+
+        let card = cards.get_mut(&id).ok_or("Card not found")?;
+        let previous = card.clone();
+        SpacedRepetition::answer_card(card, &scheduled_as, settings, now, &load);
+        if scheduled_as != difficulty && matches!(difficulty, ReviewDifficulty::Good | ReviewDifficulty::Easy) {
+            // A slow Good is scheduled as Hard but was still remembered
+            card.correct_count += 1;
+        }
+        card.last_answer_ms = time_taken_ms;
+        if card.lapses > previous.lapses && SpacedRepetition::is_leech_lapse(card.lapses, settings.leech_threshold) {
+            card.tags.insert(LEECH_TAG.to_string());
+            card.suspended |= settings.leech_action == LeechAction::Suspend;
+        }
+
+        let log_entry = ReviewLog {
+            id: Uuid::new_v4().to_string(),
+            card_id: card.id.clone(),
+            reviewed_at: now,
+            difficulty: difficulty.clone(),
+            previous_interval: previous.interval,
+            new_interval: card.interval,
+            previous_ease_factor: previous.ease_factor,
+            new_ease_factor: card.ease_factor,
+            algorithm: settings.algorithm.clone(),
+            time_taken_ms,
+            was_new: previous.review_count == 0,
+            learning: previous.state.is_learning(),
+            scheduled_as: (scheduled_as != difficulty).then_some(scheduled_as),
+        };
+
+        let updated_card = card.clone();
+        self.save_changed_cards(&cards, std::slice::from_ref(&id))?;
+        self.storage
+            .append_review_log(&log_entry)
+            .map_err(|e| format!("Failed to save review log: {}", e))?;
+        self.record_operation("Review card", vec![(id, Some(previous))], &cards, Some(log_entry))?;
+        Ok(updated_card)
+    }
+
+    // Review session methods
+    /// Start a session over today's due cards in the requested order, replacing any session in progress
+    pub fn start_review_session(&self, order: SessionOrder) -> Result<SessionProgress, String> {
+        let due_cards = self.get_due_cards()?;
+        let mut session = self.session.lock().map_err(|_| "Failed to lock review session")?;
+        let session = session.insert(ReviewSession::new(due_cards, order, self.clock.now()));
+        self.session_progress(session)
+    }
+
+    /// The card to show now in the session in progress
+    pub fn next_session_card(&self) -> Result<SessionProgress, String> {
+        let mut session = self.session.lock().map_err(|_| "Failed to lock review session")?;
+        self.session_progress(session.as_mut().ok_or("No review session in progress")?)
+    }
+
+    /// Answer the current card of the session and move on to the next one. `card_id` must be the
+    /// current card, so an answer sent twice is not applied to the next card.
+    pub fn answer_session_card(
+        &self,
+        card_id: String,
+        difficulty: ReviewDifficulty,
+        time_taken_ms: Option<u64>,
+    ) -> Result<SessionProgress, String> {
+        let mut session = self.session.lock().map_err(|_| "Failed to lock review session")?;
+        let session = session.as_mut().ok_or("No review session in progress")?;
+        if session.current() != Some(card_id.as_str()) {
+            return Err("Card is not the current card of the review session".to_string());
+        }
+
+        let card = self.review_card(card_id, difficulty.clone(), time_taken_ms)?;
+        session.record_answer(&card, difficulty, self.clock.now());
+        self.session_progress(session)
+    }
+
+    /// Undo the last answer of the session, which must be the last change made, and show its card again
+    pub fn undo_session_answer(&self) -> Result<SessionProgress, String> {
+        let mut session = self.session.lock().map_err(|_| "Failed to lock review session")?;
+        let session = session.as_mut().ok_or("No review session in progress")?;
+        let card_id = session.last_answered().ok_or("No answer to undo")?;
+
+        let last_change_is_answer = {
+            let journal = self.journal.lock().map_err(|_| "Failed to lock journal")?;
+            journal
+                .last_undo()
+                .and_then(|operation| operation.review_log_entry.as_ref())
+                .is_some_and(|entry| entry.card_id == card_id)
+        };
+        if !last_change_is_answer {
+            return Err("The last change was not an answer in this review session".to_string());
+        }
+
+        self.undo()?;
+        session.undo_answer();
+        self.session_progress(session)
+    }
+
+    /// End the session in progress and sum up its answers
+    pub fn finish_review_session(&self) -> Result<SessionSummary, String> {
+        let mut session = self.session.lock().map_err(|_| "Failed to lock review session")?;
+        let session = session.take().ok_or("No review session in progress")?;
+        Ok(session.summary(self.clock.now()))
+    }
+
+    /// Progress of `session`, skipping cards deleted since they were queued
+    fn session_progress(&self, session: &mut ReviewSession) -> Result<SessionProgress, String> {
+        let cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let card = loop {
+            // Cards deleted, suspended or buried since the session started are passed over
+            match session.current() {
+                Some(id) => match cards.get(id).filter(|card| !card.suspended && !card.is_buried(self.clock.now())) {
+                    Some(card) => break Some(card.clone()),
+                    None => session.skip_current(),
+                },
+                None => break None,
+            }
+        };
+
+        Ok(SessionProgress {
+            card,
+            answered: session.answered(),
+            remaining: session.remaining(),
+        })
+    }
+
+    /// Get the review history, optionally limited to a single card
+    pub fn get_review_log(&self, card_id: Option<String>) -> Result<Vec<ReviewLog>, String> {
+        let mut entries = self
+            .storage
+            .load_review_log()
+            .map_err(|e| format!("Failed to load review log: {}", e))?;
+
+        if let Some(card_id) = card_id {
+            entries.retain(|entry| entry.card_id == card_id);
         }
+
+        Ok(entries)
+    }
+
+    /// Average and median answer times overall, per card and per tag, from the review history
+    pub fn get_answer_time_stats(&self) -> Result<AnswerTimeReport, String> {
+        let log = self
+            .storage
+            .load_review_log()
+            .map_err(|e| format!("Failed to load review log: {}", e))?;
+        let cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        Ok(answer_time::answer_time_report(&cards, &log))
     }
 
     pub fn get_review_stats(&self) -> Result<ReviewStats, String> {
         let cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
-        Ok(SpacedRepetition::calculate_stats(&cards))
+        let mut stats = SpacedRepetition::calculate_stats(&cards, self.clock.now());
+
+        let due_today = self.limited_due_cards(&cards)?;
+        stats.new_cards_remaining = due_today.iter().filter(|card| card.review_count == 0).count();
+        stats.reviews_remaining = due_today.len() - stats.new_cards_remaining;
+        Ok(stats)
+    }
+
+    /// Cards due on each of the coming days, for the whole collection or for one tag or deck
+    pub fn get_review_forecast(&self, request: ForecastRequest) -> Result<Vec<ForecastDay>, String> {
+        if request.days == 0 || request.days > MAX_FORECAST_DAYS {
+            return Err(format!("Days must be between 1 and {}", MAX_FORECAST_DAYS));
+        }
+
+        let cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let deck_ids = match &request.deck_id {
+            Some(deck_id) => Some(self.decks.lock().map_err(|_| "Failed to lock decks")?.subtree_ids(deck_id)),
+            None => None,
+        };
+        let selected = cards.values().filter(|card| {
+            request.tag.as_ref().map_or(true, |tag| card.tags.contains(tag))
+                && deck_ids
+                    .as_ref()
+                    .map_or(true, |deck_ids| card.deck_id.as_ref().is_some_and(|id| deck_ids.contains(id)))
+        });
+
+        Ok(SpacedRepetition::forecast(selected, request.days, self.clock.now()))
+    }
+
+    /// Project the workload of the cards in a deck, or of every card, under each algorithm,
+    /// starting today with the deck's current scheduling state and settings
+    pub fn simulate_schedules(&self, request: SimulationRequest) -> Result<Vec<SimulationResult>, String> {
+        if request.days == 0 || request.days > simulator::MAX_SIMULATION_DAYS {
+            return Err(format!("Days must be between 1 and {}", simulator::MAX_SIMULATION_DAYS));
+        }
+
+        let cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let global_settings = self.settings.lock().map_err(|_| "Failed to lock settings")?;
+
+        let selected: Vec<&Card> = match &request.deck_id {
+            Some(deck_id) => {
+                if decks.deck(deck_id).is_none() {
+                    return Err("Deck not found".to_string());
+                }
+                let deck_ids = decks.subtree_ids(deck_id);
+                cards
+                    .values()
+                    .filter(|card| card.deck_id.as_ref().is_some_and(|id| deck_ids.contains(id)))
+                    .collect()
+            }
+            None => cards.values().collect(),
+        };
+        // Suspended cards are not studied, so they add nothing to the workload. Each card is
+        // scheduled with the options of its own deck, as when it is reviewed.
+        let selected: Vec<(&Card, &AppSettings)> = selected
+            .into_iter()
+            .filter(|card| !card.suspended)
+            .map(|card| (card, decks.options_for(card.deck_id.as_deref(), &global_settings)))
+            .collect();
+        let settings = decks.options_for(request.deck_id.as_deref(), &global_settings);
+
+        Ok(simulator::simulate_algorithms(&selected, settings, request.days, self.clock.now()))
     }
 
     // Organization and search methods
@@ -130,6 +611,12 @@ impl CardService {
         let cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
         let mut filtered_cards: Vec<Card> = cards.values().cloned().collect();
 
+        // Filter by deck, including nested decks
+        if let Some(deck_id) = &request.deck_id {
+            let deck_ids = self.decks.lock().map_err(|_| "Failed to lock decks")?.subtree_ids(deck_id);
+            filtered_cards.retain(|card| card.deck_id.as_ref().is_some_and(|id| deck_ids.contains(id)));
+        }
+
         // Filter by query (searches front and back text)
         if let Some(query) = &request.query {
             let query_lower = query.to_lowercase();
@@ -137,9 +624,12 @@ impl CardService {
                 .retain(|card| card.front.to_lowercase().contains(&query_lower) || card.back.to_lowercase().contains(&query_lower));
         }
 
-        // Filter by tag
-        if let Some(tag) = &request.tag {
-            filtered_cards.retain(|card| card.tag.as_ref().map_or(false, |c| c == tag));
+        // Filter by tags
+        if let Some(tags) = request.tags.as_ref().filter(|tags| !tags.is_empty()) {
+            filtered_cards.retain(|card| match request.tag_match {
+                TagMatch::Any => tags.iter().any(|tag| card.tags.contains(tag)),
+                TagMatch::All => tags.iter().all(|tag| card.tags.contains(tag)),
+            });
         }
 
         Ok(filtered_cards)
@@ -147,33 +637,42 @@ impl CardService {
 
     pub fn get_tags(&self) -> Result<Vec<String>, String> {
         let cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
-        let mut tags: Vec<String> = cards
-            .values()
-            .filter_map(|card| card.tag.clone())
-            .collect::<std::collections::HashSet<_>>()
-            .into_iter()
-            .collect();
+        let tags: BTreeSet<String> = cards.values().flat_map(|card| card.tags.iter().cloned()).collect();
+        Ok(tags.into_iter().collect())
+    }
 
-        tags.sort();
-        Ok(tags)
+    /// Cards tagged as leeches, most lapses first. Removing the tag, e.g. after rewriting the card,
+    /// takes a card off the list.
+    pub fn get_leeches(&self) -> Result<Vec<Card>, String> {
+        let cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let mut leeches: Vec<Card> = cards.values().filter(|card| card.tags.contains(LEECH_TAG)).cloned().collect();
+        leeches.sort_by(|a, b| b.lapses.cmp(&a.lapses).then_with(|| a.front.cmp(&b.front)));
+        Ok(leeches)
     }
 
     pub fn get_tag_stats(&self) -> Result<Vec<TagStats>, String> {
         let cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
         let mut tag_map: HashMap<String, Vec<Card>> = HashMap::new();
 
-        // Group cards by tag
+        // Group cards by tag; a card with several tags counts towards each of them
         for card in cards.values() {
-            let tag = card.tag.clone().unwrap_or_else(|| "Uncategorized".to_string());
-            tag_map.entry(tag).or_insert_with(Vec::new).push(card.clone());
+            if card.tags.is_empty() {
+                tag_map.entry("Uncategorized".to_string()).or_default().push(card.clone());
+            }
+            for tag in &card.tags {
+                tag_map.entry(tag.clone()).or_default().push(card.clone());
+            }
         }
 
+        let now = self.clock.now();
         let mut stats: Vec<TagStats> = tag_map
             .into_iter()
             .map(|(name, cards)| {
-                let due_cards = SpacedRepetition::get_due_cards_from_vec(&cards);
+                let due_cards = SpacedRepetition::get_due_cards_from_vec(&cards, now);
                 let new_cards = cards.iter().filter(|c| c.review_count == 0).count();
                 let mature_cards = cards.iter().filter(|c| c.review_count >= 5).count();
+                let suspended_cards = cards.iter().filter(|c| c.suspended).count();
+                let buried_cards = cards.iter().filter(|c| !c.suspended && c.is_buried(now)).count();
 
                 TagStats {
                     name,
@@ -181,6 +680,8 @@ impl CardService {
                     cards_due: due_cards.len(),
                     cards_new: new_cards,
                     cards_mature: mature_cards,
+                    cards_suspended: suspended_cards,
+                    cards_buried: buried_cards,
                 }
             })
             .collect();
@@ -189,19 +690,168 @@ impl CardService {
         Ok(stats)
     }
 
+    /// Replace the tags of every given card
     pub fn bulk_update_tag(&self, request: BulkUpdateRequest) -> Result<Vec<Card>, String> {
+        let tags = normalize_tags(request.tags);
+        self.bulk_edit_tags(&request.card_ids, "Change tags of", |card_tags| {
+            if *card_tags == tags {
+                return false;
+            }
+            *card_tags = tags.clone();
+            true
+        })
+    }
+
+    /// Add a tag to every given card, returning the cards that did not have it yet
+    pub fn bulk_add_tag(&self, request: BulkTagRequest) -> Result<Vec<Card>, String> {
+        let tag = request.tag.trim().to_string();
+        if tag.is_empty() {
+            return Err("Tag cannot be empty".to_string());
+        }
+        self.bulk_edit_tags(&request.card_ids, "Add tag to", |card_tags| card_tags.insert(tag.clone()))
+    }
+
+    /// Remove a tag from every given card, returning the cards that had it
+    pub fn bulk_remove_tag(&self, request: BulkTagRequest) -> Result<Vec<Card>, String> {
+        let tag = request.tag.trim();
+        self.bulk_edit_tags(&request.card_ids, "Remove tag from", |card_tags| card_tags.remove(tag))
+    }
+
+    /// Apply `edit` to the tags of each given card; `edit` returns whether it changed anything
+    fn bulk_edit_tags(
+        &self,
+        card_ids: &[String],
+        action: &str,
+        mut edit: impl FnMut(&mut BTreeSet<String>) -> bool,
+    ) -> Result<Vec<Card>, String> {
+        self.bulk_edit_cards(card_ids, action, |card| edit(&mut card.tags))
+    }
+
+    /// Leave cards out of reviews until they are unsuspended, returning the cards that were not suspended yet
+    pub fn suspend_cards(&self, card_ids: Vec<String>) -> Result<Vec<Card>, String> {
+        self.bulk_edit_cards(&card_ids, "Suspend", |card| !std::mem::replace(&mut card.suspended, true))
+    }
+
+    /// Return suspended cards to reviews, returning the cards that were suspended
+    pub fn unsuspend_cards(&self, card_ids: Vec<String>) -> Result<Vec<Card>, String> {
+        self.bulk_edit_cards(&card_ids, "Unsuspend", |card| std::mem::replace(&mut card.suspended, false))
+    }
+
+    /// Leave cards out of reviews for the rest of the local day, returning the cards that were not buried yet
+    pub fn bury_cards(&self, card_ids: Vec<String>) -> Result<Vec<Card>, String> {
+        let now = self.clock.now();
+        let until = start_of_next_day(now);
+        self.bulk_edit_cards(&card_ids, "Bury", |card| {
+            if card.is_buried(now) {
+                return false;
+            }
+            card.buried_until = Some(until);
+            true
+        })
+    }
+
+    /// Return buried cards to today's reviews, returning the cards that were buried
+    pub fn unbury_cards(&self, card_ids: Vec<String>) -> Result<Vec<Card>, String> {
+        let now = self.clock.now();
+        self.bulk_edit_cards(&card_ids, "Unbury", |card| {
+            card.is_buried(now) && card.buried_until.take().is_some()
+        })
+    }
+
+    // Reschedule methods
+    /// Make cards new again, returning the cards that were not new yet
+    pub fn reset_cards(&self, card_ids: Vec<String>) -> Result<Vec<Card>, String> {
+        let now = self.clock.now();
+        self.bulk_edit_cards(&card_ids, "Reset", |card| {
+            if card.state == CardState::New && card.review_count == 0 {
+                return false;
+            }
+            SpacedRepetition::reset_to_new(card, now);
+            true
+        })
+    }
+
+    /// Treat reviewed cards as forgotten, due now with their scheduling started over; new cards are skipped
+    pub fn forget_cards(&self, card_ids: Vec<String>) -> Result<Vec<Card>, String> {
+        let now = self.clock.now();
+        self.bulk_edit_cards(&card_ids, "Forget", |card| {
+            SpacedRepetition::forget(card, now);
+            card.review_count > 0
+        })
+    }
+
+    /// Make cards due at the start of the days in the requested range, spread evenly over them in
+    /// the order they were due. Their intervals are kept.
+    pub fn set_due_date(&self, request: SetDueRequest) -> Result<Vec<Card>, String> {
+        if request.from_days > request.to_days {
+            return Err("The first day must not be after the last day".to_string());
+        }
+
+        let ordered_ids: Vec<String> = {
+            let cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+            let mut selected: Vec<&Card> = request.card_ids.iter().filter_map(|id| cards.get(id)).collect();
+            selected.sort_by(|a, b| a.next_review.cmp(&b.next_review).then_with(|| a.id.cmp(&b.id)));
+            selected.into_iter().map(|card| card.id.clone()).collect()
+        };
+
+        let now = self.clock.now();
+        let span = (request.to_days - request.from_days) as usize + 1;
+        let due_dates: HashMap<String, DateTime<Utc>> = ordered_ids
+            .iter()
+            .enumerate()
+            .map(|(index, id)| {
+                let days = request.from_days as usize + index * span / ordered_ids.len();
+                (id.clone(), start_of_day_in(now, days as i64))
+            })
+            .collect();
+
+        self.bulk_edit_cards(&ordered_ids, "Set due date of", |card| {
+            let due = due_dates[&card.id];
+            if card.next_review == due {
+                return false;
+            }
+            card.next_review = due;
+            true
+        })
+    }
+
+    /// Move the due dates of scheduled cards by a number of days, e.g. to push them past a vacation.
+    /// New cards are not scheduled yet and are skipped; returns the cards that moved.
+    pub fn shift_due_dates(&self, request: ShiftDueRequest) -> Result<Vec<Card>, String> {
+        if request.days == 0 {
+            return Ok(Vec::new());
+        }
+        let shift = Duration::days(request.days);
+        self.bulk_edit_cards(&request.card_ids, "Reschedule", |card| {
+            if card.state == CardState::New {
+                return false;
+            }
+            card.next_review += shift;
+            true
+        })
+    }
+
+    /// Apply `edit` to each given card; `edit` returns whether it changed anything
+    fn bulk_edit_cards(&self, card_ids: &[String], action: &str, mut edit: impl FnMut(&mut Card) -> bool) -> Result<Vec<Card>, String> {
         let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
         let mut updated_cards = Vec::new();
+        let mut previous_states = Vec::new();
 
-        for card_id in &request.card_ids {
+        for card_id in card_ids {
             if let Some(card) = cards.get_mut(card_id) {
-                card.tag = request.tag.clone();
-                updated_cards.push(card.clone());
+                let previous = card.clone();
+                if edit(card) {
+                    previous_states.push((card_id.clone(), Some(previous)));
+                    updated_cards.push(card.clone());
+                }
             }
         }
 
         if !updated_cards.is_empty() {
-            self.save_cards(&cards)?;
+            let updated_ids: Vec<String> = updated_cards.iter().map(|card| card.id.clone()).collect();
+            self.save_changed_cards(&cards, &updated_ids)?;
+            let description = journal::describe(action, updated_cards.len());
+            self.record_operation(&description, previous_states, &cards, None)?;
         }
 
         Ok(updated_cards)
@@ -209,302 +859,2141 @@ impl CardService {
 
     pub fn delete_multiple_cards(&self, card_ids: Vec<String>) -> Result<(), String> {
         let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
-        let mut deleted_count = 0;
+        let mut deleted_ids = Vec::new();
+        let mut previous_states = Vec::new();
 
         for card_id in card_ids {
-            if cards.remove(&card_id).is_some() {
-                deleted_count += 1;
+            if let Some(previous) = cards.remove(&card_id) {
+                deleted_ids.push(card_id.clone());
+                previous_states.push((card_id, Some(previous)));
             }
         }
 
-        if deleted_count > 0 {
-            self.save_cards(&cards)?;
+        if !deleted_ids.is_empty() {
+            self.save_deleted_cards(&cards, &deleted_ids)?;
+            let description = journal::describe("Delete", deleted_ids.len());
+            self.record_operation(&description, previous_states, &cards, None)?;
         }
 
         Ok(())
     }
 
-    // Settings management methods
-    pub fn get_settings(&self) -> Result<AppSettings, String> {
-        let settings = self.settings.lock().map_err(|_| "Failed to lock settings")?;
-        Ok(settings.clone())
+    // Deck methods
+    /// All decks sorted by name, so nested decks follow their parent
+    pub fn get_decks(&self) -> Result<Vec<Deck>, String> {
+        let decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let mut result = decks.decks.clone();
+        result.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(result)
     }
 
-    pub fn update_settings(&self, new_settings: AppSettings) -> Result<AppSettings, String> {
-        let mut settings = self.settings.lock().map_err(|_| "Failed to lock settings")?;
-        *settings = new_settings.clone();
-        self.save_settings(&settings)?;
-        Ok(new_settings)
+    pub fn create_deck(&self, request: CreateDeckRequest) -> Result<Deck, String> {
+        let mut decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let name = decks::normalize_deck_name(&request.name)?;
+        if decks.deck_by_name(&name).is_some() {
+            return Err(format!("Deck '{}' already exists", name));
+        }
+        if let Some(preset_id) = request.preset_id.as_deref().filter(|id| decks.preset(id).is_none()) {
+            return Err(format!("Preset {} not found", preset_id));
+        }
+
+        let id = decks.ensure_deck(&name)?;
+        let deck = decks.decks.iter_mut().find(|deck| deck.id == id).ok_or("Deck not found")?;
+        deck.preset_id = request.preset_id;
+        let deck = deck.clone();
+
+        self.save_decks(&decks)?;
+        Ok(deck)
     }
 
-    // Helper method to save cards
-    fn save_cards(&self, cards: &HashMap<String, Card>) -> Result<(), String> {
-        self.storage.save_cards(cards).map_err(|e| format!("Failed to save cards: {}", e))
+    /// Rename a deck; decks nested below it move along with it
+    pub fn rename_deck(&self, id: String, name: String) -> Result<Deck, String> {
+        let mut decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let new_name = decks::normalize_deck_name(&name)?;
+        let old_name = decks.deck(&id).ok_or("Deck not found")?.name.clone();
+        if new_name == old_name {
+            return decks.deck(&id).cloned().ok_or_else(|| "Deck not found".to_string());
+        }
+        if decks::is_within(&new_name, &old_name) {
+            return Err("A deck cannot be moved into itself".to_string());
+        }
+        if decks.deck_by_name(&new_name).is_some() {
+            return Err(format!("Deck '{}' already exists", new_name));
+        }
+
+        for deck in decks.decks.iter_mut().filter(|deck| decks::is_within(&deck.name, &old_name)) {
+            deck.name = format!("{}{}", new_name, &deck.name[old_name.len()..]);
+        }
+        // The new location may need parent decks that do not exist yet
+        if let Some(parent) = decks::parent_name(&new_name) {
+            decks.ensure_deck(parent)?;
+        }
+
+        self.save_decks(&decks)?;
+        decks.deck(&id).cloned().ok_or_else(|| "Deck not found".to_string())
     }
 
-    // Helper method to save settings
-    fn save_settings(&self, settings: &AppSettings) -> Result<(), String> {
-        self.storage
-            .save_settings(settings)
-            .map_err(|e| format!("Failed to save settings: {}", e))
+    /// Delete a deck and the decks nested below it. Their cards are kept and move to the
+    /// parent of the deleted deck, or out of every deck for a top-level deck.
+    pub fn delete_deck(&self, id: String) -> Result<(), String> {
+        let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let mut decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let deck = decks.deck(&id).cloned().ok_or("Deck not found")?;
+        let removed_ids = decks.subtree_ids(&id);
+        let parent_id = decks::parent_name(&deck.name)
+            .and_then(|parent| decks.deck_by_name(parent))
+            .map(|parent| parent.id.clone());
+
+        let mut updated = decks.clone();
+        updated.decks.retain(|deck| !removed_ids.contains(&deck.id));
+
+        // Cards taking the options of the parent deck may change algorithm
+        let settings = self.settings.lock().map_err(|_| "Failed to lock settings")?;
+        let now = self.clock.now();
+        let mut moved_ids = Vec::new();
+        for card in cards.values_mut() {
+            if card.deck_id.as_ref().is_some_and(|deck_id| removed_ids.contains(deck_id)) {
+                let old_options = decks.options_for(card.deck_id.as_deref(), &settings);
+                let new_options = updated.options_for(parent_id.as_deref(), &settings);
+                if let Some(migrated) = Self::migrated_card(card, old_options, new_options, now) {
+                    *card = migrated;
+                }
+                card.deck_id = parent_id.clone();
+                moved_ids.push(card.id.clone());
+            }
+        }
+        *decks = updated;
+
+        if !moved_ids.is_empty() {
+            self.save_changed_cards(&cards, &moved_ids)?;
+        }
+        self.save_decks(&decks)?;
+
+        // Undoing earlier operations could put cards back into a deck that no longer exists
+        self.journal.lock().map_err(|_| "Failed to lock journal")?.clear();
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::models::*;
-    use serial_test::serial;
-    use tempfile::TempDir;
+    /// Use a preset for a deck, or inherit the options of its parent when `preset_id` is not set.
+    /// Cards whose algorithm changes with it are migrated, as when the global algorithm changes.
+    pub fn set_deck_preset(&self, id: String, preset_id: Option<String>) -> Result<Deck, String> {
+        let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let mut decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let settings = self.settings.lock().map_err(|_| "Failed to lock settings")?;
 
-    // Create a test storage instance
-    fn create_test_storage() -> (Storage, TempDir) {
-        let temp_dir = TempDir::new().unwrap();
-        let data_file = temp_dir.path().join("test_cards.json");
-        let storage = Storage::new_with_path(data_file);
-        (storage, temp_dir)
+        let updated = Self::with_deck_preset(&decks, &id, preset_id)?;
+        let migrated = self.migrated_cards(&cards, (&decks, &settings), (&updated, &settings));
+        self.apply_migration(&mut cards, migrated)?;
+
+        *decks = updated;
+        self.save_decks(&decks)?;
+        decks.deck(&id).cloned().ok_or_else(|| "Deck not found".to_string())
     }
 
-    // Create a test card service
-    fn create_test_service() -> (CardService, TempDir) {
-        let (storage, temp_dir) = create_test_storage();
-        let service = CardService::new(storage).unwrap();
-        (service, temp_dir)
+    /// What `set_deck_preset` would do to the cards, without changing anything
+    pub fn preview_deck_preset(&self, id: String, preset_id: Option<String>) -> Result<MigrationPreview, String> {
+        let cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let settings = self.settings.lock().map_err(|_| "Failed to lock settings")?;
+
+        let updated = Self::with_deck_preset(&decks, &id, preset_id)?;
+        let migrated = self.migrated_cards(&cards, (&decks, &settings), (&updated, &settings));
+        Ok(self.migration_preview(&cards, migrated))
     }
 
-    // Create test card request
-    fn create_test_request(front: &str, back: &str, tag: Option<&str>) -> CreateCardRequest {
-        CreateCardRequest {
-            front: front.to_string(),
-            back: back.to_string(),
-            tag: tag.map(|c| c.to_string()),
+    fn with_deck_preset(decks: &DeckCollection, id: &str, preset_id: Option<String>) -> Result<DeckCollection, String> {
+        if let Some(preset_id) = preset_id.as_deref().filter(|preset_id| decks.preset(preset_id).is_none()) {
+            return Err(format!("Preset {} not found", preset_id));
         }
+
+        let mut updated = decks.clone();
+        let deck = updated.decks.iter_mut().find(|deck| deck.id == id).ok_or("Deck not found")?;
+        deck.preset_id = preset_id;
+        Ok(updated)
+    }
+
+    /// Move cards into a deck, or out of every deck when `deck_id` is not set. Cards that get
+    /// another algorithm from the options of their new deck are migrated to it.
+    pub fn move_cards_to_deck(&self, request: MoveCardsRequest) -> Result<Vec<Card>, String> {
+        let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        self.check_deck_exists(request.deck_id.as_deref())?;
+        let decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let settings = self.settings.lock().map_err(|_| "Failed to lock settings")?;
+        let new_options = decks.options_for(request.deck_id.as_deref(), &settings);
+        let now = self.clock.now();
+
+        // Undoing the move restores each card whole, deck and scheduling state together, so unlike
+        // other migrations this one can stay undoable
+        let mut moved_cards = Vec::new();
+        let mut previous_states = Vec::new();
+        for card_id in &request.card_ids {
+            if let Some(card) = cards.get_mut(card_id).filter(|card| card.deck_id != request.deck_id) {
+                previous_states.push((card_id.clone(), Some(card.clone())));
+                let old_options = decks.options_for(card.deck_id.as_deref(), &settings);
+                if let Some(migrated) = Self::migrated_card(card, old_options, new_options, now) {
+                    *card = migrated;
+                }
+                card.deck_id = request.deck_id.clone();
+                moved_cards.push(card.clone());
+            }
+        }
+
+        if !moved_cards.is_empty() {
+            let moved_ids: Vec<String> = moved_cards.iter().map(|card| card.id.clone()).collect();
+            self.save_changed_cards(&cards, &moved_ids)?;
+            let description = journal::describe("Move", moved_cards.len());
+            self.record_operation(&description, previous_states, &cards, None)?;
+        }
+
+        Ok(moved_cards)
+    }
+
+    pub fn get_presets(&self) -> Result<Vec<DeckPreset>, String> {
+        let decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        Ok(decks.presets.clone())
+    }
+
+    pub fn create_preset(&self, request: PresetRequest) -> Result<DeckPreset, String> {
+        let mut decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let preset = DeckPreset {
+            id: Uuid::new_v4().to_string(),
+            name: request.name.trim().to_string(),
+            options: request.options,
+        };
+        decks.presets.push(preset.clone());
+        self.save_decks(&decks)?;
+        Ok(preset)
+    }
+
+    /// Change a preset. When its algorithm changes, the cards of the decks using it are migrated.
+    pub fn update_preset(&self, id: String, request: PresetRequest) -> Result<DeckPreset, String> {
+        let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let mut decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let settings = self.settings.lock().map_err(|_| "Failed to lock settings")?;
+
+        let updated = Self::with_preset_update(&decks, &id, request)?;
+        let migrated = self.migrated_cards(&cards, (&decks, &settings), (&updated, &settings));
+        self.apply_migration(&mut cards, migrated)?;
+
+        *decks = updated;
+        self.save_decks(&decks)?;
+        decks.preset(&id).cloned().ok_or_else(|| "Preset not found".to_string())
+    }
+
+    /// What `update_preset` would do to the cards, without changing anything
+    pub fn preview_preset_update(&self, id: String, request: PresetRequest) -> Result<MigrationPreview, String> {
+        let cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let settings = self.settings.lock().map_err(|_| "Failed to lock settings")?;
+
+        let updated = Self::with_preset_update(&decks, &id, request)?;
+        let migrated = self.migrated_cards(&cards, (&decks, &settings), (&updated, &settings));
+        Ok(self.migration_preview(&cards, migrated))
+    }
+
+    fn with_preset_update(decks: &DeckCollection, id: &str, request: PresetRequest) -> Result<DeckCollection, String> {
+        let mut updated = decks.clone();
+        let preset = updated
+            .presets
+            .iter_mut()
+            .find(|preset| preset.id == id)
+            .ok_or("Preset not found")?;
+        preset.name = request.name.trim().to_string();
+        preset.options = request.options;
+        Ok(updated)
+    }
+
+    /// Delete a preset. Decks that used it inherit the options of their parent again, and their
+    /// cards are migrated when that changes their algorithm.
+    pub fn delete_preset(&self, id: String) -> Result<(), String> {
+        let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let mut decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let settings = self.settings.lock().map_err(|_| "Failed to lock settings")?;
+
+        let mut updated = decks.clone();
+        updated.presets.retain(|preset| preset.id != id);
+        if updated.presets.len() == decks.presets.len() {
+            return Err("Preset not found".to_string());
+        }
+        for deck in updated
+            .decks
+            .iter_mut()
+            .filter(|deck| deck.preset_id.as_deref() == Some(id.as_str()))
+        {
+            deck.preset_id = None;
+        }
+
+        let migrated = self.migrated_cards(&cards, (&decks, &settings), (&updated, &settings));
+        self.apply_migration(&mut cards, migrated)?;
+
+        *decks = updated;
+        self.save_decks(&decks)?;
+        Ok(())
+    }
+
+    fn check_deck_exists(&self, deck_id: Option<&str>) -> Result<(), String> {
+        let decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        match deck_id {
+            Some(id) if decks.deck(id).is_none() => Err(format!("Deck {} not found", id)),
+            _ => Ok(()),
+        }
+    }
+
+    // Settings management methods
+    pub fn get_settings(&self) -> Result<AppSettings, String> {
+        let settings = self.settings.lock().map_err(|_| "Failed to lock settings")?;
+        Ok(settings.clone())
+    }
+
+    /// Save new settings. When the algorithm changes, the cards that follow the global settings
+    /// get scheduling state for the new algorithm, derived from their current intervals.
+    pub fn update_settings(&self, new_settings: AppSettings) -> Result<AppSettings, String> {
+        let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let mut settings = self.settings.lock().map_err(|_| "Failed to lock settings")?;
+
+        let migrated = self.migrated_cards(&cards, (&decks, &settings), (&decks, &new_settings));
+        self.apply_migration(&mut cards, migrated)?;
+
+        *settings = new_settings.clone();
+        self.save_settings(&settings)?;
+        Ok(new_settings)
+    }
+
+    /// What `update_settings` would do to the cards, without changing anything
+    pub fn preview_settings_update(&self, new_settings: AppSettings) -> Result<MigrationPreview, String> {
+        let cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let settings = self.settings.lock().map_err(|_| "Failed to lock settings")?;
+
+        let migrated = self.migrated_cards(&cards, (&decks, &settings), (&decks, &new_settings));
+        Ok(self.migration_preview(&cards, migrated))
+    }
+
+    /// How migrating cards changes when they are due
+    fn migration_preview(&self, cards: &HashMap<String, Card>, migrated: Vec<Card>) -> MigrationPreview {
+        let now = self.clock.now();
+        let mut preview = MigrationPreview::default();
+        for migrated in migrated {
+            let card = &cards[&migrated.id];
+            preview.cards_migrated += 1;
+            preview.due_earlier += (migrated.next_review < card.next_review) as usize;
+            preview.due_later += (migrated.next_review > card.next_review) as usize;
+            preview.cards_due_before += SpacedRepetition::is_due(card, now) as usize;
+            preview.cards_due_after += SpacedRepetition::is_due(&migrated, now) as usize;
+        }
+        preview
+    }
+
+    /// The cards whose algorithm differs between the options resolved from the decks and global
+    /// settings `before` and `after` a change, migrated to their new algorithm
+    fn migrated_cards(
+        &self,
+        cards: &HashMap<String, Card>,
+        before: (&DeckCollection, &AppSettings),
+        after: (&DeckCollection, &AppSettings),
+    ) -> Vec<Card> {
+        let now = self.clock.now();
+        cards
+            .values()
+            .filter_map(|card| {
+                let old_options = before.0.options_for(card.deck_id.as_deref(), before.1);
+                let new_options = after.0.options_for(card.deck_id.as_deref(), after.1);
+                Self::migrated_card(card, old_options, new_options, now)
+            })
+            .collect()
+    }
+
+    /// `card` migrated to the algorithm of `new_options` when it was reviewed under another one
+    fn migrated_card(card: &Card, old_options: &AppSettings, new_options: &AppSettings, now: DateTime<Utc>) -> Option<Card> {
+        (card.review_count > 0 && old_options.algorithm != new_options.algorithm)
+            .then(|| SpacedRepetition::migrate_card(card, new_options, now))
+    }
+
+    /// Save migrated cards. Undoing earlier operations would bring back state from the previous
+    /// algorithm, so the undo history is cleared.
+    fn apply_migration(&self, cards: &mut HashMap<String, Card>, migrated: Vec<Card>) -> Result<(), String> {
+        if migrated.is_empty() {
+            return Ok(());
+        }
+
+        let migrated_ids: Vec<String> = migrated.iter().map(|card| card.id.clone()).collect();
+        for card in migrated {
+            cards.insert(card.id.clone(), card);
+        }
+        self.save_changed_cards(cards, &migrated_ids)?;
+        self.journal.lock().map_err(|_| "Failed to lock journal")?.clear();
+        Ok(())
+    }
+
+    // Undo and redo methods
+    /// Revert the most recent card mutation, including the review log entry written by a review
+    pub fn undo(&self) -> Result<UndoState, String> {
+        let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let mut journal = self.journal.lock().map_err(|_| "Failed to lock journal")?;
+        let operation = journal.pop_undo().ok_or("Nothing to undo")?;
+
+        let states = operation
+            .changes
+            .iter()
+            .map(|change| (change.card_id.clone(), change.before.clone()))
+            .collect();
+        let result = self
+            .apply_card_states(&mut cards, states)
+            .and_then(|_| match &operation.review_log_entry {
+                Some(entry) => self
+                    .storage
+                    .remove_review_log_entry(&entry.id)
+                    .map_err(|e| format!("Failed to save review log: {}", e)),
+                None => Ok(()),
+            });
+
+        match result {
+            Ok(()) => journal.push_redo(operation),
+            Err(e) => {
+                journal.push_undo(operation);
+                return Err(e);
+            }
+        }
+        Ok(journal.state())
+    }
+
+    /// Re-apply the most recently undone card mutation
+    pub fn redo(&self) -> Result<UndoState, String> {
+        let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let mut journal = self.journal.lock().map_err(|_| "Failed to lock journal")?;
+        let operation = journal.pop_redo().ok_or("Nothing to redo")?;
+
+        let states = operation
+            .changes
+            .iter()
+            .map(|change| (change.card_id.clone(), change.after.clone()))
+            .collect();
+        let result = self
+            .apply_card_states(&mut cards, states)
+            .and_then(|_| match &operation.review_log_entry {
+                Some(entry) => self
+                    .storage
+                    .append_review_log(entry)
+                    .map_err(|e| format!("Failed to save review log: {}", e)),
+                None => Ok(()),
+            });
+
+        match result {
+            Ok(()) => journal.push_undo(operation),
+            Err(e) => {
+                journal.push_redo(operation);
+                return Err(e);
+            }
+        }
+        Ok(journal.state())
+    }
+
+    pub fn get_undo_state(&self) -> Result<UndoState, String> {
+        let journal = self.journal.lock().map_err(|_| "Failed to lock journal")?;
+        Ok(journal.state())
+    }
+
+    /// Record a mutation in the journal. `previous_states` holds each affected card as it was
+    /// before the change (`None` for cards that did not exist); the new states are read from `cards`.
+    fn record_operation(
+        &self,
+        description: &str,
+        previous_states: Vec<(String, Option<Card>)>,
+        cards: &HashMap<String, Card>,
+        review_log_entry: Option<ReviewLog>,
+    ) -> Result<(), String> {
+        let changes = previous_states
+            .into_iter()
+            .map(|(card_id, before)| CardChange {
+                after: cards.get(&card_id).cloned(),
+                card_id,
+                before,
+            })
+            .collect();
+
+        let mut journal = self.journal.lock().map_err(|_| "Failed to lock journal")?;
+        journal.record(Operation {
+            description: description.to_string(),
+            changes,
+            review_log_entry,
+        });
+        Ok(())
+    }
+
+    /// Put each card into the given state, removing cards whose state is `None`
+    fn apply_card_states(&self, cards: &mut HashMap<String, Card>, states: Vec<(String, Option<Card>)>) -> Result<(), String> {
+        let mut changed_ids = Vec::new();
+        let mut deleted_ids = Vec::new();
+
+        for (card_id, state) in states {
+            match state {
+                Some(card) => {
+                    cards.insert(card_id.clone(), card);
+                    changed_ids.push(card_id);
+                }
+                None => {
+                    cards.remove(&card_id);
+                    deleted_ids.push(card_id);
+                }
+            }
+        }
+
+        if !changed_ids.is_empty() {
+            self.save_changed_cards(cards, &changed_ids)?;
+        }
+        if !deleted_ids.is_empty() {
+            self.save_deleted_cards(cards, &deleted_ids)?;
+        }
+        Ok(())
+    }
+
+    // Backup and restore methods
+    /// Back up all cards, settings and review history. Without a path the backup goes to the
+    /// backup directory in the app data dir.
+    pub fn create_backup(&self, path: Option<String>) -> Result<BackupInfo, String> {
+        let path = path
+            .map(PathBuf::from)
+            .unwrap_or_else(|| backup::new_backup_path(&self.storage.backup_dir(), false));
+        self.write_backup(&path)
+    }
+
+    pub fn list_backups(&self) -> Result<Vec<BackupInfo>, String> {
+        backup::list_backups(&self.storage.backup_dir()).map_err(|e| format!("Failed to list backups: {}", e))
+    }
+
+    /// Replace all cards, settings and review history with the contents of a backup.
+    /// The current state is backed up first so the restore itself can be undone.
+    pub fn restore_backup(&self, path: String) -> Result<BackupInfo, String> {
+        let path = PathBuf::from(path);
+        let archive = backup::read_backup(&path).map_err(|e| format!("Failed to read backup: {}", e))?;
+        self.write_backup(&backup::new_backup_path(&self.storage.backup_dir(), true))?;
+
+        let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let mut decks = self.decks.lock().map_err(|_| "Failed to lock decks")?;
+        let mut settings = self.settings.lock().map_err(|_| "Failed to lock settings")?;
+
+        let mut restored_cards: HashMap<String, Card> = archive.cards.iter().map(|card| (card.id.clone(), card.clone())).collect();
+        restored_cards.values_mut().for_each(Card::infer_legacy_state);
+        self.storage
+            .save_cards(&restored_cards)
+            .map_err(|e| format!("Failed to save cards: {}", e))?;
+        self.save_settings(&archive.settings)?;
+        self.save_decks(&archive.decks)?;
+        self.storage
+            .replace_review_log(&archive.review_log)
+            .map_err(|e| format!("Failed to save review log: {}", e))?;
+
+        *cards = restored_cards;
+        *settings = archive.settings.clone();
+        *decks = archive.decks.clone();
+
+        // Earlier operations no longer apply to the restored cards
+        self.journal.lock().map_err(|_| "Failed to lock journal")?.clear();
+        Ok(backup::backup_info(&path, &archive))
+    }
+
+    /// Put cards back exactly as they were, keeping their ids and scheduling state
+    pub fn restore_cards(&self, restored: Vec<Card>) -> Result<Vec<Card>, String> {
+        let mut cards = self.cards.lock().map_err(|_| "Failed to lock cards")?;
+        let ids: Vec<String> = restored.iter().map(|card| card.id.clone()).collect();
+        let mut previous_states = Vec::new();
+
+        for card in &restored {
+            previous_states.push((card.id.clone(), cards.insert(card.id.clone(), card.clone())));
+        }
+        if !ids.is_empty() {
+            self.save_changed_cards(&cards, &ids)?;
+            let description = journal::describe("Restore", ids.len());
+            self.record_operation(&description, previous_states, &cards, None)?;
+        }
+
+        Ok(restored)
+    }
+
+    /// Take an automatic backup when the last one is more than a day old, keeping only the newest few
+    fn auto_backup(&self) -> Result<Option<BackupInfo>, String> {
+        let backup_dir = self.storage.backup_dir();
+        let is_empty = self.cards.lock().map_err(|_| "Failed to lock cards")?.is_empty();
+        if is_empty || !backup::auto_backup_due(&backup_dir).map_err(|e| e.to_string())? {
+            return Ok(None);
+        }
+
+        let info = self.write_backup(&backup::new_backup_path(&backup_dir, true))?;
+        backup::prune_auto_backups(&backup_dir, backup::AUTO_BACKUPS_TO_KEEP)
+            .map_err(|e| format!("Failed to remove old backups: {}", e))?;
+        Ok(Some(info))
+    }
+
+    fn write_backup(&self, path: &Path) -> Result<BackupInfo, String> {
+        let cards: Vec<Card> = self.cards.lock().map_err(|_| "Failed to lock cards")?.values().cloned().collect();
+        let settings = self.get_settings()?;
+        let decks = self.decks.lock().map_err(|_| "Failed to lock decks")?.clone();
+        let review_log = self
+            .storage
+            .load_review_log()
+            .map_err(|e| format!("Failed to load review log: {}", e))?;
+
+        let archive = BackupArchive::new(cards, settings, decks, review_log);
+        backup::write_backup(path, &archive).map_err(|e| format!("Failed to write backup: {}", e))
+    }
+
+    // Helper method to save cards that were created or changed
+    fn save_changed_cards(&self, cards: &HashMap<String, Card>, ids: &[String]) -> Result<(), String> {
+        self.storage
+            .upsert_cards(cards, ids)
+            .map_err(|e| format!("Failed to save cards: {}", e))
+    }
+
+    // Helper method to save the removal of cards
+    fn save_deleted_cards(&self, cards: &HashMap<String, Card>, ids: &[String]) -> Result<(), String> {
+        self.storage
+            .delete_cards(cards, ids)
+            .map_err(|e| format!("Failed to save cards: {}", e))
+    }
+
+    // Helper method to save decks and presets
+    fn save_decks(&self, decks: &DeckCollection) -> Result<(), String> {
+        self.storage.save_decks(decks).map_err(|e| format!("Failed to save decks: {}", e))
+    }
+
+    // Helper method to save settings
+    fn save_settings(&self, settings: &AppSettings) -> Result<(), String> {
+        self.storage
+            .save_settings(settings)
+            .map_err(|e| format!("Failed to save settings: {}", e))
+    }
+}
+
+/// Trim tags and drop the empty ones
+fn normalize_tags(tags: BTreeSet<String>) -> BTreeSet<String> {
+    tags.iter()
+        .map(|tag| tag.trim())
+        .filter(|tag| !tag.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::models::*;
+    use crate::sqlite_storage::SqliteStorage;
+    use crate::storage::JsonStorage;
+    use chrono::{TimeZone, Utc};
+    use serial_test::serial;
+    use tempfile::TempDir;
+
+    // Create a test storage instance
+    fn create_test_storage() -> (JsonStorage, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let data_file = temp_dir.path().join("test_cards.json");
+        let storage = JsonStorage::new_with_path(data_file);
+        (storage, temp_dir)
+    }
+
+    // Create a test card service
+    fn create_test_service() -> (CardService, TempDir) {
+        let (storage, temp_dir) = create_test_storage();
+        let service = CardService::new(storage).unwrap();
+        (service, temp_dir)
+    }
+
+    // Let new cards graduate on their first answer, so every rating is scheduled in days
+    fn disable_learning_steps(service: &CardService) {
+        service
+            .update_settings(AppSettings {
+                learning_steps: Vec::new(),
+                ..Default::default()
+            })
+            .unwrap();
+    }
+
+    // Create test card request
+    fn create_test_request(front: &str, back: &str, tag: Option<&str>) -> CreateCardRequest {
+        CreateCardRequest {
+            front: front.to_string(),
+            back: back.to_string(),
+            tags: tag.into_iter().map(String::from).collect(),
+            deck_id: None,
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_create_card() {
+        let (service, _temp_dir) = create_test_service();
+        let request = create_test_request("What is 2+2?", "4", Some("Math"));
+
+        let result = service.create_card(request);
+        assert!(result.is_ok());
+
+        let card = result.unwrap();
+        assert_eq!(card.front, "What is 2+2?");
+        assert_eq!(card.back, "4");
+        assert_eq!(card.tags, BTreeSet::from(["Math".to_string()]));
+        assert_eq!(card.review_count, 0);
+        assert_eq!(card.correct_count, 0);
+        assert_eq!(card.interval, 0);
+        assert_eq!(card.ease_factor, 2.5);
+        assert!(card.last_reviewed.is_none());
+        assert!(!card.id.is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_create_card_no_tag() {
+        let (service, _temp_dir) = create_test_service();
+        let request = create_test_request("Question", "Answer", None);
+
+        let result = service.create_card(request);
+        assert!(result.is_ok());
+
+        let card = result.unwrap();
+        assert!(card.tags.is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_cards_empty() {
+        let (service, _temp_dir) = create_test_service();
+        let result = service.get_cards();
+        assert!(result.is_ok());
+        assert!(result.unwrap().is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_cards_with_data() {
+        let (service, _temp_dir) = create_test_service();
+
+        // Create multiple cards
+        let request1 = create_test_request("Q1", "A1", Some("Cat1"));
+        let request2 = create_test_request("Q2", "A2", Some("Cat2"));
+
+        let card1 = service.create_card(request1).unwrap();
+        let card2 = service.create_card(request2).unwrap();
+
+        let result = service.get_cards();
+        assert!(result.is_ok());
+
+        let cards = result.unwrap();
+        assert_eq!(cards.len(), 2);
+
+        let card_ids: Vec<String> = cards.iter().map(|c| c.id.clone()).collect();
+        assert!(card_ids.contains(&card1.id));
+        assert!(card_ids.contains(&card2.id));
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_card_exists() {
+        let (service, _temp_dir) = create_test_service();
+        let request = create_test_request("Question", "Answer", None);
+        let created_card = service.create_card(request).unwrap();
+
+        let result = service.get_card(created_card.id.clone());
+        assert!(result.is_ok());
+
+        let retrieved_card = result.unwrap();
+        assert!(retrieved_card.is_some());
+
+        let card = retrieved_card.unwrap();
+        assert_eq!(card.id, created_card.id);
+        assert_eq!(card.front, "Question");
+        assert_eq!(card.back, "Answer");
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_card_not_exists() {
+        let (service, _temp_dir) = create_test_service();
+        let result = service.get_card("nonexistent-id".to_string());
+        assert!(result.is_ok());
+        assert!(result.unwrap().is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn test_update_card_success() {
+        let (service, _temp_dir) = create_test_service();
+        let request = create_test_request("Original", "Original Answer", Some("Original"));
+        let created_card = service.create_card(request).unwrap();
+
+        let update_request = UpdateCardRequest {
+            front: "Updated Question".to_string(),
+            back: "Updated Answer".to_string(),
+            tags: BTreeSet::from(["Updated Tag".to_string()]),
+            deck_id: None,
+        };
+
+        let result = service.update_card(created_card.id.clone(), update_request);
+        assert!(result.is_ok());
+
+        let updated_card = result.unwrap();
+        assert_eq!(updated_card.id, created_card.id);
+        assert_eq!(updated_card.front, "Updated Question");
+        assert_eq!(updated_card.back, "Updated Answer");
+        assert_eq!(updated_card.tags, BTreeSet::from(["Updated Tag".to_string()]));
+
+        // Verify persistence
+        let retrieved_card = service.get_card(created_card.id).unwrap().unwrap();
+        assert_eq!(retrieved_card.front, "Updated Question");
+    }
+
+    #[test]
+    #[serial]
+    fn test_update_card_not_found() {
+        let (service, _temp_dir) = create_test_service();
+        let update_request = UpdateCardRequest {
+            front: "Updated".to_string(),
+            back: "Updated".to_string(),
+            tags: BTreeSet::new(),
+            deck_id: None,
+        };
+
+        let result = service.update_card("nonexistent-id".to_string(), update_request);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), "Card not found");
+    }
+
+    #[test]
+    #[serial]
+    fn test_delete_card_success() {
+        let (service, _temp_dir) = create_test_service();
+        let request = create_test_request("To Delete", "Answer", None);
+        let created_card = service.create_card(request).unwrap();
+
+        let result = service.delete_card(created_card.id.clone());
+        assert!(result.is_ok());
+
+        // Verify card is deleted
+        let retrieved = service.get_card(created_card.id);
+        assert!(retrieved.is_ok());
+        assert!(retrieved.unwrap().is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn test_delete_card_not_found() {
+        let (service, _temp_dir) = create_test_service();
+        let result = service.delete_card("nonexistent-id".to_string());
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), "Card not found");
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_due_cards() {
+        let (service, _temp_dir) = create_test_service();
+
+        // Create a card that's due (next_review in the past)
+        let request = create_test_request("Due Card", "Answer", None);
+        let card = service.create_card(request).unwrap();
+
+        // The card should be due immediately (next_review is set to now for new cards)
+        let due_cards = service.get_due_cards().unwrap();
+        assert_eq!(due_cards.len(), 1);
+        assert_eq!(due_cards[0].id, card.id);
+    }
+
+    #[test]
+    #[serial]
+    fn test_review_card_success() {
+        let (service, _temp_dir) = create_test_service();
+        let request = create_test_request("Review Test", "Answer", None);
+        let created_card = service.create_card(request).unwrap();
+
+        let result = service.review_card(created_card.id.clone(), ReviewDifficulty::Good, None);
+        assert!(result.is_ok());
+
+        // The first answer moves the card to the second learning step, ten minutes away
+        let reviewed_card = result.unwrap();
+        assert_eq!(reviewed_card.id, created_card.id);
+        assert_eq!(reviewed_card.review_count, 1);
+        assert_eq!(reviewed_card.correct_count, 1);
+        assert_eq!(reviewed_card.state, CardState::Learning);
+        assert_eq!(reviewed_card.learning_step, 1);
+        assert_eq!(reviewed_card.interval, 0);
+        assert!(reviewed_card.last_reviewed.is_some());
+        assert!(reviewed_card.next_review > Utc::now() + Duration::minutes(9));
+        assert!(reviewed_card.next_review <= Utc::now() + Duration::minutes(10));
+
+        // Passing the last step graduates it to a review in a day
+        let graduated = service.review_card(created_card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+        assert_eq!(graduated.state, CardState::Review);
+        assert_eq!(graduated.learning_step, 0);
+        assert_eq!(graduated.interval, 1);
+        assert!(graduated.next_review > Utc::now() + Duration::hours(23));
+    }
+
+    #[test]
+    #[serial]
+    fn test_review_card_again() {
+        let (service, _temp_dir) = create_test_service();
+        let request = create_test_request("Review Test", "Answer", None);
+        let created_card = service.create_card(request).unwrap();
+
+        let result = service.review_card(created_card.id.clone(), ReviewDifficulty::Again, None);
+        assert!(result.is_ok());
+
+        let reviewed_card = result.unwrap();
+        assert_eq!(reviewed_card.review_count, 1);
+        assert_eq!(reviewed_card.correct_count, 0); // Not incremented for "Again"
+        assert_eq!(reviewed_card.state, CardState::Learning);
+        assert_eq!(reviewed_card.learning_step, 0);
+        assert_eq!(reviewed_card.interval, 0);
+        assert!(reviewed_card.next_review <= Utc::now() + Duration::minutes(1));
+    }
+
+    #[test]
+    #[serial]
+    fn test_review_card_not_found() {
+        let (service, _temp_dir) = create_test_service();
+        let result = service.review_card("nonexistent-id".to_string(), ReviewDifficulty::Good, None);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), "Card not found");
+    }
+
+    #[test]
+    #[serial]
+    fn test_review_card_appends_review_log() {
+        let (service, _temp_dir) = create_test_service();
+        disable_learning_steps(&service);
+        let card = service.create_card(create_test_request("Logged", "Answer", None)).unwrap();
+
+        service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+        let reviewed = service.review_card(card.id.clone(), ReviewDifficulty::Hard, None).unwrap();
+
+        let log = service.get_review_log(Some(card.id.clone())).unwrap();
+        assert_eq!(log.len(), 2);
+
+        assert_eq!(log[0].card_id, card.id);
+        assert!(matches!(log[0].difficulty, ReviewDifficulty::Good));
+        assert_eq!(log[0].previous_interval, 0);
+        assert_eq!(log[0].new_interval, 1);
+        assert_eq!(log[0].previous_ease_factor, 2.5);
+        assert_eq!(log[0].algorithm, SpacedRepetitionAlgorithm::SM2);
+
+        assert!(matches!(log[1].difficulty, ReviewDifficulty::Hard));
+        assert_eq!(log[1].previous_interval, 1);
+        assert_eq!(log[1].new_interval, reviewed.interval);
+        assert_eq!(log[1].new_ease_factor, reviewed.ease_factor);
+        assert_eq!(Some(log[1].reviewed_at), reviewed.last_reviewed);
+    }
+
+    #[test]
+    #[serial]
+    fn test_service_with_sqlite_storage() {
+        let temp_dir = TempDir::new().unwrap();
+        let card_id;
+        {
+            let service = CardService::new(SqliteStorage::in_dir(temp_dir.path()).unwrap()).unwrap();
+            let card = service.create_card(create_test_request("Q1", "A1", None)).unwrap();
+            let other = service.create_card(create_test_request("Q2", "A2", None)).unwrap();
+            service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+            service.delete_card(other.id).unwrap();
+            card_id = card.id;
+        }
+
+        let service = CardService::new(SqliteStorage::in_dir(temp_dir.path()).unwrap()).unwrap();
+        let cards = service.get_cards().unwrap();
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].id, card_id);
+        assert_eq!(cards[0].review_count, 1);
+        assert_eq!(service.get_review_log(None).unwrap().len(), 1);
+    }
+
+    #[test]
+    #[serial]
+    fn test_import_anki() {
+        let (service, temp_dir) = create_test_service();
+        service.create_card(create_test_request("hola", "hi", Some("Spanish"))).unwrap();
+        service.create_card(create_test_request("adiós", "goodbye", None)).unwrap();
+
+        let package = crate::anki_import::tests::create_test_package(
+            temp_dir.path(),
+            &[
+                ("hola\u{1f}hello", "Languages::Spanish", 2, 2, 12),
+                ("adiós\u{1f}goodbye", "Default", 2, 2, 30),
+                ("gato\u{1f}cat", "Languages::Spanish", 2, 2, 8),
+                ("perro\u{1f}dog", "Default", 0, 0, 0),
+                ("\u{1f}empty front", "Default", 0, 0, 0),
+                ("pez\u{1f}fish", "Default", 2, -1, 8),
+                ("pájaro\u{1f}bird", "Default", 2, -2, 8),
+                ("ratón\u{1f}mouse", "Default", 0, -3, 0),
+            ],
+        );
+
+        let summary = service.import_anki(package.to_string_lossy().into_owned(), true).unwrap();
+        assert_eq!(summary.imported, 5);
+        assert_eq!(summary.merged, 1);
+        assert_eq!(summary.skipped, 2);
+
+        let cards = service.get_cards().unwrap();
+        assert_eq!(cards.len(), 7);
+
+        // Notes are put into decks named after their Anki deck, parents included, and tagged with its name
+        let decks = service.get_decks().unwrap();
+        let names: Vec<&str> = decks.iter().map(|deck| deck.name.as_str()).collect();
+        assert_eq!(names, vec!["Languages", "Languages::Spanish"]);
+        let spanish = decks[1].id.clone();
+
+        let merged = cards.iter().find(|c| c.front == "hola").unwrap();
+        assert_eq!(merged.back, "hello");
+        assert_eq!(
+            merged.tags,
+            BTreeSet::from(["Languages::Spanish".to_string(), "Spanish".to_string()])
+        );
+        assert_eq!(merged.deck_id, Some(spanish.clone()));
+        assert_eq!(merged.interval, 0);
+
+        let scheduled = cards.iter().find(|c| c.front == "gato").unwrap();
+        assert_eq!(scheduled.interval, 8);
+        assert_eq!(scheduled.ease_factor, 2.3);
+        assert_eq!(scheduled.review_count, 5);
+        assert!(scheduled.next_review > Utc::now());
+
+        assert_eq!(scheduled.deck_id, Some(spanish));
+        assert_eq!(scheduled.tags, BTreeSet::from(["Languages::Spanish".to_string()]));
+
+        let new_card = cards.iter().find(|c| c.front == "perro").unwrap();
+        assert!(new_card.deck_id.is_none());
+        assert!(new_card.tags.is_empty());
+        assert_eq!(new_card.review_count, 0);
+        assert!(!new_card.suspended && new_card.buried_until.is_none());
+
+        // Suspended cards stay suspended, and buried cards stay buried for the rest of the day
+        let suspended = cards.iter().find(|c| c.front == "pez").unwrap();
+        assert!(suspended.suspended);
+        assert_eq!(suspended.interval, 8);
+        let until = start_of_next_day(Utc::now());
+        for front in ["pájaro", "ratón"] {
+            let buried = cards.iter().find(|c| c.front == front).unwrap();
+            assert!(!buried.suspended);
+            assert_eq!(buried.buried_until, Some(until));
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_import_anki_without_scheduling() {
+        let (service, temp_dir) = create_test_service();
+        let package = crate::anki_import::tests::create_test_package(temp_dir.path(), &[("gato\u{1f}cat", "Languages::Spanish", 2, -1, 8)]);
+
+        let summary = service.import_anki(package.to_string_lossy().into_owned(), false).unwrap();
+        assert_eq!(summary.imported, 1);
+
+        // The card starts over as a new card, suspended or not
+        let cards = service.get_cards().unwrap();
+        assert_eq!(cards[0].interval, 0);
+        assert_eq!(cards[0].review_count, 0);
+        assert!(cards[0].next_review <= Utc::now());
+        assert!(!cards[0].suspended);
+    }
+
+    #[test]
+    #[serial]
+    fn test_import_anki_invalid_file() {
+        let (service, temp_dir) = create_test_service();
+        let path = temp_dir.path().join("not_a_package.apkg");
+        std::fs::write(&path, "not a zip").unwrap();
+
+        let result = service.import_anki(path.to_string_lossy().into_owned(), false);
+        assert!(result.unwrap_err().contains("Failed to read Anki package"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_csv_export_import_round_trip() {
+        let (service, temp_dir) = create_test_service();
+        let card = service.create_card(create_test_request("Q1", "A1", Some("Tag"))).unwrap();
+        service.create_card(create_test_request("Q2", "A2", None)).unwrap();
+        let reviewed = service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+
+        let path = temp_dir.path().join("export.csv");
+        let exported = service
+            .export_csv(CsvExportRequest {
+                path: path.to_string_lossy().into_owned(),
+                delimiter: None,
+                card_ids: None,
+            })
+            .unwrap();
+        assert_eq!(exported, 2);
+
+        // Import into an empty collection keeps ids and scheduling
+        let other_dir = TempDir::new().unwrap();
+        let other = CardService::new(JsonStorage::new_with_path(other_dir.path().join("cards.json"))).unwrap();
+        let import_request = || CsvImportRequest {
+            path: path.to_string_lossy().into_owned(),
+            delimiter: None,
+            has_headers: true,
+            columns: None,
+        };
+        let summary = other.import_csv(import_request()).unwrap();
+        assert_eq!(summary.imported, 2);
+
+        let imported = other.get_card(card.id.clone()).unwrap().unwrap();
+        assert_eq!(serde_json::to_value(&imported).unwrap(), serde_json::to_value(&reviewed).unwrap());
+
+        // Importing the same file again updates the cards in place
+        let summary = other.import_csv(import_request()).unwrap();
+        assert_eq!(summary.merged, 2);
+        assert_eq!(other.get_cards().unwrap().len(), 2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_csv_import_with_column_mapping() {
+        let (service, temp_dir) = create_test_service();
+        service.create_card(create_test_request("hola", "hello", None)).unwrap();
+
+        let path = temp_dir.path().join("spreadsheet.tsv");
+        std::fs::write(
+            &path,
+            "Word\tMeaning\tTopic\nhola\thello\tSpanish\ngato\tcat\tSpanish\n\tno front\t\n",
+        )
+        .unwrap();
+
+        let summary = service
+            .import_csv(CsvImportRequest {
+                path: path.to_string_lossy().into_owned(),
+                delimiter: None,
+                has_headers: true,
+                columns: Some(HashMap::from([
+                    ("front".to_string(), 0),
+                    ("back".to_string(), 1),
+                    ("tag".to_string(), 2),
+                ])),
+            })
+            .unwrap();
+
+        assert_eq!(summary.imported, 1);
+        assert_eq!(summary.skipped, 2);
+
+        let cards = service.get_cards().unwrap();
+        let gato = cards.iter().find(|c| c.front == "gato").unwrap();
+        assert_eq!(gato.back, "cat");
+        assert_eq!(gato.tags, BTreeSet::from(["Spanish".to_string()]));
+        assert_eq!(gato.review_count, 0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_backup_and_restore_preserves_scheduling() {
+        let (service, temp_dir) = create_test_service();
+        let card = service.create_card(create_test_request("Q1", "A1", Some("Tag"))).unwrap();
+        service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+        service.review_card(card.id.clone(), ReviewDifficulty::Easy, None).unwrap();
+        service
+            .update_settings(AppSettings {
+                algorithm: SpacedRepetitionAlgorithm::Leitner,
+                ..Default::default()
+            })
+            .unwrap();
+        // As migrated to the new algorithm
+        let reviewed = service.get_card(card.id.clone()).unwrap().unwrap();
+
+        let backup_path = temp_dir.path().join("my-backup.json");
+        let info = service.create_backup(Some(backup_path.to_string_lossy().into_owned())).unwrap();
+        assert_eq!(info.card_count, 1);
+        assert_eq!(info.review_log_count, 2);
+
+        // Change everything after the backup
+        service.delete_card(card.id.clone()).unwrap();
+        service.create_card(create_test_request("Q2", "A2", None)).unwrap();
+        service.update_settings(AppSettings::default()).unwrap();
+
+        service.restore_backup(backup_path.to_string_lossy().into_owned()).unwrap();
+
+        let cards = service.get_cards().unwrap();
+        assert_eq!(cards.len(), 1);
+        assert_eq!(serde_json::to_value(&cards[0]).unwrap(), serde_json::to_value(&reviewed).unwrap());
+        assert_eq!(service.get_settings().unwrap().algorithm, SpacedRepetitionAlgorithm::Leitner);
+        assert_eq!(service.get_review_log(None).unwrap().len(), 2);
+
+        // The state before the restore was kept as an automatic backup
+        let backups = service.list_backups().unwrap();
+        assert!(backups.iter().any(|b| b.automatic && b.card_count == 1));
+
+        // Restored data is persisted
+        let reloaded = CardService::new(JsonStorage::new_with_path(temp_dir.path().join("test_cards.json"))).unwrap();
+        assert_eq!(reloaded.get_card(card.id).unwrap().unwrap().interval, reviewed.interval);
+    }
+
+    #[test]
+    #[serial]
+    fn test_restore_cards_keeps_ids_and_scheduling() {
+        let (service, _temp_dir) = create_test_service();
+        let card = service.create_card(create_test_request("Q1", "A1", None)).unwrap();
+        let reviewed = service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+
+        service.delete_card(card.id.clone()).unwrap();
+        service.restore_cards(vec![reviewed.clone()]).unwrap();
+
+        let restored = service.get_card(card.id).unwrap().unwrap();
+        assert_eq!(serde_json::to_value(&restored).unwrap(), serde_json::to_value(&reviewed).unwrap());
+    }
+
+    #[test]
+    #[serial]
+    fn test_automatic_backup_on_startup() {
+        let (service, temp_dir) = create_test_service();
+        assert!(service.list_backups().unwrap().is_empty());
+        service.create_card(create_test_request("Q1", "A1", None)).unwrap();
+
+        let data_file = temp_dir.path().join("test_cards.json");
+        let service = CardService::new(JsonStorage::new_with_path(data_file.clone())).unwrap();
+        let backups = service.list_backups().unwrap();
+        assert_eq!(backups.len(), 1);
+        assert!(backups[0].automatic);
+
+        // Only one automatic backup per day
+        CardService::new(JsonStorage::new_with_path(data_file)).unwrap();
+        assert_eq!(service.list_backups().unwrap().len(), 1);
+    }
+
+    #[test]
+    #[serial]
+    fn test_undo_review_restores_scheduling_and_review_log() {
+        let (service, _temp_dir) = create_test_service();
+        disable_learning_steps(&service);
+        let card = service.create_card(create_test_request("Q1", "A1", None)).unwrap();
+        service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+        let before_again = service.get_card(card.id.clone()).unwrap().unwrap();
+
+        // An accidental "Again" rating
+        service.review_card(card.id.clone(), ReviewDifficulty::Again, None).unwrap();
+        assert_eq!(service.get_undo_state().unwrap().undo_description, Some("Review card".to_string()));
+
+        let state = service.undo().unwrap();
+        assert!(state.can_redo);
+
+        let restored = service.get_card(card.id.clone()).unwrap().unwrap();
+        assert_eq!(
+            serde_json::to_value(&restored).unwrap(),
+            serde_json::to_value(&before_again).unwrap()
+        );
+        let log = service.get_review_log(Some(card.id.clone())).unwrap();
+        assert_eq!(log.len(), 1);
+        assert!(matches!(log[0].difficulty, ReviewDifficulty::Good));
+
+        // Redo puts the rating back
+        service.redo().unwrap();
+        let redone = service.get_card(card.id.clone()).unwrap().unwrap();
+        assert_eq!(redone.interval, 1);
+        assert_eq!(service.get_review_log(Some(card.id)).unwrap().len(), 2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_undo_delete_and_bulk_operations() {
+        let (service, _temp_dir) = create_test_service();
+        let card1 = service.create_card(create_test_request("Q1", "A1", Some("Old"))).unwrap();
+        let card2 = service.create_card(create_test_request("Q2", "A2", Some("Old"))).unwrap();
+        let reviewed = service.review_card(card1.id.clone(), ReviewDifficulty::Easy, None).unwrap();
+
+        service
+            .bulk_update_tag(BulkUpdateRequest {
+                card_ids: vec![card1.id.clone(), card2.id.clone()],
+                tags: BTreeSet::from(["New".to_string()]),
+            })
+            .unwrap();
+        service.delete_multiple_cards(vec![card1.id.clone(), card2.id.clone()]).unwrap();
+        assert!(service.get_cards().unwrap().is_empty());
+
+        service.undo().unwrap();
+        let restored = service.get_card(card1.id.clone()).unwrap().unwrap();
+        assert_eq!(restored.tags, BTreeSet::from(["New".to_string()]));
+        assert_eq!(restored.interval, reviewed.interval);
+        assert_eq!(restored.ease_factor, reviewed.ease_factor);
+
+        service.undo().unwrap();
+        assert_eq!(
+            service.get_card(card2.id.clone()).unwrap().unwrap().tags,
+            BTreeSet::from(["Old".to_string()])
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_undo_create_and_edit() {
+        let (service, temp_dir) = create_test_service();
+        let card = service.create_card(create_test_request("Q1", "A1", None)).unwrap();
+        service
+            .update_card(
+                card.id.clone(),
+                UpdateCardRequest {
+                    front: "Changed".to_string(),
+                    back: "A1".to_string(),
+                    tags: BTreeSet::new(),
+                    deck_id: None,
+                },
+            )
+            .unwrap();
+
+        service.undo().unwrap();
+        assert_eq!(service.get_card(card.id.clone()).unwrap().unwrap().front, "Q1");
+
+        service.undo().unwrap();
+        assert!(service.get_card(card.id.clone()).unwrap().is_none());
+        assert!(service.undo().is_err());
+
+        // A new mutation discards the redo history
+        service.redo().unwrap();
+        service.create_card(create_test_request("Q2", "A2", None)).unwrap();
+        assert!(!service.get_undo_state().unwrap().can_redo);
+
+        // Undo is persisted
+        let reloaded = CardService::new(JsonStorage::new_with_path(temp_dir.path().join("test_cards.json"))).unwrap();
+        assert_eq!(reloaded.get_cards().unwrap().len(), 2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_deck_presets_apply_per_card() {
+        let (service, _temp_dir) = create_test_service();
+        disable_learning_steps(&service);
+        let cram = service
+            .create_preset(PresetRequest {
+                name: "Cram".to_string(),
+                options: AppSettings {
+                    algorithm: SpacedRepetitionAlgorithm::Leitner,
+                    leitner_intervals: vec![1, 2, 4],
+                    learning_steps: Vec::new(),
+                    ..Default::default()
+                },
+            })
+            .unwrap();
+        let exam = service
+            .create_deck(CreateDeckRequest {
+                name: "Exam".to_string(),
+                preset_id: Some(cram.id.clone()),
+            })
+            .unwrap();
+        // Nested decks inherit the preset of their parent
+        let chapter = service
+            .create_deck(CreateDeckRequest {
+                name: "Exam::Chapter 1".to_string(),
+                preset_id: None,
+            })
+            .unwrap();
+
+        let mut request = create_test_request("Q1", "A1", None);
+        request.deck_id = Some(chapter.id.clone());
+        let crammed = service.create_card(request).unwrap();
+        let long_term = service.create_card(create_test_request("Q2", "A2", None)).unwrap();
+
+        let crammed = service.review_card(crammed.id, ReviewDifficulty::Good, None).unwrap();
+        assert_eq!(crammed.leitner_box, 1);
+        assert_eq!(crammed.interval, 2);
+        let long_term = service.review_card(long_term.id, ReviewDifficulty::Good, None).unwrap();
+        assert_eq!(long_term.interval, 1);
+
+        let log = service.get_review_log(Some(crammed.id.clone())).unwrap();
+        assert_eq!(log[0].algorithm, SpacedRepetitionAlgorithm::Leitner);
+
+        // Without the preset the deck falls back to the global settings
+        service.delete_preset(cram.id).unwrap();
+        assert!(service.get_decks().unwrap().iter().all(|deck| deck.preset_id.is_none()));
+        let reviewed = service.review_card(crammed.id, ReviewDifficulty::Good, None).unwrap();
+        assert_eq!(reviewed.leitner_box, 1);
+        assert_eq!(reviewed.interval, 6); // SM-2 second review
+
+        assert!(service.set_deck_preset(exam.id, Some("missing".to_string())).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_switching_preset_algorithm_migrates_cards() {
+        let (storage, _temp_dir) = create_test_storage();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let clock = Arc::new(FixedClock::new(start));
+        let service = CardService::with_clock(storage, clock.clone()).unwrap();
+        disable_learning_steps(&service);
+        let sm2 = AppSettings {
+            learning_steps: Vec::new(),
+            ..Default::default()
+        };
+        let preset = service
+            .create_preset(PresetRequest {
+                name: "Long term".to_string(),
+                options: sm2,
+            })
+            .unwrap();
+        let physics = service
+            .create_deck(CreateDeckRequest {
+                name: "Physics".to_string(),
+                preset_id: Some(preset.id.clone()),
+            })
+            .unwrap();
+
+        let mut request = create_test_request("Q1", "A1", None);
+        request.deck_id = Some(physics.id.clone());
+        let card = service.create_card(request).unwrap();
+        for _ in 0..3 {
+            let reviewed = service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+            clock.set(reviewed.next_review);
+        }
+        let mature = service.get_card(card.id.clone()).unwrap().unwrap();
+        assert_eq!(mature.interval, 15);
+
+        let leitner = PresetRequest {
+            name: "Long term".to_string(),
+            options: AppSettings {
+                algorithm: SpacedRepetitionAlgorithm::Leitner,
+                learning_steps: Vec::new(),
+                ..Default::default()
+            },
+        };
+        let preview = service.preview_preset_update(preset.id.clone(), leitner.clone()).unwrap();
+        assert_eq!(preview.cards_migrated, 1);
+        assert_eq!(preview.due_earlier, 1);
+
+        service.update_preset(preset.id.clone(), leitner).unwrap();
+        let migrated = service.get_card(card.id.clone()).unwrap().unwrap();
+        assert_eq!(migrated.leitner_box, 3);
+        assert_eq!(migrated.next_review, mature.next_review - Duration::days(1));
+        assert!(!service.get_undo_state().unwrap().can_undo);
+
+        // Without the preset the deck follows the global SM-2 settings again
+        assert_eq!(service.preview_deck_preset(physics.id.clone(), None).unwrap().cards_migrated, 1);
+        service.set_deck_preset(physics.id.clone(), None).unwrap();
+        let global = service.get_card(card.id.clone()).unwrap().unwrap();
+        assert!(global.scheduler_state.contains_key("SM2"));
+        assert_eq!(global.interval, 14);
+
+        // Moving the card into a deck with the Leitner preset migrates it, and undo moves it back whole
+        let cram = service
+            .create_deck(CreateDeckRequest {
+                name: "Cram".to_string(),
+                preset_id: Some(preset.id),
+            })
+            .unwrap();
+        let moved = service
+            .move_cards_to_deck(MoveCardsRequest {
+                card_ids: vec![card.id.clone()],
+                deck_id: Some(cram.id),
+            })
+            .unwrap();
+        assert_eq!(moved[0].leitner_box, 3);
+        service.undo().unwrap();
+        let restored = service.get_card(card.id).unwrap().unwrap();
+        assert_eq!(restored.deck_id, Some(physics.id));
+        assert_eq!(serde_json::to_value(&restored).unwrap(), serde_json::to_value(&global).unwrap());
+    }
+
+    #[test]
+    #[serial]
+    fn test_rename_and_delete_decks() {
+        let (service, _temp_dir) = create_test_service();
+        let verbs = service
+            .create_deck(CreateDeckRequest {
+                name: "Languages :: Spanish :: Verbs".to_string(),
+                preset_id: None,
+            })
+            .unwrap();
+        assert_eq!(verbs.name, "Languages::Spanish::Verbs");
+        assert_eq!(service.get_decks().unwrap().len(), 3);
+
+        let mut request = create_test_request("hablar", "to speak", None);
+        request.deck_id = Some(verbs.id.clone());
+        let card = service.create_card(request).unwrap();
+
+        let languages = service.get_decks().unwrap()[0].clone();
+        service.rename_deck(languages.id.clone(), "Idiomas".to_string()).unwrap();
+        let names: Vec<String> = service.get_decks().unwrap().into_iter().map(|deck| deck.name).collect();
+        assert_eq!(names, vec!["Idiomas", "Idiomas::Spanish", "Idiomas::Spanish::Verbs"]);
+        assert!(service
+            .rename_deck(languages.id.clone(), "Idiomas::Spanish::Nested".to_string())
+            .is_err());
+
+        // Searching a deck includes its nested decks
+        let search = SearchRequest {
+            query: None,
+            tags: None,
+            tag_match: TagMatch::Any,
+            deck_id: Some(languages.id.clone()),
+        };
+        assert_eq!(service.search_cards(search).unwrap().len(), 1);
+
+        // Cards of a deleted deck move to its parent
+        let spanish = service.get_decks().unwrap()[1].id.clone();
+        service.delete_deck(spanish).unwrap();
+        assert_eq!(service.get_decks().unwrap().len(), 1);
+        assert_eq!(
+            service.get_card(card.id.clone()).unwrap().unwrap().deck_id,
+            Some(languages.id.clone())
+        );
+
+        service.delete_deck(languages.id).unwrap();
+        assert!(service.get_card(card.id).unwrap().unwrap().deck_id.is_none());
     }
 
     #[test]
     #[serial]
-    fn test_create_card() {
-        let (service, _temp_dir) = create_test_service();
-        let request = create_test_request("What is 2+2?", "4", Some("Math"));
-
-        let result = service.create_card(request);
-        assert!(result.is_ok());
+    fn test_decks_persist_and_move_cards() {
+        let temp_dir = TempDir::new().unwrap();
+        let card_id;
+        {
+            let service = CardService::new(SqliteStorage::in_dir(temp_dir.path()).unwrap()).unwrap();
+            let deck = service
+                .create_deck(CreateDeckRequest {
+                    name: "Physics".to_string(),
+                    preset_id: None,
+                })
+                .unwrap();
+            card_id = service.create_card(create_test_request("Q1", "A1", None)).unwrap().id;
+
+            let moved = service
+                .move_cards_to_deck(MoveCardsRequest {
+                    card_ids: vec![card_id.clone(), "missing".to_string()],
+                    deck_id: Some(deck.id),
+                })
+                .unwrap();
+            assert_eq!(moved.len(), 1);
+
+            let mut request = create_test_request("Q2", "A2", None);
+            request.deck_id = Some("missing".to_string());
+            assert!(service.create_card(request).is_err());
+        }
 
-        let card = result.unwrap();
-        assert_eq!(card.front, "What is 2+2?");
-        assert_eq!(card.back, "4");
-        assert_eq!(card.tag, Some("Math".to_string()));
-        assert_eq!(card.review_count, 0);
-        assert_eq!(card.correct_count, 0);
-        assert_eq!(card.interval, 0);
-        assert_eq!(card.ease_factor, 2.5);
-        assert!(card.last_reviewed.is_none());
-        assert!(!card.id.is_empty());
+        let service = CardService::new(SqliteStorage::in_dir(temp_dir.path()).unwrap()).unwrap();
+        let decks = service.get_decks().unwrap();
+        assert_eq!(decks.len(), 1);
+        assert_eq!(service.get_card(card_id).unwrap().unwrap().deck_id, Some(decks[0].id.clone()));
     }
 
     #[test]
     #[serial]
-    fn test_create_card_no_tag() {
+    fn test_daily_new_card_limit() {
         let (service, _temp_dir) = create_test_service();
-        let request = create_test_request("Question", "Answer", None);
+        service
+            .update_settings(AppSettings {
+                new_cards_per_day: 2,
+                ..Default::default()
+            })
+            .unwrap();
+        for i in 0..4 {
+            service.create_card(create_test_request(&format!("Q{}", i), "A", None)).unwrap();
+        }
 
-        let result = service.create_card(request);
-        assert!(result.is_ok());
+        let due = service.get_due_cards().unwrap();
+        assert_eq!(due.len(), 2);
+        let stats = service.get_review_stats().unwrap();
+        assert_eq!(stats.cards_due, 4);
+        assert_eq!(stats.new_cards_remaining, 2);
+
+        // Studying a new card uses up part of today's allowance; it stays due while in learning
+        service.review_card(due[0].id.clone(), ReviewDifficulty::Good, None).unwrap();
+        let due = service.get_due_cards().unwrap();
+        assert_eq!(due.len(), 2);
+        assert_eq!(due.iter().filter(|card| card.state == CardState::Learning).count(), 1);
+        assert_eq!(service.get_review_stats().unwrap().new_cards_remaining, 1);
+
+        // Undoing the review gives it back
+        service.undo().unwrap();
+        assert_eq!(service.get_review_stats().unwrap().new_cards_remaining, 2);
+    }
 
-        let card = result.unwrap();
-        assert_eq!(card.tag, None);
+    #[test]
+    #[serial]
+    fn test_sm2_intervals_after_learning_steps() {
+        let (storage, _temp_dir) = create_test_storage();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let clock = Arc::new(FixedClock::new(start));
+        let service = CardService::with_clock(storage, clock.clone()).unwrap();
+        let card = service.create_card(create_test_request("Q1", "A1", None)).unwrap();
+
+        // Through the default learning steps of 1 and 10 minutes
+        service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+        clock.advance(Duration::minutes(10));
+        let graduated = service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+        assert_eq!(graduated.state, CardState::Review);
+        assert_eq!(graduated.review_count, 2);
+
+        // The learning steps are not SM-2 repetitions: 1, 6, then 6 times the ease factor
+        let mut intervals = vec![graduated.interval];
+        for _ in 0..2 {
+            clock.advance(Duration::days(*intervals.last().unwrap()));
+            intervals.push(service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap().interval);
+        }
+        assert_eq!(intervals, vec![1, 6, 15]);
     }
 
     #[test]
     #[serial]
-    fn test_get_cards_empty() {
-        let (service, _temp_dir) = create_test_service();
-        let result = service.get_cards();
-        assert!(result.is_ok());
-        assert!(result.unwrap().is_empty());
+    fn test_scheduling_follows_the_clock() {
+        let (storage, _temp_dir) = create_test_storage();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let clock = Arc::new(FixedClock::new(start));
+        let service = CardService::with_clock(storage, clock.clone()).unwrap();
+
+        let card = service.create_card(create_test_request("Q1", "A1", None)).unwrap();
+        assert_eq!(card.created_at, start);
+        assert_eq!(card.next_review, start);
+
+        // Through the second learning step to a review one day later
+        let learning = service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+        assert_eq!(learning.next_review, start + Duration::minutes(10));
+        clock.advance(Duration::minutes(10));
+        let graduated = service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+        let answered_at = start + Duration::minutes(10);
+        assert_eq!(graduated.state, CardState::Review);
+        assert_eq!(graduated.last_reviewed, Some(answered_at));
+        assert_eq!(graduated.next_review, answered_at + Duration::days(1));
+        assert_eq!(service.get_review_log(None).unwrap()[1].reviewed_at, answered_at);
+        assert!(service.get_due_cards().unwrap().is_empty());
+
+        clock.advance(Duration::days(1));
+        assert_eq!(service.get_due_cards().unwrap().len(), 1);
+        assert_eq!(service.get_review_stats().unwrap().cards_due, 1);
     }
 
     #[test]
     #[serial]
-    fn test_get_cards_with_data() {
+    fn test_fuzz_and_load_balancing_spread_reviews() {
         let (service, _temp_dir) = create_test_service();
+        let mut settings = AppSettings {
+            learning_steps: Vec::new(),
+            load_balancing: true,
+            fuzz_tolerance: 0.25,
+            ..Default::default()
+        };
+        service.update_settings(settings.clone()).unwrap();
 
-        // Create multiple cards
-        let request1 = create_test_request("Q1", "A1", Some("Cat1"));
-        let request2 = create_test_request("Q2", "A2", Some("Cat2"));
+        // The second Good answer is six days out without fuzz or balancing
+        let answer_twice = |front: &str| {
+            let card = service.create_card(create_test_request(front, "A", None)).unwrap();
+            service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+            service.review_card(card.id, ReviewDifficulty::Good, None).unwrap().interval
+        };
+        let balanced: Vec<i64> = ["Q1", "Q2", "Q3"].iter().map(|front| answer_twice(front)).collect();
+        assert_eq!(balanced, vec![6, 5, 7]);
+
+        settings.load_balancing = false;
+        settings.interval_fuzz = true;
+        service.update_settings(settings).unwrap();
+        let fuzzed: BTreeSet<i64> = (0..20).map(|i| answer_twice(&format!("Fuzzed {}", i))).collect();
+        assert!(fuzzed.len() > 1);
+        assert!(fuzzed.iter().all(|interval| (4..=8).contains(interval)));
+    }
 
-        let card1 = service.create_card(request1).unwrap();
-        let card2 = service.create_card(request2).unwrap();
+    #[test]
+    #[serial]
+    fn test_load_balancing_ignores_suspended_and_buried_cards() {
+        let (storage, _temp_dir) = create_test_storage();
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let service = CardService::with_clock(storage, Arc::new(FixedClock::new(now))).unwrap();
+        service
+            .update_settings(AppSettings {
+                learning_steps: Vec::new(),
+                load_balancing: true,
+                fuzz_tolerance: 0.25,
+                ..Default::default()
+            })
+            .unwrap();
 
-        let result = service.get_cards();
-        assert!(result.is_ok());
+        // Other cards in review, due `days` from now
+        let due_in = |days: i64, front: &str| {
+            let other = service.create_card(create_test_request(front, "A", None)).unwrap();
+            let mut cards = service.cards.lock().unwrap();
+            let other = cards.get_mut(&other.id).unwrap();
+            other.state = CardState::Review;
+            other.next_review = now + Duration::days(days);
+            other.id.clone()
+        };
 
-        let cards = result.unwrap();
-        assert_eq!(cards.len(), 2);
+        // A Good answer takes this review card from 10 to 25 days
+        let card = service.create_card(create_test_request("Q", "A", None)).unwrap();
+        {
+            let mut cards = service.cards.lock().unwrap();
+            let card = cards.get_mut(&card.id).unwrap();
+            card.state = CardState::Review;
+            card.review_count = 3;
+            card.interval = 10;
+            card.last_reviewed = Some(now - Duration::days(10));
+        }
 
-        let card_ids: Vec<String> = cards.iter().map(|c| c.id.clone()).collect();
-        assert!(card_ids.contains(&card1.id));
-        assert!(card_ids.contains(&card2.id));
+        // The cards due on day 25 are all suspended or buried; one card is due on day 24
+        for i in 0..4 {
+            let id = due_in(25, &format!("Suspended {}", i));
+            service.cards.lock().unwrap().get_mut(&id).unwrap().suspended = true;
+            let id = due_in(25, &format!("Buried {}", i));
+            service.cards.lock().unwrap().get_mut(&id).unwrap().buried_until = Some(now + Duration::hours(12));
+        }
+        due_in(24, "Active");
+
+        let reviewed = service.review_card(card.id, ReviewDifficulty::Good, None).unwrap();
+        assert_eq!(reviewed.interval, 25);
     }
 
     #[test]
     #[serial]
-    fn test_get_card_exists() {
-        let (service, _temp_dir) = create_test_service();
-        let request = create_test_request("Question", "Answer", None);
-        let created_card = service.create_card(request).unwrap();
-
-        let result = service.get_card(created_card.id.clone());
-        assert!(result.is_ok());
+    fn test_switching_algorithm_migrates_cards() {
+        let (storage, _temp_dir) = create_test_storage();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let clock = Arc::new(FixedClock::new(start));
+        let service = CardService::with_clock(storage, clock.clone()).unwrap();
+        disable_learning_steps(&service);
+
+        // Three Good answers under SM-2 give an interval of 15 days
+        let card = service.create_card(create_test_request("Q1", "A1", None)).unwrap();
+        for _ in 0..3 {
+            let reviewed = service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+            clock.set(reviewed.next_review);
+        }
+        let mature = service.get_card(card.id.clone()).unwrap().unwrap();
+        assert_eq!(mature.interval, 15);
+        service.create_card(create_test_request("New", "Card", None)).unwrap();
+
+        let leitner = AppSettings {
+            algorithm: SpacedRepetitionAlgorithm::Leitner,
+            learning_steps: Vec::new(),
+            ..Default::default()
+        };
+        let preview = service.preview_settings_update(leitner.clone()).unwrap();
+        assert_eq!(preview.cards_migrated, 1);
+        assert_eq!(preview.due_earlier, 1); // Box 3 has a 14 day interval
+        assert_eq!(service.get_card(card.id.clone()).unwrap().unwrap().leitner_box, 0);
+
+        service.update_settings(leitner.clone()).unwrap();
+        let migrated = service.get_card(card.id.clone()).unwrap().unwrap();
+        assert_eq!(migrated.leitner_box, 3);
+        assert_eq!(migrated.next_review, mature.next_review - Duration::days(1));
+
+        // Saving settings with the same algorithm leaves cards alone
+        assert_eq!(service.preview_settings_update(leitner).unwrap().cards_migrated, 0);
+    }
 
-        let retrieved_card = result.unwrap();
-        assert!(retrieved_card.is_some());
+    #[test]
+    #[serial]
+    fn test_review_session() {
+        let (storage, _temp_dir) = create_test_storage();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let service = CardService::with_clock(storage, Arc::new(FixedClock::new(start))).unwrap();
+        for i in 0..3 {
+            service.create_card(create_test_request(&format!("Q{}", i), "A", None)).unwrap();
+        }
+        assert!(service.next_session_card().is_err());
+
+        let progress = service.start_review_session(SessionOrder::DueDate).unwrap();
+        assert_eq!((progress.answered, progress.remaining), (0, 3));
+        let first = progress.card.unwrap();
+
+        // Good moves a new card to its next learning step, which brings it back in the session
+        let progress = service.answer_session_card(first.id.clone(), ReviewDifficulty::Good, None).unwrap();
+        assert_eq!((progress.answered, progress.remaining), (1, 3));
+        assert_ne!(progress.card.as_ref().unwrap().id, first.id);
+        assert!(service.answer_session_card(first.id.clone(), ReviewDifficulty::Good, None).is_err());
+
+        let progress = service.undo_session_answer().unwrap();
+        assert_eq!((progress.answered, progress.remaining), (0, 3));
+        assert_eq!(progress.card.as_ref().unwrap().id, first.id);
+        assert_eq!(progress.card.unwrap().review_count, 0);
+
+        // Easy graduates a new card straight away, so each card is answered once
+        let mut progress = service.next_session_card().unwrap();
+        while let Some(card) = progress.card {
+            progress = service.answer_session_card(card.id, ReviewDifficulty::Easy, None).unwrap();
+        }
+        assert_eq!(progress.answered, 3);
 
-        let card = retrieved_card.unwrap();
-        assert_eq!(card.id, created_card.id);
-        assert_eq!(card.front, "Question");
-        assert_eq!(card.back, "Answer");
+        let summary = service.finish_review_session().unwrap();
+        assert_eq!((summary.answers, summary.easy, summary.again), (3, 3, 0));
+        assert_eq!(summary.cards_studied, 3);
+        assert_eq!(summary.cards_left, 0);
+        assert!(service.finish_review_session().is_err());
     }
 
     #[test]
     #[serial]
-    fn test_get_card_not_exists() {
-        let (service, _temp_dir) = create_test_service();
-        let result = service.get_card("nonexistent-id".to_string());
-        assert!(result.is_ok());
-        assert!(result.unwrap().is_none());
+    fn test_reschedule_cards() {
+        let (storage, _temp_dir) = create_test_storage();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let service = CardService::with_clock(storage, Arc::new(FixedClock::new(start))).unwrap();
+        disable_learning_steps(&service);
+        let ids: Vec<String> = (0..4)
+            .map(|i| {
+                let card = service.create_card(create_test_request(&format!("Q{}", i), "A", None)).unwrap();
+                service.review_card(card.id.clone(), ReviewDifficulty::Easy, None).unwrap().id
+            })
+            .collect();
+        let new_card = service.create_card(create_test_request("New", "A", None)).unwrap();
+        let card = |id: &String| service.get_card(id.clone()).unwrap().unwrap();
+
+        // Four cards over two days: two a day, in the order they were due
+        let due = service
+            .set_due_date(SetDueRequest {
+                card_ids: ids.clone(),
+                from_days: 1,
+                to_days: 2,
+            })
+            .unwrap();
+        assert_eq!(due.len(), 4);
+        let days: Vec<i64> = ids
+            .iter()
+            .map(|id| (card(id).next_review - start_of_day(start)).num_days())
+            .collect();
+        assert_eq!(days.iter().filter(|&&day| day == 1).count(), 2);
+        assert_eq!(days.iter().filter(|&&day| day == 2).count(), 2);
+        assert!(service
+            .set_due_date(SetDueRequest {
+                card_ids: ids.clone(),
+                from_days: 3,
+                to_days: 2,
+            })
+            .is_err());
+
+        // Shifting moves scheduled cards only
+        let before = card(&ids[0]).next_review;
+        let shifted = service
+            .shift_due_dates(ShiftDueRequest {
+                card_ids: vec![ids[0].clone(), new_card.id.clone()],
+                days: 7,
+            })
+            .unwrap();
+        assert_eq!(shifted.len(), 1);
+        assert_eq!(card(&ids[0]).next_review, before + Duration::days(7));
+        assert_eq!(card(&new_card.id).next_review, start);
+
+        // With only new cards nothing changes, and nothing is added to the undo history
+        let undo_state = service.get_undo_state().unwrap();
+        let shifted = service
+            .shift_due_dates(ShiftDueRequest {
+                card_ids: vec![new_card.id.clone()],
+                days: 7,
+            })
+            .unwrap();
+        assert!(shifted.is_empty());
+        assert_eq!(service.get_undo_state().unwrap().undo_description, undo_state.undo_description);
+        service.undo().unwrap();
+        assert_eq!(card(&ids[0]).next_review, before);
+        service.redo().unwrap();
+
+        // Forgetting skips new cards; resetting makes a reviewed card new again
+        assert_eq!(service.forget_cards(vec![ids[1].clone(), new_card.id.clone()]).unwrap().len(), 1);
+        let forgotten = card(&ids[1]);
+        assert_eq!(
+            (forgotten.state, forgotten.interval, forgotten.next_review),
+            (CardState::Review, 1, start)
+        );
+        assert_eq!(forgotten.ease_factor, 2.5);
+        assert_eq!(forgotten.review_count, 1);
+
+        assert_eq!(service.reset_cards(vec![ids[2].clone(), new_card.id.clone()]).unwrap().len(), 1);
+        let reset = card(&ids[2]);
+        assert_eq!((reset.state, reset.review_count, reset.ease_factor), (CardState::New, 0, 2.5));
+        assert_eq!(reset.next_review, start);
+
+        service.undo().unwrap();
+        assert_eq!(card(&ids[2]).review_count, 1);
     }
 
     #[test]
     #[serial]
-    fn test_update_card_success() {
-        let (service, _temp_dir) = create_test_service();
-        let request = create_test_request("Original", "Original Answer", Some("Original"));
-        let created_card = service.create_card(request).unwrap();
-
-        let update_request = UpdateCardRequest {
-            front: "Updated Question".to_string(),
-            back: "Updated Answer".to_string(),
-            tag: Some("Updated Tag".to_string()),
+    fn test_suspend_and_bury() {
+        let (storage, _temp_dir) = create_test_storage();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let clock = Arc::new(FixedClock::new(start));
+        let service = CardService::with_clock(storage, clock.clone()).unwrap();
+        let ids: Vec<String> = (0..3)
+            .map(|i| {
+                service
+                    .create_card(create_test_request(&format!("Q{}", i), "A", Some("verbs")))
+                    .unwrap()
+                    .id
+            })
+            .collect();
+        let due_ids = || {
+            service
+                .get_due_cards()
+                .unwrap()
+                .into_iter()
+                .map(|card| card.id)
+                .collect::<BTreeSet<_>>()
         };
 
-        let result = service.update_card(created_card.id.clone(), update_request);
-        assert!(result.is_ok());
-
-        let updated_card = result.unwrap();
-        assert_eq!(updated_card.id, created_card.id);
-        assert_eq!(updated_card.front, "Updated Question");
-        assert_eq!(updated_card.back, "Updated Answer");
-        assert_eq!(updated_card.tag, Some("Updated Tag".to_string()));
+        assert_eq!(service.suspend_cards(vec![ids[0].clone(), ids[1].clone()]).unwrap().len(), 2);
+        assert_eq!(service.suspend_cards(vec![ids[0].clone()]).unwrap().len(), 0); // Already suspended
+        assert_eq!(service.bury_cards(vec![ids[2].clone()]).unwrap().len(), 1);
+        assert!(due_ids().is_empty());
 
-        // Verify persistence
-        let retrieved_card = service.get_card(created_card.id).unwrap().unwrap();
-        assert_eq!(retrieved_card.front, "Updated Question");
+        let stats = service.get_review_stats().unwrap();
+        assert_eq!((stats.cards_due, stats.cards_suspended, stats.cards_buried), (0, 2, 1));
+        let tag_stats = service.get_tag_stats().unwrap();
+        assert_eq!(
+            (tag_stats[0].cards_due, tag_stats[0].cards_suspended, tag_stats[0].cards_buried),
+            (0, 2, 1)
+        );
+
+        // Buried cards come back the next local day; suspended cards stay out until unsuspended
+        clock.set(start_of_next_day(start));
+        assert_eq!(due_ids(), BTreeSet::from([ids[2].clone()]));
+        service.unsuspend_cards(vec![ids[1].clone()]).unwrap();
+        assert_eq!(due_ids(), BTreeSet::from([ids[1].clone(), ids[2].clone()]));
+
+        // Unburying and undo both bring a buried card back the same day
+        service.bury_cards(ids.clone()).unwrap();
+        assert!(due_ids().is_empty());
+        service.unbury_cards(vec![ids[1].clone()]).unwrap();
+        assert_eq!(due_ids(), BTreeSet::from([ids[1].clone()]));
+        service.undo().unwrap();
+        service.undo().unwrap();
+        assert_eq!(due_ids(), BTreeSet::from([ids[1].clone(), ids[2].clone()]));
     }
 
     #[test]
     #[serial]
-    fn test_update_card_not_found() {
-        let (service, _temp_dir) = create_test_service();
-        let update_request = UpdateCardRequest {
-            front: "Updated".to_string(),
-            back: "Updated".to_string(),
-            tag: None,
-        };
+    fn test_leeches() {
+        let (storage, _temp_dir) = create_test_storage();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let service = CardService::with_clock(storage, Arc::new(FixedClock::new(start))).unwrap();
+        service
+            .update_settings(AppSettings {
+                learning_steps: Vec::new(),
+                leech_threshold: 2,
+                leech_action: LeechAction::Suspend,
+                ..Default::default()
+            })
+            .unwrap();
 
-        let result = service.update_card("nonexistent-id".to_string(), update_request);
-        assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Card not found");
+        let card = service
+            .create_card(create_test_request("Hard to remember", "A", Some("verbs")))
+            .unwrap();
+        service.create_card(create_test_request("Easy to remember", "A", None)).unwrap();
+        // Graduating is not a lapse, and the first lapse is below the threshold
+        service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+        let lapsed = service.review_card(card.id.clone(), ReviewDifficulty::Again, None).unwrap();
+        assert_eq!(lapsed.lapses, 1);
+        assert!(!lapsed.suspended);
+        assert!(service.get_leeches().unwrap().is_empty());
+
+        // Relearned, then forgotten again: in relearning it would be due right away, were it not suspended
+        service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+        let leech = service.review_card(card.id.clone(), ReviewDifficulty::Again, None).unwrap();
+        assert_eq!(leech.lapses, 2);
+        assert!(leech.suspended);
+        assert!(leech.tags.contains(LEECH_TAG));
+        assert!(!service.get_due_cards().unwrap().iter().any(|due| due.id == card.id));
+        assert_eq!(service.get_leeches().unwrap()[0].id, card.id);
+
+        // Undoing the lapse takes the leech status back with it
+        service.undo().unwrap();
+        let card = service.get_card(card.id).unwrap().unwrap();
+        assert!(!card.suspended);
+        assert!(!card.tags.contains(LEECH_TAG));
     }
 
     #[test]
     #[serial]
-    fn test_delete_card_success() {
-        let (service, _temp_dir) = create_test_service();
-        let request = create_test_request("To Delete", "Answer", None);
-        let created_card = service.create_card(request).unwrap();
-
-        let result = service.delete_card(created_card.id.clone());
-        assert!(result.is_ok());
+    fn test_answer_times() {
+        let (storage, _temp_dir) = create_test_storage();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let service = CardService::with_clock(storage, Arc::new(FixedClock::new(start))).unwrap();
+        service
+            .update_settings(AppSettings {
+                learning_steps: Vec::new(),
+                slow_answer_seconds: 30,
+                ..Default::default()
+            })
+            .unwrap();
 
-        // Verify card is deleted
-        let retrieved = service.get_card(created_card.id);
-        assert!(retrieved.is_ok());
-        assert!(retrieved.unwrap().is_none());
+        let quick = service.create_card(create_test_request("Quick", "A", Some("verbs"))).unwrap();
+        let slow = service.create_card(create_test_request("Slow", "A", Some("verbs"))).unwrap();
+        let quick = service.review_card(quick.id, ReviewDifficulty::Good, Some(4_000)).unwrap();
+        // Left open for an hour: counted as ten minutes, and scheduled as Hard
+        let slow = service.review_card(slow.id, ReviewDifficulty::Good, Some(3_600_000)).unwrap();
+
+        assert_eq!(quick.last_answer_ms, Some(4_000));
+        assert_eq!(slow.last_answer_ms, Some(MAX_ANSWER_MS));
+        // Scheduled as Hard, while the history and the correct count keep the Good that was given
+        assert!(slow.interval < quick.interval);
+        assert_eq!(slow.correct_count, 1);
+        let log = service.get_review_log(Some(slow.id.clone())).unwrap();
+        assert_eq!(log[0].time_taken_ms, Some(MAX_ANSWER_MS));
+        assert_eq!(log[0].difficulty, ReviewDifficulty::Good);
+        assert_eq!(log[0].scheduled_as, Some(ReviewDifficulty::Hard));
+        let log = service.get_review_log(Some(quick.id.clone())).unwrap();
+        assert_eq!(
+            (log[0].difficulty.clone(), log[0].scheduled_as.clone()),
+            (ReviewDifficulty::Good, None)
+        );
+
+        let report = service.get_answer_time_stats().unwrap();
+        assert_eq!(report.cards[0].card_id, slow.id);
+        assert_eq!(report.cards[1].time.average_ms, 4_000);
+        assert_eq!(report.tags.len(), 1);
+        assert_eq!(report.tags[0].time.median_ms, (4_000 + MAX_ANSWER_MS) / 2);
+        assert_eq!(report.overall.unwrap().answers, 2);
     }
 
     #[test]
     #[serial]
-    fn test_delete_card_not_found() {
-        let (service, _temp_dir) = create_test_service();
-        let result = service.delete_card("nonexistent-id".to_string());
-        assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Card not found");
+    fn test_review_forecast() {
+        let (storage, _temp_dir) = create_test_storage();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let service = CardService::with_clock(storage, Arc::new(FixedClock::new(start))).unwrap();
+        disable_learning_steps(&service);
+        let deck = service
+            .create_deck(CreateDeckRequest {
+                name: "Exam".to_string(),
+                preset_id: None,
+            })
+            .unwrap();
+
+        let mut in_deck = create_test_request("Q1", "A1", None);
+        in_deck.deck_id = Some(deck.id.clone());
+        for request in [
+            in_deck,
+            create_test_request("Q2", "A2", Some("verbs")),
+            create_test_request("Q3", "A3", None),
+        ] {
+            let card = service.create_card(request).unwrap();
+            service.review_card(card.id, ReviewDifficulty::Good, None).unwrap();
+        }
+        service.create_card(create_test_request("New", "Card", Some("verbs"))).unwrap();
+
+        let forecast = |tag: Option<&str>, deck_id: Option<&str>| {
+            service
+                .get_review_forecast(ForecastRequest {
+                    days: 3,
+                    tag: tag.map(str::to_string),
+                    deck_id: deck_id.map(str::to_string),
+                })
+                .unwrap()
+                .iter()
+                .map(|day| day.cards_due)
+                .collect::<Vec<usize>>()
+        };
+        assert_eq!(forecast(None, None), vec![0, 3, 0]);
+        assert_eq!(forecast(Some("verbs"), None), vec![0, 1, 0]);
+        assert_eq!(forecast(None, Some(&deck.id)), vec![0, 1, 0]);
+        assert_eq!(forecast(Some("verbs"), Some(&deck.id)), vec![0, 0, 0]);
+
+        let request = ForecastRequest {
+            days: 0,
+            tag: None,
+            deck_id: None,
+        };
+        assert!(service.get_review_forecast(request).is_err());
     }
 
     #[test]
     #[serial]
-    fn test_get_due_cards() {
+    fn test_simulate_schedules() {
         let (service, _temp_dir) = create_test_service();
+        disable_learning_steps(&service);
+        let deck = service
+            .create_deck(CreateDeckRequest {
+                name: "Exam".to_string(),
+                preset_id: None,
+            })
+            .unwrap();
+        for i in 0..5 {
+            let mut request = create_test_request(&format!("Q{}", i), "A", None);
+            request.deck_id = (i < 2).then(|| deck.id.clone());
+            service.create_card(request).unwrap();
+        }
 
-        // Create a card that's due (next_review in the past)
-        let request = create_test_request("Due Card", "Answer", None);
-        let card = service.create_card(request).unwrap();
+        let results = service.simulate_schedules(SimulationRequest { days: 30, deck_id: None }).unwrap();
+        assert_eq!(results.len(), SpacedRepetitionAlgorithm::ALL.len());
+        for result in &results {
+            assert_eq!(result.daily_reviews.len(), 30);
+            assert_eq!(result.daily_reviews[0], 5);
+        }
 
-        // The card should be due immediately (next_review is set to now for new cards)
-        let due_cards = service.get_due_cards().unwrap();
-        assert_eq!(due_cards.len(), 1);
-        assert_eq!(due_cards[0].id, card.id);
+        // Only the cards in the deck are simulated, and the collection itself is left untouched
+        let in_deck = service
+            .simulate_schedules(SimulationRequest {
+                days: 1,
+                deck_id: Some(deck.id.clone()),
+            })
+            .unwrap();
+        assert_eq!(in_deck[0].daily_reviews, vec![2]);
+        assert!(service.get_cards().unwrap().iter().all(|card| card.review_count == 0));
+
+        assert!(service.simulate_schedules(SimulationRequest { days: 0, deck_id: None }).is_err());
+        assert!(service
+            .simulate_schedules(SimulationRequest {
+                days: 10,
+                deck_id: Some("missing".to_string()),
+            })
+            .is_err());
     }
 
     #[test]
     #[serial]
-    fn test_review_card_success() {
-        let (service, _temp_dir) = create_test_service();
-        let request = create_test_request("Review Test", "Answer", None);
-        let created_card = service.create_card(request).unwrap();
+    fn test_storage_recovery_reports() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_file = temp_dir.path().join("cards.json");
+        std::fs::write(&data_file, "{ not valid").unwrap();
 
-        let result = service.review_card(created_card.id.clone(), ReviewDifficulty::Good);
-        assert!(result.is_ok());
+        let service = CardService::new(JsonStorage::new_with_path(data_file)).unwrap();
+        assert!(service.get_cards().unwrap().is_empty());
 
-        let reviewed_card = result.unwrap();
-        assert_eq!(reviewed_card.id, created_card.id);
-        assert_eq!(reviewed_card.review_count, 1);
-        assert_eq!(reviewed_card.correct_count, 1);
-        assert_eq!(reviewed_card.interval, 1);
-        assert!(reviewed_card.last_reviewed.is_some());
-        assert!(reviewed_card.next_review > Utc::now());
+        let reports = service.get_storage_recovery_reports().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].file, "cards.json");
+
+        service.dismiss_storage_recovery_reports().unwrap();
+        assert!(service.get_storage_recovery_reports().unwrap().is_empty());
+
+        // The salvaged (empty) deck can be saved once the damaged file is quarantined
+        assert!(service.create_card(create_test_request("Q", "A", None)).is_ok());
     }
 
     #[test]
     #[serial]
-    fn test_review_card_again() {
-        let (service, _temp_dir) = create_test_service();
-        let request = create_test_request("Review Test", "Answer", None);
-        let created_card = service.create_card(request).unwrap();
-
-        let result = service.review_card(created_card.id.clone(), ReviewDifficulty::Again);
-        assert!(result.is_ok());
-
-        let reviewed_card = result.unwrap();
-        assert_eq!(reviewed_card.review_count, 1);
-        assert_eq!(reviewed_card.correct_count, 0); // Not incremented for "Again"
-        assert_eq!(reviewed_card.interval, 1);
+    fn test_damaged_settings_are_reported_and_kept() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_file = temp_dir.path().join("cards.json");
+        std::fs::write(
+            temp_dir.path().join("settings.json"),
+            r#"{"algorithm": "Leitner", "new_cards_per_day": "#,
+        )
+        .unwrap();
+
+        let service = CardService::new(JsonStorage::new_with_path(data_file.clone())).unwrap();
+        let reports = service.get_storage_recovery_reports().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].file, "settings.json");
+        let quarantined = reports[0].quarantined_to.clone().unwrap();
+        assert!(std::path::Path::new(&quarantined).exists());
+
+        // Settings that cannot be read at all keep the app from starting instead of being replaced
+        drop(service);
+        std::fs::remove_file(temp_dir.path().join("settings.json")).unwrap();
+        std::fs::create_dir(temp_dir.path().join("settings.json")).unwrap();
+        assert!(CardService::new(JsonStorage::new_with_path(data_file)).is_err());
     }
 
     #[test]
     #[serial]
-    fn test_review_card_not_found() {
-        let (service, _temp_dir) = create_test_service();
-        let result = service.review_card("nonexistent-id".to_string(), ReviewDifficulty::Good);
-        assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Card not found");
+    fn test_get_review_log_filters_by_card() {
+        let (service, temp_dir) = create_test_service();
+        let card1 = service.create_card(create_test_request("Q1", "A1", None)).unwrap();
+        let card2 = service.create_card(create_test_request("Q2", "A2", None)).unwrap();
+
+        service.review_card(card1.id.clone(), ReviewDifficulty::Good, None).unwrap();
+        service.review_card(card2.id.clone(), ReviewDifficulty::Again, None).unwrap();
+        service.review_card(card1.id.clone(), ReviewDifficulty::Easy, None).unwrap();
+
+        assert_eq!(service.get_review_log(None).unwrap().len(), 3);
+        assert_eq!(service.get_review_log(Some(card1.id.clone())).unwrap().len(), 2);
+        assert_eq!(service.get_review_log(Some(card2.id.clone())).unwrap().len(), 1);
+
+        // History survives a restart
+        let new_service = CardService::new(JsonStorage::new_with_path(temp_dir.path().join("test_cards.json"))).unwrap();
+        assert_eq!(new_service.get_review_log(None).unwrap().len(), 3);
     }
 
     #[test]
     #[serial]
     fn test_get_review_stats() {
         let (service, _temp_dir) = create_test_service();
+        disable_learning_steps(&service);
 
         // Create various types of cards
         let _new_card = service.create_card(create_test_request("New", "Answer", None)).unwrap();
@@ -512,7 +3001,7 @@ mod tests {
         // Create a reviewed card
         let reviewed_request = create_test_request("Reviewed", "Answer", None);
         let reviewed_card = service.create_card(reviewed_request).unwrap();
-        service.review_card(reviewed_card.id, ReviewDifficulty::Good).unwrap();
+        service.review_card(reviewed_card.id, ReviewDifficulty::Good, None).unwrap();
 
         let stats = service.get_review_stats().unwrap();
         assert_eq!(stats.total_cards, 2);
@@ -537,8 +3026,9 @@ mod tests {
 
         let search_request = SearchRequest {
             query: Some("programming".to_string()),
-            tag: None,
             tags: None,
+            tag_match: TagMatch::Any,
+            deck_id: None,
         };
 
         let results = service.search_cards(search_request).unwrap();
@@ -560,15 +3050,16 @@ mod tests {
 
         let search_request = SearchRequest {
             query: None,
-            tag: Some("Math".to_string()),
-            tags: None,
+            tags: Some(vec!["Math".to_string()]),
+            tag_match: TagMatch::Any,
+            deck_id: None,
         };
 
         let results = service.search_cards(search_request).unwrap();
         assert_eq!(results.len(), 2);
 
         for card in results {
-            assert_eq!(card.tag, Some("Math".to_string()));
+            assert_eq!(card.tags, BTreeSet::from(["Math".to_string()]));
         }
     }
 
@@ -589,8 +3080,9 @@ mod tests {
 
         let search_request = SearchRequest {
             query: Some("addition".to_string()),
-            tag: Some("Math".to_string()),
-            tags: None,
+            tags: Some(vec!["Math".to_string()]),
+            tag_match: TagMatch::Any,
+            deck_id: None,
         };
 
         let results = service.search_cards(search_request).unwrap();
@@ -650,7 +3142,7 @@ mod tests {
 
         let bulk_request = BulkUpdateRequest {
             card_ids: vec![card1.id.clone(), card2.id.clone()],
-            tag: Some("New Tag".to_string()),
+            tags: BTreeSet::from(["New Tag".to_string()]),
         };
 
         let result = service.bulk_update_tag(bulk_request);
@@ -664,9 +3156,10 @@ mod tests {
         let retrieved_card2 = service.get_card(card2.id).unwrap().unwrap();
         let retrieved_card3 = service.get_card(card3.id).unwrap().unwrap();
 
-        assert_eq!(retrieved_card1.tag, Some("New Tag".to_string()));
-        assert_eq!(retrieved_card2.tag, Some("New Tag".to_string()));
-        assert_eq!(retrieved_card3.tag, Some("Other".to_string())); // Unchanged
+        assert_eq!(retrieved_card1.tags, BTreeSet::from(["New Tag".to_string()]));
+        assert_eq!(retrieved_card2.tags, BTreeSet::from(["New Tag".to_string()]));
+        assert_eq!(retrieved_card3.tags, BTreeSet::from(["Other".to_string()]));
+        // Unchanged
     }
 
     #[test]
@@ -676,7 +3169,7 @@ mod tests {
 
         let bulk_request = BulkUpdateRequest {
             card_ids: vec!["nonexistent-1".to_string(), "nonexistent-2".to_string()],
-            tag: Some("New Tag".to_string()),
+            tags: BTreeSet::from(["New Tag".to_string()]),
         };
 
         let result = service.bulk_update_tag(bulk_request);
@@ -686,6 +3179,86 @@ mod tests {
         assert!(updated_cards.is_empty());
     }
 
+    #[test]
+    #[serial]
+    fn test_bulk_add_and_remove_tag() {
+        let (service, _temp_dir) = create_test_service();
+
+        let card1 = service.create_card(create_test_request("Q1", "A1", Some("Spanish"))).unwrap();
+        let card2 = service.create_card(create_test_request("Q2", "A2", Some("Verbs"))).unwrap();
+        let ids = vec![card1.id.clone(), card2.id.clone()];
+
+        let added = service
+            .bulk_add_tag(BulkTagRequest {
+                card_ids: ids.clone(),
+                tag: " Verbs ".to_string(),
+            })
+            .unwrap();
+        assert_eq!(added.len(), 1); // card2 already had the tag
+        assert_eq!(
+            service.get_card(card1.id.clone()).unwrap().unwrap().tags,
+            BTreeSet::from(["Spanish".to_string(), "Verbs".to_string()])
+        );
+
+        let removed = service
+            .bulk_remove_tag(BulkTagRequest {
+                card_ids: ids.clone(),
+                tag: "Spanish".to_string(),
+            })
+            .unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(
+            service.get_card(card1.id.clone()).unwrap().unwrap().tags,
+            BTreeSet::from(["Verbs".to_string()])
+        );
+
+        let empty = BulkTagRequest {
+            card_ids: ids,
+            tag: "  ".to_string(),
+        };
+        assert!(service.bulk_add_tag(empty).is_err());
+
+        // Each bulk change can be undone on its own
+        service.undo().unwrap();
+        assert!(service.get_card(card1.id).unwrap().unwrap().tags.contains("Spanish"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_cards_with_multiple_tags() {
+        let (service, _temp_dir) = create_test_service();
+
+        let request = CreateCardRequest {
+            front: "hablar".to_string(),
+            back: "to speak".to_string(),
+            tags: BTreeSet::from(["Spanish".to_string(), "Verbs".to_string(), " ".to_string()]),
+            deck_id: None,
+        };
+        let card = service.create_card(request).unwrap();
+        assert_eq!(card.tags.len(), 2);
+        service.create_card(create_test_request("gato", "cat", Some("Spanish"))).unwrap();
+        service.create_card(create_test_request("run", "correr", Some("Verbs"))).unwrap();
+
+        let search = |tags: &[&str], tag_match| {
+            let request = SearchRequest {
+                query: None,
+                tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+                tag_match,
+                deck_id: None,
+            };
+            service.search_cards(request).unwrap().len()
+        };
+        assert_eq!(search(&["Spanish", "Verbs"], TagMatch::Any), 3);
+        assert_eq!(search(&["Spanish", "Verbs"], TagMatch::All), 1);
+        assert_eq!(search(&[], TagMatch::All), 3);
+
+        // A card counts towards each of its tags
+        let tag_stats = service.get_tag_stats().unwrap();
+        assert_eq!(tag_stats.len(), 2);
+        assert!(tag_stats.iter().all(|stats| stats.total_cards == 2));
+        assert_eq!(service.get_tags().unwrap(), vec!["Spanish".to_string(), "Verbs".to_string()]);
+    }
+
     #[test]
     #[serial]
     fn test_delete_multiple_cards() {
@@ -736,7 +3309,7 @@ mod tests {
         }
 
         // Create new storage instance pointing to same file
-        let new_storage = Storage::new_with_path(temp_dir.path().join("test_cards.json"));
+        let new_storage = JsonStorage::new_with_path(temp_dir.path().join("test_cards.json"));
         let new_service = CardService::new(new_storage).unwrap();
 
         // Verify data persisted
@@ -744,7 +3317,7 @@ mod tests {
         assert_eq!(cards.len(), 1);
         assert_eq!(cards[0].front, "Persistent");
         assert_eq!(cards[0].back, "Data");
-        assert_eq!(cards[0].tag, Some("Test".to_string()));
+        assert_eq!(cards[0].tags, BTreeSet::from(["Test".to_string()]));
     }
 
     // Settings management tests
@@ -817,12 +3390,13 @@ mod tests {
     #[serial]
     fn test_review_card_with_different_algorithms() {
         let (service, _temp_dir) = create_test_service();
+        disable_learning_steps(&service);
 
         // Create a card
         let card = service.create_card(create_test_request("Q1", "A1", None)).unwrap();
 
         // Test with SM2 algorithm (default)
-        service.review_card(card.id.clone(), ReviewDifficulty::Good).unwrap();
+        service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
         let updated_card = service.get_card(card.id.clone()).unwrap().unwrap();
         assert!(updated_card.interval > 0);
         assert!(updated_card.ease_factor >= 1.3);
@@ -833,7 +3407,7 @@ mod tests {
         service.update_settings(leitner_settings).unwrap();
 
         // Review the card again with Leitner
-        service.review_card(card.id.clone(), ReviewDifficulty::Good).unwrap();
+        service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
         let leitner_card = service.get_card(card.id.clone()).unwrap().unwrap();
         // Leitner should have updated the leitner_box field
         assert!(leitner_card.leitner_box > 0);
@@ -845,12 +3419,40 @@ mod tests {
         service.update_settings(exp_settings).unwrap();
 
         // Review the card again with SimpleExponential
-        service.review_card(card.id.clone(), ReviewDifficulty::Good).unwrap();
+        service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
         let exp_card = service.get_card(card.id).unwrap().unwrap();
         // SimpleExponential should have updated the exponential_factor field
         assert!(exp_card.exponential_factor > 1.0);
     }
 
+    #[test]
+    #[serial]
+    fn test_review_card_with_fsrs() {
+        let (service, _temp_dir) = create_test_service();
+        let card = service.create_card(create_test_request("Q1", "A1", None)).unwrap();
+
+        let fsrs_settings = AppSettings {
+            algorithm: SpacedRepetitionAlgorithm::FSRS,
+            desired_retention: 0.85,
+            learning_steps: Vec::new(),
+            ..Default::default()
+        };
+        service.update_settings(fsrs_settings).unwrap();
+
+        let reviewed = service.review_card(card.id.clone(), ReviewDifficulty::Good, None).unwrap();
+        assert!(reviewed.fsrs_stability > 0.0);
+        assert!(reviewed.fsrs_difficulty >= 1.0);
+        assert!(reviewed.interval >= 1);
+
+        // FSRS state is persisted with the card
+        let stored = service.get_card(card.id.clone()).unwrap().unwrap();
+        assert_eq!(stored.fsrs_stability, reviewed.fsrs_stability);
+        assert_eq!(stored.fsrs_difficulty, reviewed.fsrs_difficulty);
+
+        let log = service.get_review_log(Some(card.id)).unwrap();
+        assert_eq!(log[0].algorithm, SpacedRepetitionAlgorithm::FSRS);
+    }
+
     #[test]
     #[serial]
     fn test_settings_persistence_across_instances() {
@@ -859,7 +3461,7 @@ mod tests {
 
         // Create first service instance and update settings
         {
-            let storage = Storage::new_with_path(storage_path.clone());
+            let storage = JsonStorage::new_with_path(storage_path.clone());
             let service = CardService::new(storage).unwrap();
 
             let mut new_settings = AppSettings::default();
@@ -870,7 +3472,7 @@ mod tests {
 
         // Create second service instance and verify settings persistence
         {
-            let storage = Storage::new_with_path(storage_path);
+            let storage = JsonStorage::new_with_path(storage_path);
             let service = CardService::new(storage).unwrap();
 
             let settings = service.get_settings().unwrap();
@@ -0,0 +1,90 @@
+use chrono::{DateTime, Duration, Utc};
+use std::sync::{Arc, Mutex};
+
+/// Pins the clock of a debug build to an RFC 3339 time, e.g. `2030-01-01T09:00:00Z`,
+/// to review the collection as it would be on that day
+pub const REVIEW_AS_OF_VAR: &str = "SPACED_OUT_REVIEW_AS_OF";
+
+/// Source of the current time for scheduling. The app uses the system clock; tests and
+/// simulations use a `FixedClock` they can move forward.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The real time
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that stays where it is set until it is moved
+pub struct FixedClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl FixedClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        FixedClock { now: Mutex::new(now) }
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) = now;
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) += duration;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The clock the app schedules with: the system clock, unless a debug build is started
+/// with `SPACED_OUT_REVIEW_AS_OF` set
+pub fn app_clock() -> Arc<dyn Clock> {
+    if cfg!(debug_assertions) {
+        if let Ok(value) = std::env::var(REVIEW_AS_OF_VAR) {
+            match DateTime::parse_from_rfc3339(&value) {
+                Ok(as_of) => {
+                    log::info!("Reviewing as of {}", as_of);
+                    return Arc::new(FixedClock::new(as_of.with_timezone(&Utc)));
+                }
+                Err(e) => log::warn!("Ignoring {}={}: {}", REVIEW_AS_OF_VAR, value, e),
+            }
+        }
+    }
+    Arc::new(SystemClock)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_fixed_clock() {
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let clock = FixedClock::new(start);
+        assert_eq!(clock.now(), start);
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::days(2));
+        assert_eq!(clock.now(), start + Duration::days(2));
+
+        clock.set(start);
+        assert_eq!(clock.now(), start);
+    }
+
+    #[test]
+    fn test_system_clock() {
+        let before = Utc::now();
+        let now = SystemClock.now();
+        assert!(now >= before && now <= Utc::now());
+    }
+}
@@ -1,7 +1,10 @@
 use crate::card_service::CardService;
 use crate::models::{
-    AppSettings, BulkUpdateRequest, Card, CreateCardRequest, ReviewDifficulty, ReviewStats, SearchRequest, SpacedRepetitionAlgorithm,
-    TagStats, UpdateCardRequest,
+    AnswerTimeReport, AppSettings, BackupInfo, BulkTagRequest, BulkUpdateRequest, Card, CreateCardRequest, CreateDeckRequest,
+    CsvExportRequest, CsvImportRequest, Deck, DeckPreset, ForecastDay, ForecastRequest, ImportSummary, MigrationPreview, MoveCardsRequest,
+    PresetRequest, ReviewDifficulty, ReviewLog, ReviewStats, SearchRequest, SessionOrder, SessionProgress, SessionSummary, SetDueRequest,
+    ShiftDueRequest, SimulationRequest, SimulationResult, SpacedRepetitionAlgorithm, StorageRecoveryReport, TagStats, UndoState,
+    UpdateCardRequest,
 };
 use tauri::State;
 
@@ -11,6 +14,21 @@ pub async fn create_card(service: State<'_, CardService>, request: CreateCardReq
     service.create_card(request)
 }
 
+#[tauri::command]
+pub async fn import_anki(service: State<'_, CardService>, path: String, carry_scheduling: bool) -> Result<ImportSummary, String> {
+    service.import_anki(path, carry_scheduling)
+}
+
+#[tauri::command]
+pub async fn import_csv(service: State<'_, CardService>, request: CsvImportRequest) -> Result<ImportSummary, String> {
+    service.import_csv(request)
+}
+
+#[tauri::command]
+pub async fn export_csv(service: State<'_, CardService>, request: CsvExportRequest) -> Result<usize, String> {
+    service.export_csv(request)
+}
+
 #[tauri::command]
 pub async fn get_cards(service: State<'_, CardService>) -> Result<Vec<Card>, String> {
     service.get_cards()
@@ -38,9 +56,45 @@ pub async fn get_due_cards(service: State<'_, CardService>) -> Result<Vec<Card>,
 }
 
 #[tauri::command]
-pub async fn review_card(service: State<'_, CardService>, id: String, difficulty: u8) -> Result<Card, String> {
+pub async fn review_card(service: State<'_, CardService>, id: String, difficulty: u8, time_taken_ms: Option<u64>) -> Result<Card, String> {
+    let difficulty = ReviewDifficulty::from_u8(difficulty)?;
+    service.review_card(id, difficulty, time_taken_ms)
+}
+
+#[tauri::command]
+pub async fn start_review_session(service: State<'_, CardService>, order: SessionOrder) -> Result<SessionProgress, String> {
+    service.start_review_session(order)
+}
+
+#[tauri::command]
+pub async fn next_session_card(service: State<'_, CardService>) -> Result<SessionProgress, String> {
+    service.next_session_card()
+}
+
+#[tauri::command]
+pub async fn answer_session_card(
+    service: State<'_, CardService>,
+    card_id: String,
+    difficulty: u8,
+    time_taken_ms: Option<u64>,
+) -> Result<SessionProgress, String> {
     let difficulty = ReviewDifficulty::from_u8(difficulty)?;
-    service.review_card(id, difficulty)
+    service.answer_session_card(card_id, difficulty, time_taken_ms)
+}
+
+#[tauri::command]
+pub async fn undo_session_answer(service: State<'_, CardService>) -> Result<SessionProgress, String> {
+    service.undo_session_answer()
+}
+
+#[tauri::command]
+pub async fn finish_review_session(service: State<'_, CardService>) -> Result<SessionSummary, String> {
+    service.finish_review_session()
+}
+
+#[tauri::command]
+pub async fn get_answer_time_stats(service: State<'_, CardService>) -> Result<AnswerTimeReport, String> {
+    service.get_answer_time_stats()
 }
 
 #[tauri::command]
@@ -48,6 +102,31 @@ pub async fn get_review_stats(service: State<'_, CardService>) -> Result<ReviewS
     service.get_review_stats()
 }
 
+#[tauri::command]
+pub async fn get_review_forecast(service: State<'_, CardService>, request: ForecastRequest) -> Result<Vec<ForecastDay>, String> {
+    service.get_review_forecast(request)
+}
+
+#[tauri::command]
+pub async fn simulate_schedules(service: State<'_, CardService>, request: SimulationRequest) -> Result<Vec<SimulationResult>, String> {
+    service.simulate_schedules(request)
+}
+
+#[tauri::command]
+pub async fn get_review_log(service: State<'_, CardService>, card_id: Option<String>) -> Result<Vec<ReviewLog>, String> {
+    service.get_review_log(card_id)
+}
+
+#[tauri::command]
+pub async fn get_storage_recovery_reports(service: State<'_, CardService>) -> Result<Vec<StorageRecoveryReport>, String> {
+    service.get_storage_recovery_reports()
+}
+
+#[tauri::command]
+pub async fn dismiss_storage_recovery_reports(service: State<'_, CardService>) -> Result<(), String> {
+    service.dismiss_storage_recovery_reports()
+}
+
 // Organization and search commands
 #[tauri::command]
 pub async fn search_cards(service: State<'_, CardService>, request: SearchRequest) -> Result<Vec<Card>, String> {
@@ -59,6 +138,11 @@ pub async fn get_tags(service: State<'_, CardService>) -> Result<Vec<String>, St
     service.get_tags()
 }
 
+#[tauri::command]
+pub async fn get_leeches(service: State<'_, CardService>) -> Result<Vec<Card>, String> {
+    service.get_leeches()
+}
+
 #[tauri::command]
 pub async fn get_tag_stats(service: State<'_, CardService>) -> Result<Vec<TagStats>, String> {
     service.get_tag_stats()
@@ -69,11 +153,191 @@ pub async fn bulk_update_tag(service: State<'_, CardService>, request: BulkUpdat
     service.bulk_update_tag(request)
 }
 
+#[tauri::command]
+pub async fn bulk_add_tag(service: State<'_, CardService>, request: BulkTagRequest) -> Result<Vec<Card>, String> {
+    service.bulk_add_tag(request)
+}
+
+#[tauri::command]
+pub async fn bulk_remove_tag(service: State<'_, CardService>, request: BulkTagRequest) -> Result<Vec<Card>, String> {
+    service.bulk_remove_tag(request)
+}
+
 #[tauri::command]
 pub async fn delete_multiple_cards(service: State<'_, CardService>, card_ids: Vec<String>) -> Result<(), String> {
     service.delete_multiple_cards(card_ids)
 }
 
+#[tauri::command]
+pub async fn suspend_card(service: State<'_, CardService>, id: String) -> Result<Card, String> {
+    service.suspend_cards(vec![id.clone()])?;
+    service.get_card(id)?.ok_or_else(|| "Card not found".to_string())
+}
+
+#[tauri::command]
+pub async fn suspend_cards(service: State<'_, CardService>, card_ids: Vec<String>) -> Result<Vec<Card>, String> {
+    service.suspend_cards(card_ids)
+}
+
+#[tauri::command]
+pub async fn unsuspend_card(service: State<'_, CardService>, id: String) -> Result<Card, String> {
+    service.unsuspend_cards(vec![id.clone()])?;
+    service.get_card(id)?.ok_or_else(|| "Card not found".to_string())
+}
+
+#[tauri::command]
+pub async fn unsuspend_cards(service: State<'_, CardService>, card_ids: Vec<String>) -> Result<Vec<Card>, String> {
+    service.unsuspend_cards(card_ids)
+}
+
+#[tauri::command]
+pub async fn bury_card(service: State<'_, CardService>, id: String) -> Result<Card, String> {
+    service.bury_cards(vec![id.clone()])?;
+    service.get_card(id)?.ok_or_else(|| "Card not found".to_string())
+}
+
+#[tauri::command]
+pub async fn bury_cards(service: State<'_, CardService>, card_ids: Vec<String>) -> Result<Vec<Card>, String> {
+    service.bury_cards(card_ids)
+}
+
+#[tauri::command]
+pub async fn unbury_card(service: State<'_, CardService>, id: String) -> Result<Card, String> {
+    service.unbury_cards(vec![id.clone()])?;
+    service.get_card(id)?.ok_or_else(|| "Card not found".to_string())
+}
+
+#[tauri::command]
+pub async fn unbury_cards(service: State<'_, CardService>, card_ids: Vec<String>) -> Result<Vec<Card>, String> {
+    service.unbury_cards(card_ids)
+}
+
+#[tauri::command]
+pub async fn reset_cards(service: State<'_, CardService>, card_ids: Vec<String>) -> Result<Vec<Card>, String> {
+    service.reset_cards(card_ids)
+}
+
+#[tauri::command]
+pub async fn forget_cards(service: State<'_, CardService>, card_ids: Vec<String>) -> Result<Vec<Card>, String> {
+    service.forget_cards(card_ids)
+}
+
+#[tauri::command]
+pub async fn set_due_date(service: State<'_, CardService>, request: SetDueRequest) -> Result<Vec<Card>, String> {
+    service.set_due_date(request)
+}
+
+#[tauri::command]
+pub async fn shift_due_dates(service: State<'_, CardService>, request: ShiftDueRequest) -> Result<Vec<Card>, String> {
+    service.shift_due_dates(request)
+}
+
+// Deck commands
+#[tauri::command]
+pub async fn get_decks(service: State<'_, CardService>) -> Result<Vec<Deck>, String> {
+    service.get_decks()
+}
+
+#[tauri::command]
+pub async fn create_deck(service: State<'_, CardService>, request: CreateDeckRequest) -> Result<Deck, String> {
+    service.create_deck(request)
+}
+
+#[tauri::command]
+pub async fn rename_deck(service: State<'_, CardService>, id: String, name: String) -> Result<Deck, String> {
+    service.rename_deck(id, name)
+}
+
+#[tauri::command]
+pub async fn delete_deck(service: State<'_, CardService>, id: String) -> Result<(), String> {
+    service.delete_deck(id)
+}
+
+#[tauri::command]
+pub async fn set_deck_preset(service: State<'_, CardService>, id: String, preset_id: Option<String>) -> Result<Deck, String> {
+    service.set_deck_preset(id, preset_id)
+}
+
+#[tauri::command]
+pub async fn preview_deck_preset(
+    service: State<'_, CardService>,
+    id: String,
+    preset_id: Option<String>,
+) -> Result<MigrationPreview, String> {
+    service.preview_deck_preset(id, preset_id)
+}
+
+#[tauri::command]
+pub async fn move_cards_to_deck(service: State<'_, CardService>, request: MoveCardsRequest) -> Result<Vec<Card>, String> {
+    service.move_cards_to_deck(request)
+}
+
+#[tauri::command]
+pub async fn get_presets(service: State<'_, CardService>) -> Result<Vec<DeckPreset>, String> {
+    service.get_presets()
+}
+
+#[tauri::command]
+pub async fn create_preset(service: State<'_, CardService>, request: PresetRequest) -> Result<DeckPreset, String> {
+    service.create_preset(request)
+}
+
+#[tauri::command]
+pub async fn update_preset(service: State<'_, CardService>, id: String, request: PresetRequest) -> Result<DeckPreset, String> {
+    service.update_preset(id, request)
+}
+
+#[tauri::command]
+pub async fn preview_preset_update(
+    service: State<'_, CardService>,
+    id: String,
+    request: PresetRequest,
+) -> Result<MigrationPreview, String> {
+    service.preview_preset_update(id, request)
+}
+
+#[tauri::command]
+pub async fn delete_preset(service: State<'_, CardService>, id: String) -> Result<(), String> {
+    service.delete_preset(id)
+}
+
+// Undo and redo commands
+#[tauri::command]
+pub async fn undo(service: State<'_, CardService>) -> Result<UndoState, String> {
+    service.undo()
+}
+
+#[tauri::command]
+pub async fn redo(service: State<'_, CardService>) -> Result<UndoState, String> {
+    service.redo()
+}
+
+#[tauri::command]
+pub async fn get_undo_state(service: State<'_, CardService>) -> Result<UndoState, String> {
+    service.get_undo_state()
+}
+
+// Backup and restore commands
+#[tauri::command]
+pub async fn create_backup(service: State<'_, CardService>, path: Option<String>) -> Result<BackupInfo, String> {
+    service.create_backup(path)
+}
+
+#[tauri::command]
+pub async fn list_backups(service: State<'_, CardService>) -> Result<Vec<BackupInfo>, String> {
+    service.list_backups()
+}
+
+#[tauri::command]
+pub async fn restore_backup(service: State<'_, CardService>, path: String) -> Result<BackupInfo, String> {
+    service.restore_backup(path)
+}
+
+#[tauri::command]
+pub async fn restore_cards(service: State<'_, CardService>, cards: Vec<Card>) -> Result<Vec<Card>, String> {
+    service.restore_cards(cards)
+}
+
 // Settings commands
 #[tauri::command]
 pub async fn get_settings(service: State<'_, CardService>) -> Result<AppSettings, String> {
@@ -85,18 +349,25 @@ pub async fn update_settings(service: State<'_, CardService>, settings: AppSetti
     service.update_settings(settings)
 }
 
+#[tauri::command]
+pub async fn preview_settings_update(service: State<'_, CardService>, settings: AppSettings) -> Result<MigrationPreview, String> {
+    service.preview_settings_update(settings)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::storage::Storage;
+    use crate::models::TagMatch;
+    use crate::storage::JsonStorage;
     use serial_test::serial;
+    use std::collections::BTreeSet;
     use tempfile::TempDir;
 
     // Helper to create a test card service wrapped in State-like structure
     fn create_test_service() -> (CardService, TempDir) {
         let temp_dir = TempDir::new().unwrap();
         let data_file = temp_dir.path().join("test_cards.json");
-        let storage = Storage::new_with_path(data_file);
+        let storage = JsonStorage::new_with_path(data_file);
         let service = CardService::new(storage).unwrap();
         (service, temp_dir)
     }
@@ -108,7 +379,8 @@ mod tests {
         let request = CreateCardRequest {
             front: "Test Question".to_string(),
             back: "Test Answer".to_string(),
-            tag: Some("Test".to_string()),
+            tags: BTreeSet::from(["Test".to_string()]),
+            deck_id: None,
         };
 
         let result = service.create_card(request);
@@ -117,7 +389,7 @@ mod tests {
         let card = result.unwrap();
         assert_eq!(card.front, "Test Question");
         assert_eq!(card.back, "Test Answer");
-        assert_eq!(card.tag, Some("Test".to_string()));
+        assert_eq!(card.tags, BTreeSet::from(["Test".to_string()]));
     }
 
     #[tokio::test]
@@ -134,7 +406,8 @@ mod tests {
         let request = CreateCardRequest {
             front: "Q".to_string(),
             back: "A".to_string(),
-            tag: None,
+            tags: BTreeSet::new(),
+            deck_id: None,
         };
         service.create_card(request).unwrap();
 
@@ -150,7 +423,8 @@ mod tests {
         let request = CreateCardRequest {
             front: "Question".to_string(),
             back: "Answer".to_string(),
-            tag: None,
+            tags: BTreeSet::new(),
+            deck_id: None,
         };
         let created_card = service.create_card(request).unwrap();
 
@@ -169,14 +443,16 @@ mod tests {
         let create_request = CreateCardRequest {
             front: "Original".to_string(),
             back: "Original".to_string(),
-            tag: None,
+            tags: BTreeSet::new(),
+            deck_id: None,
         };
         let created_card = service.create_card(create_request).unwrap();
 
         let update_request = UpdateCardRequest {
             front: "Updated".to_string(),
             back: "Updated".to_string(),
-            tag: Some("New Tag".to_string()),
+            tags: BTreeSet::from(["New Tag".to_string()]),
+            deck_id: None,
         };
 
         let result = service.update_card(created_card.id, update_request);
@@ -185,7 +461,7 @@ mod tests {
         let updated_card = result.unwrap();
         assert_eq!(updated_card.front, "Updated");
         assert_eq!(updated_card.back, "Updated");
-        assert_eq!(updated_card.tag, Some("New Tag".to_string()));
+        assert_eq!(updated_card.tags, BTreeSet::from(["New Tag".to_string()]));
     }
 
     #[tokio::test]
@@ -195,7 +471,8 @@ mod tests {
         let request = CreateCardRequest {
             front: "To Delete".to_string(),
             back: "Answer".to_string(),
-            tag: None,
+            tags: BTreeSet::new(),
+            deck_id: None,
         };
         let created_card = service.create_card(request).unwrap();
 
@@ -215,11 +492,12 @@ mod tests {
         let request = CreateCardRequest {
             front: "Review Test".to_string(),
             back: "Answer".to_string(),
-            tag: None,
+            tags: BTreeSet::new(),
+            deck_id: None,
         };
         let created_card = service.create_card(request).unwrap();
 
-        let result = service.review_card(created_card.id, ReviewDifficulty::Good);
+        let result = service.review_card(created_card.id, ReviewDifficulty::Good, None);
         assert!(result.is_ok());
 
         let reviewed_card = result.unwrap();
@@ -227,6 +505,27 @@ mod tests {
         assert_eq!(reviewed_card.correct_count, 1);
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_get_review_log_command() {
+        let (service, _temp_dir) = create_test_service();
+        let request = CreateCardRequest {
+            front: "Logged".to_string(),
+            back: "Answer".to_string(),
+            tags: BTreeSet::new(),
+            deck_id: None,
+        };
+        let created_card = service.create_card(request).unwrap();
+        service.review_card(created_card.id.clone(), ReviewDifficulty::Again, None).unwrap();
+
+        let result = service.get_review_log(Some(created_card.id));
+        assert!(result.is_ok());
+
+        let log = result.unwrap();
+        assert_eq!(log.len(), 1);
+        assert!(matches!(log[0].difficulty, ReviewDifficulty::Again));
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_get_due_cards_command() {
@@ -234,7 +533,8 @@ mod tests {
         let request = CreateCardRequest {
             front: "Due Card".to_string(),
             back: "Answer".to_string(),
-            tag: None,
+            tags: BTreeSet::new(),
+            deck_id: None,
         };
         service.create_card(request).unwrap();
 
@@ -263,14 +563,16 @@ mod tests {
         let request = CreateCardRequest {
             front: "Searchable content".to_string(),
             back: "Answer".to_string(),
-            tag: Some("Test".to_string()),
+            tags: BTreeSet::from(["Test".to_string()]),
+            deck_id: None,
         };
         service.create_card(request).unwrap();
 
         let search_request = SearchRequest {
             query: Some("Searchable".to_string()),
-            tag: None,
             tags: None,
+            tag_match: TagMatch::Any,
+            deck_id: None,
         };
 
         let result = service.search_cards(search_request);
@@ -285,7 +587,8 @@ mod tests {
         let request = CreateCardRequest {
             front: "Q".to_string(),
             back: "A".to_string(),
-            tag: Some("TestTag".to_string()),
+            tags: BTreeSet::from(["TestTag".to_string()]),
+            deck_id: None,
         };
         service.create_card(request).unwrap();
 
@@ -305,13 +608,14 @@ mod tests {
             .create_card(CreateCardRequest {
                 front: "Q1".to_string(),
                 back: "A1".to_string(),
-                tag: Some("Old".to_string()),
+                tags: BTreeSet::from(["Old".to_string()]),
+                deck_id: None,
             })
             .unwrap();
 
         let bulk_request = BulkUpdateRequest {
             card_ids: vec![card1.id],
-            tag: Some("New".to_string()),
+            tags: BTreeSet::from(["New".to_string()]),
         };
 
         let result = service.bulk_update_tag(bulk_request);
@@ -327,7 +631,8 @@ mod tests {
             .create_card(CreateCardRequest {
                 front: "Q1".to_string(),
                 back: "A1".to_string(),
-                tag: None,
+                tags: BTreeSet::new(),
+                deck_id: None,
             })
             .unwrap();
 
@@ -335,7 +640,8 @@ mod tests {
             .create_card(CreateCardRequest {
                 front: "Q2".to_string(),
                 back: "A2".to_string(),
-                tag: None,
+                tags: BTreeSet::new(),
+                deck_id: None,
             })
             .unwrap();
 
@@ -420,17 +726,19 @@ mod tests {
         let request = CreateCardRequest {
             front: "Test Question".to_string(),
             back: "Test Answer".to_string(),
-            tag: None,
+            tags: BTreeSet::new(),
+            deck_id: None,
         };
         let card = service.create_card(request).unwrap();
 
         // Switch to Leitner algorithm
         let mut leitner_settings = AppSettings::default();
         leitner_settings.algorithm = SpacedRepetitionAlgorithm::Leitner;
+        leitner_settings.learning_steps.clear();
         service.update_settings(leitner_settings).unwrap();
 
         // Review the card
-        let result = service.review_card(card.id.clone(), ReviewDifficulty::Good);
+        let result = service.review_card(card.id.clone(), ReviewDifficulty::Good, None);
         assert!(result.is_ok());
 
         // Verify the card was updated with Leitner-specific fields
@@ -0,0 +1,248 @@
+use crate::models::{AppSettings, Card, DeckCollection, ReviewLog};
+use chrono::{DateTime, Duration, Local, NaiveTime, Utc};
+use std::collections::HashMap;
+
+/// Start of the local calendar day containing `now`; daily limits reset at local midnight
+pub fn start_of_day(now: DateTime<Utc>) -> DateTime<Utc> {
+    let midnight = now.with_timezone(&Local).date_naive().and_time(NaiveTime::MIN);
+    midnight
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|start| start.with_timezone(&Utc))
+        // Midnight skipped by a DST change; treat the day as starting at midnight UTC instead
+        .unwrap_or_else(|| midnight.and_utc())
+}
+
+/// Start of the local calendar day after the one containing `now`, when buried cards come back
+pub fn start_of_next_day(now: DateTime<Utc>) -> DateTime<Utc> {
+    start_of_day_in(now, 1)
+}
+
+/// Start of the local calendar day `days` after the one containing `now`
+pub fn start_of_day_in(now: DateTime<Utc>, days: i64) -> DateTime<Utc> {
+    // Local days last 23 to 25 hours, so noon is always within an hour of twelve hours after midnight
+    start_of_day(start_of_day(now) + Duration::hours(12) + Duration::days(days))
+}
+
+/// Cards already studied today under one limit
+#[derive(Debug, Default, Clone, Copy)]
+struct Usage {
+    new_cards: u32,
+    reviews: u32,
+}
+
+impl Usage {
+    fn add(&mut self, is_new: bool) {
+        if is_new {
+            self.new_cards += 1;
+        } else {
+            self.reviews += 1;
+        }
+    }
+
+    fn allows(&self, settings: &AppSettings, is_new: bool) -> bool {
+        if is_new {
+            self.new_cards < settings.new_cards_per_day
+        } else {
+            self.reviews < settings.reviews_per_day
+        }
+    }
+}
+
+/// Tracks the global daily limits together with the limits of every deck preset. A card counts
+/// against the global limits and, when its deck resolves to a preset, against that preset's limits.
+pub struct DailyLimits<'a> {
+    settings: &'a AppSettings,
+    decks: &'a DeckCollection,
+    global: Usage,
+    presets: HashMap<String, Usage>,
+}
+
+impl<'a> DailyLimits<'a> {
+    /// Limits with today's reviews, taken from `todays_log`, already counted
+    pub fn new(settings: &'a AppSettings, decks: &'a DeckCollection, cards: &HashMap<String, Card>, todays_log: &[ReviewLog]) -> Self {
+        let mut limits = DailyLimits {
+            settings,
+            decks,
+            global: Usage::default(),
+            presets: HashMap::new(),
+        };
+        for entry in todays_log.iter().filter(|entry| !entry.learning) {
+            let deck_id = cards.get(&entry.card_id).and_then(|card| card.deck_id.as_deref());
+            limits.count(deck_id, entry.was_new);
+        }
+        limits
+    }
+
+    fn count(&mut self, deck_id: Option<&str>, is_new: bool) {
+        self.global.add(is_new);
+        if let Some(preset) = self.decks.preset_for(deck_id) {
+            self.presets.entry(preset.id.clone()).or_default().add(is_new);
+        }
+    }
+
+    fn allows(&self, deck_id: Option<&str>, is_new: bool) -> bool {
+        if !self.global.allows(self.settings, is_new) {
+            return false;
+        }
+        match self.decks.preset_for(deck_id) {
+            Some(preset) => self
+                .presets
+                .get(&preset.id)
+                .copied()
+                .unwrap_or_default()
+                .allows(&preset.options, is_new),
+            None => true,
+        }
+    }
+
+    /// The due cards that still fit in today's limits. Cards in (re)learning are never limited:
+    /// those already due come first, then reviews, most overdue first, then new cards in the order
+    /// they were created, and last the learning cards that are only due a few minutes from now.
+    pub fn apply(mut self, due_cards: Vec<Card>, now: DateTime<Utc>) -> Vec<Card> {
+        let (mut learning, due_cards): (Vec<Card>, Vec<Card>) = due_cards.into_iter().partition(|card| card.state.is_learning());
+        let (mut new_cards, mut reviews): (Vec<Card>, Vec<Card>) = due_cards.into_iter().partition(|card| card.review_count == 0);
+        learning.sort_by_key(|card| card.next_review);
+        reviews.sort_by_key(|card| card.next_review);
+        new_cards.sort_by_key(|card| card.created_at);
+
+        let (mut allowed, learning_ahead): (Vec<Card>, Vec<Card>) = learning.into_iter().partition(|card| card.next_review <= now);
+        for card in reviews.into_iter().chain(new_cards) {
+            let is_new = card.review_count == 0;
+            if self.allows(card.deck_id.as_deref(), is_new) {
+                self.count(card.deck_id.as_deref(), is_new);
+                allowed.push(card);
+            }
+        }
+        allowed.extend(learning_ahead);
+        allowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CardState, DeckPreset};
+
+    fn create_test_card(id: &str, review_count: u32, deck_id: Option<&str>) -> Card {
+        Card {
+            deck_id: deck_id.map(str::to_string),
+            next_review: Utc::now() - Duration::hours(1),
+            review_count,
+            state: if review_count > 0 { CardState::Review } else { CardState::New },
+            ..Card::new_for_test(id)
+        }
+    }
+
+    fn create_test_log(card_id: &str, was_new: bool) -> ReviewLog {
+        ReviewLog {
+            was_new,
+            ..ReviewLog::new_for_test(card_id)
+        }
+    }
+
+    fn settings(new_cards_per_day: u32, reviews_per_day: u32) -> AppSettings {
+        AppSettings {
+            new_cards_per_day,
+            reviews_per_day,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_start_of_day() {
+        let now = Utc::now();
+        let start = start_of_day(now);
+        assert!(start <= now);
+        assert!(now - start < Duration::hours(25));
+        assert_eq!(start_of_day(start), start);
+
+        let next = start_of_next_day(now);
+        assert!(next > now);
+        assert_eq!(start_of_day(next), next);
+        assert_eq!(start_of_day(next - Duration::seconds(1)), start);
+        assert_eq!(start_of_day_in(now, 0), start);
+        assert_eq!(start_of_day_in(now, 2), start_of_next_day(next));
+        assert_eq!(start_of_day_in(now, -1), start_of_day(start - Duration::seconds(1)));
+    }
+
+    #[test]
+    fn test_limits_new_cards_and_reviews() {
+        let decks = DeckCollection::default();
+        let settings = settings(2, 1);
+        let due: Vec<Card> = (0..4)
+            .map(|i| create_test_card(&format!("new{}", i), 0, None))
+            .chain((0..3).map(|i| create_test_card(&format!("review{}", i), 3, None)))
+            .collect();
+
+        let allowed = DailyLimits::new(&settings, &decks, &HashMap::new(), &[]).apply(due, Utc::now());
+
+        assert_eq!(allowed.len(), 3);
+        assert_eq!(allowed[0].review_count, 3); // Reviews come before new cards
+        assert_eq!(allowed.iter().filter(|card| card.review_count == 0).count(), 2);
+    }
+
+    #[test]
+    fn test_todays_reviews_count_against_limits() {
+        let decks = DeckCollection::default();
+        let settings = settings(2, 5);
+        let studied = create_test_card("studied", 1, None);
+        let cards = HashMap::from([(studied.id.clone(), studied)]);
+        let log = vec![create_test_log("studied", true), create_test_log("studied", false)];
+        let due = vec![
+            create_test_card("a", 0, None),
+            create_test_card("b", 0, None),
+            create_test_card("c", 2, None),
+        ];
+
+        let allowed = DailyLimits::new(&settings, &decks, &cards, &log).apply(due, Utc::now());
+
+        // One of the two new cards was already used up by the earlier first review
+        assert_eq!(allowed.len(), 2);
+        assert_eq!(allowed.iter().filter(|card| card.review_count == 0).count(), 1);
+    }
+
+    #[test]
+    fn test_preset_limits_apply_to_their_decks() {
+        let mut decks = DeckCollection::default();
+        let spanish = decks.ensure_deck("Languages::Spanish").unwrap();
+        decks.presets.push(DeckPreset {
+            id: "slow".to_string(),
+            name: "Slow".to_string(),
+            options: settings(1, 200),
+        });
+        decks.decks.iter_mut().find(|deck| deck.name == "Languages").unwrap().preset_id = Some("slow".to_string());
+        let settings = settings(3, 200);
+        let due = vec![
+            create_test_card("a", 0, Some(&spanish)),
+            create_test_card("b", 0, Some(&spanish)),
+            create_test_card("c", 0, None),
+            create_test_card("d", 0, None),
+            create_test_card("e", 0, None),
+        ];
+
+        let allowed = DailyLimits::new(&settings, &decks, &HashMap::new(), &[]).apply(due, Utc::now());
+
+        // The preset allows one card from the deck, and the global limit caps the total at three
+        assert_eq!(allowed.len(), 3);
+        assert_eq!(allowed.iter().filter(|card| card.deck_id.is_some()).count(), 1);
+    }
+
+    #[test]
+    fn test_learning_cards_are_not_limited() {
+        let decks = DeckCollection::default();
+        let settings = settings(0, 0);
+        let mut learning = create_test_card("learning", 1, None);
+        learning.state = CardState::Learning;
+        let mut ahead = create_test_card("ahead", 1, None);
+        ahead.state = CardState::Relearning;
+        ahead.next_review = Utc::now() + Duration::minutes(5);
+        let due = vec![ahead, create_test_card("review", 3, None), learning];
+
+        let allowed = DailyLimits::new(&settings, &decks, &HashMap::new(), &[]).apply(due, Utc::now());
+
+        // Cards only due a few minutes from now come last
+        let ids: Vec<&str> = allowed.iter().map(|card| card.id.as_str()).collect();
+        assert_eq!(ids, vec!["learning", "ahead"]);
+    }
+}
@@ -0,0 +1,167 @@
+use crate::models::{AppSettings, Deck, DeckCollection, DeckPreset};
+use chrono::Utc;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Separates the levels of a nested deck name, as in Anki: `Languages::Spanish::Verbs`
+pub const DECK_SEPARATOR: &str = "::";
+
+/// Trim every level of a deck name. Names with an empty level are rejected.
+pub fn normalize_deck_name(name: &str) -> Result<String, String> {
+    let levels: Vec<&str> = name.split(DECK_SEPARATOR).map(str::trim).collect();
+    if levels.iter().any(|level| level.is_empty()) {
+        return Err(format!("Invalid deck name '{}'", name));
+    }
+    Ok(levels.join(DECK_SEPARATOR))
+}
+
+/// Name of the deck one level up, if any
+pub fn parent_name(name: &str) -> Option<&str> {
+    name.rfind(DECK_SEPARATOR).map(|index| &name[..index])
+}
+
+/// Whether `name` is `ancestor` itself or nested somewhere below it
+pub fn is_within(name: &str, ancestor: &str) -> bool {
+    name == ancestor || (name.starts_with(ancestor) && name[ancestor.len()..].starts_with(DECK_SEPARATOR))
+}
+
+impl DeckCollection {
+    pub fn deck(&self, id: &str) -> Option<&Deck> {
+        self.decks.iter().find(|deck| deck.id == id)
+    }
+
+    pub fn deck_by_name(&self, name: &str) -> Option<&Deck> {
+        self.decks.iter().find(|deck| deck.name == name)
+    }
+
+    pub fn preset(&self, id: &str) -> Option<&DeckPreset> {
+        self.presets.iter().find(|preset| preset.id == id)
+    }
+
+    /// Id of the deck called `name`, creating it and any missing parent decks
+    pub fn ensure_deck(&mut self, name: &str) -> Result<String, String> {
+        let name = normalize_deck_name(name)?;
+        if let Some(deck) = self.deck_by_name(&name) {
+            return Ok(deck.id.clone());
+        }
+
+        if let Some(parent) = parent_name(&name) {
+            self.ensure_deck(parent)?;
+        }
+        let deck = Deck {
+            id: Uuid::new_v4().to_string(),
+            name,
+            preset_id: None,
+            created_at: Utc::now(),
+        };
+        let id = deck.id.clone();
+        self.decks.push(deck);
+        Ok(id)
+    }
+
+    /// Ids of the deck and every deck nested below it
+    pub fn subtree_ids(&self, id: &str) -> HashSet<String> {
+        match self.deck(id) {
+            Some(root) => self
+                .decks
+                .iter()
+                .filter(|deck| is_within(&deck.name, &root.name))
+                .map(|deck| deck.id.clone())
+                .collect(),
+            None => HashSet::new(),
+        }
+    }
+
+    /// Preset of the deck, or of the nearest parent deck that has one
+    pub fn preset_for(&self, deck_id: Option<&str>) -> Option<&DeckPreset> {
+        let mut name = deck_id.and_then(|id| self.deck(id)).map(|deck| deck.name.as_str());
+
+        while let Some(current) = name {
+            let preset = self
+                .deck_by_name(current)
+                .and_then(|deck| deck.preset_id.as_deref())
+                .and_then(|preset_id| self.preset(preset_id));
+            if preset.is_some() {
+                return preset;
+            }
+            name = parent_name(current);
+        }
+
+        None
+    }
+
+    /// Scheduling options for a card in `deck_id`: the options of its preset, falling back to
+    /// `default` for cards outside any deck with a preset
+    pub fn options_for<'a>(&'a self, deck_id: Option<&str>, default: &'a AppSettings) -> &'a AppSettings {
+        self.preset_for(deck_id).map_or(default, |preset| &preset.options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SpacedRepetitionAlgorithm;
+
+    #[test]
+    fn test_normalize_deck_name() {
+        assert_eq!(normalize_deck_name(" Languages :: Spanish ").unwrap(), "Languages::Spanish");
+        assert!(normalize_deck_name("Languages::").is_err());
+        assert!(normalize_deck_name("  ").is_err());
+    }
+
+    #[test]
+    fn test_nesting() {
+        assert_eq!(parent_name("Languages::Spanish::Verbs"), Some("Languages::Spanish"));
+        assert_eq!(parent_name("Languages"), None);
+        assert!(is_within("Languages::Spanish", "Languages"));
+        assert!(is_within("Languages", "Languages"));
+        assert!(!is_within("Languages2", "Languages"));
+    }
+
+    #[test]
+    fn test_ensure_deck_creates_parents() {
+        let mut collection = DeckCollection::default();
+        let verbs = collection.ensure_deck("Languages::Spanish::Verbs").unwrap();
+        assert_eq!(collection.decks.len(), 3);
+
+        // Existing decks are reused
+        assert_eq!(collection.ensure_deck("Languages::Spanish::Verbs").unwrap(), verbs);
+        collection.ensure_deck("Languages::French").unwrap();
+        assert_eq!(collection.decks.len(), 4);
+
+        let languages = collection.deck_by_name("Languages").unwrap().id.clone();
+        assert_eq!(collection.subtree_ids(&languages).len(), 4);
+        assert_eq!(collection.subtree_ids(&verbs).len(), 1);
+    }
+
+    #[test]
+    fn test_options_are_inherited_from_parent_decks() {
+        let mut collection = DeckCollection::default();
+        let verbs = collection.ensure_deck("Languages::Spanish::Verbs").unwrap();
+        let default = AppSettings::default();
+        assert_eq!(
+            collection.options_for(Some(&verbs), &default).algorithm,
+            SpacedRepetitionAlgorithm::SM2
+        );
+
+        collection.presets.push(DeckPreset {
+            id: "cram".to_string(),
+            name: "Cram".to_string(),
+            options: AppSettings {
+                algorithm: SpacedRepetitionAlgorithm::Leitner,
+                ..Default::default()
+            },
+        });
+        collection.decks.iter_mut().find(|deck| deck.name == "Languages").unwrap().preset_id = Some("cram".to_string());
+
+        assert_eq!(
+            collection.options_for(Some(&verbs), &default).algorithm,
+            SpacedRepetitionAlgorithm::Leitner
+        );
+        assert_eq!(collection.options_for(None, &default).algorithm, SpacedRepetitionAlgorithm::SM2);
+        assert_eq!(
+            collection.options_for(Some("missing"), &default).algorithm,
+            SpacedRepetitionAlgorithm::SM2
+        );
+    }
+}
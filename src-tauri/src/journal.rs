@@ -0,0 +1,148 @@
+use crate::models::{Card, ReviewLog, UndoState};
+
+// Oldest operations are dropped once the journal grows past this
+const JOURNAL_LIMIT: usize = 100;
+
+/// State of one card before and after an operation; `None` means the card did not exist
+#[derive(Debug, Clone)]
+pub struct CardChange {
+    pub card_id: String,
+    pub before: Option<Card>,
+    pub after: Option<Card>,
+}
+
+/// A reversible card mutation
+#[derive(Debug, Clone)]
+pub struct Operation {
+    pub description: String,
+    pub changes: Vec<CardChange>,
+    pub review_log_entry: Option<ReviewLog>, // Written by a review; removed again on undo
+}
+
+/// Undo and redo stacks of the operations performed in this session
+#[derive(Debug, Default)]
+pub struct Journal {
+    undo_stack: Vec<Operation>,
+    redo_stack: Vec<Operation>,
+}
+
+impl Journal {
+    /// Record a new operation. Anything that could be redone is discarded.
+    pub fn record(&mut self, operation: Operation) {
+        if operation.changes.is_empty() {
+            return;
+        }
+        self.redo_stack.clear();
+        self.push_undo(operation);
+    }
+
+    pub fn pop_undo(&mut self) -> Option<Operation> {
+        self.undo_stack.pop()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<Operation> {
+        self.redo_stack.pop()
+    }
+
+    /// Make an operation available to undo again, without touching the redo stack
+    pub fn push_undo(&mut self, operation: Operation) {
+        self.undo_stack.push(operation);
+        if self.undo_stack.len() > JOURNAL_LIMIT {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    /// The operation `pop_undo` would return
+    pub fn last_undo(&self) -> Option<&Operation> {
+        self.undo_stack.last()
+    }
+
+    pub fn push_redo(&mut self, operation: Operation) {
+        self.redo_stack.push(operation);
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    pub fn state(&self) -> UndoState {
+        UndoState {
+            can_undo: !self.undo_stack.is_empty(),
+            can_redo: !self.redo_stack.is_empty(),
+            undo_description: self.undo_stack.last().map(|operation| operation.description.clone()),
+            redo_description: self.redo_stack.last().map(|operation| operation.description.clone()),
+        }
+    }
+}
+
+/// Describe an operation on `count` cards, e.g. "Delete card" or "Delete 3 cards"
+pub fn describe(action: &str, count: usize) -> String {
+    if count == 1 {
+        format!("{} card", action)
+    } else {
+        format!("{} {} cards", action, count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_operation(description: &str) -> Operation {
+        Operation {
+            description: description.to_string(),
+            changes: vec![CardChange {
+                card_id: "1".to_string(),
+                before: None,
+                after: None,
+            }],
+            review_log_entry: None,
+        }
+    }
+
+    #[test]
+    fn test_record_clears_redo() {
+        let mut journal = Journal::default();
+        journal.record(create_operation("first"));
+        let operation = journal.pop_undo().unwrap();
+        journal.push_redo(operation);
+        assert!(journal.state().can_redo);
+
+        journal.record(create_operation("second"));
+        let state = journal.state();
+        assert!(!state.can_redo);
+        assert_eq!(state.undo_description, Some("second".to_string()));
+    }
+
+    #[test]
+    fn test_empty_operations_are_not_recorded() {
+        let mut journal = Journal::default();
+        journal.record(Operation {
+            description: "nothing".to_string(),
+            changes: Vec::new(),
+            review_log_entry: None,
+        });
+        assert!(!journal.state().can_undo);
+    }
+
+    #[test]
+    fn test_journal_limit() {
+        let mut journal = Journal::default();
+        for i in 0..JOURNAL_LIMIT + 5 {
+            journal.record(create_operation(&i.to_string()));
+        }
+
+        let mut count = 0;
+        while journal.pop_undo().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, JOURNAL_LIMIT);
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(describe("Delete", 1), "Delete card");
+        assert_eq!(describe("Delete", 3), "Delete 3 cards");
+    }
+}
@@ -1,11 +1,28 @@
+mod anki_import;
+mod answer_time;
+mod backup;
+mod card_csv;
 mod card_service;
+mod clock;
 mod commands;
+mod daily_limits;
+mod decks;
+mod journal;
+mod load_balancer;
 mod models;
+mod review_session;
+mod scheduler;
+mod simulator;
 mod spaced_repetition;
+mod sqlite_storage;
 mod storage;
 
+// Everything needed to add a scheduling algorithm, registered before `run`
+pub use models::{AppSettings, Card, ReviewDifficulty, SpacedRepetitionAlgorithm};
+pub use scheduler::{Scheduled, Scheduler, SchedulerRegistry, SchedulingState};
+
 use card_service::CardService;
-use storage::Storage;
+use sqlite_storage::SqliteStorage;
 use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -14,6 +31,9 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             // Card management commands
             commands::create_card,
+            commands::import_anki,
+            commands::import_csv,
+            commands::export_csv,
             commands::get_cards,
             commands::get_card,
             commands::update_card,
@@ -21,21 +41,70 @@ pub fn run() {
             // Review session commands
             commands::get_due_cards,
             commands::review_card,
+            commands::start_review_session,
+            commands::next_session_card,
+            commands::answer_session_card,
+            commands::undo_session_answer,
+            commands::finish_review_session,
             commands::get_review_stats,
+            commands::get_answer_time_stats,
+            commands::get_review_forecast,
+            commands::simulate_schedules,
+            commands::get_review_log,
+            commands::get_storage_recovery_reports,
+            commands::dismiss_storage_recovery_reports,
             // Organization and search commands
             commands::search_cards,
             commands::get_tags,
             commands::get_tag_stats,
+            commands::get_leeches,
             commands::bulk_update_tag,
+            commands::bulk_add_tag,
+            commands::bulk_remove_tag,
             commands::delete_multiple_cards,
+            commands::suspend_card,
+            commands::suspend_cards,
+            commands::unsuspend_card,
+            commands::unsuspend_cards,
+            commands::bury_card,
+            commands::bury_cards,
+            commands::unbury_card,
+            commands::unbury_cards,
+            commands::reset_cards,
+            commands::forget_cards,
+            commands::set_due_date,
+            commands::shift_due_dates,
+            // Deck commands
+            commands::get_decks,
+            commands::create_deck,
+            commands::rename_deck,
+            commands::delete_deck,
+            commands::set_deck_preset,
+            commands::preview_deck_preset,
+            commands::move_cards_to_deck,
+            commands::get_presets,
+            commands::create_preset,
+            commands::update_preset,
+            commands::preview_preset_update,
+            commands::delete_preset,
+            // Undo and redo commands
+            commands::undo,
+            commands::redo,
+            commands::get_undo_state,
+            // Backup and restore commands
+            commands::create_backup,
+            commands::list_backups,
+            commands::restore_backup,
+            commands::restore_cards,
             // Settings commands
             commands::get_settings,
             commands::update_settings,
+            commands::preview_settings_update,
         ])
         .setup(|app| {
             // Initialize storage and card service
-            let storage = Storage::new(app.handle().clone()).expect("Failed to initialize storage");
-            let card_service = CardService::new(storage).expect("Failed to initialize card service");
+            let storage = SqliteStorage::new(app.handle().clone()).expect("Failed to initialize storage");
+            let card_service = CardService::with_clock(storage, clock::app_clock()).expect("Failed to initialize card service");
             app.manage(card_service);
 
             if cfg!(debug_assertions) {
@@ -0,0 +1,197 @@
+use crate::models::{AppSettings, Card, CardState};
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::RangeInclusive;
+
+/// Shorter intervals are kept as they are; moving them by a day would change them too much
+const MIN_ADJUSTED_INTERVAL: i64 = 3;
+/// The tolerance window never reaches further than a quarter of the interval either way
+const MAX_TOLERANCE: f64 = 0.25;
+
+/// Review cards due on each local calendar day, the load the balancer evens out
+#[derive(Debug, Default, Clone)]
+pub struct ReviewLoad {
+    due: HashMap<NaiveDate, u32>,
+}
+
+impl ReviewLoad {
+    pub fn from_cards<'a>(cards: impl IntoIterator<Item = &'a Card>) -> Self {
+        let mut load = ReviewLoad::default();
+        for card in cards {
+            load.add(card);
+        }
+        load
+    }
+
+    /// New cards have no due date worth balancing against
+    fn due_date(card: &Card) -> Option<NaiveDate> {
+        (card.state != CardState::New).then(|| card.next_review.with_timezone(&Local).date_naive())
+    }
+
+    pub fn add(&mut self, card: &Card) {
+        if let Some(date) = Self::due_date(card) {
+            *self.due.entry(date).or_default() += 1;
+        }
+    }
+
+    pub fn remove(&mut self, card: &Card) {
+        if let Some(count) = Self::due_date(card).and_then(|date| self.due.get_mut(&date)) {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    pub fn due_on(&self, date: NaiveDate) -> u32 {
+        self.due.get(&date).copied().unwrap_or(0)
+    }
+}
+
+/// Intervals an interval may be moved to: give or take `tolerance` of it, but at least a day
+pub fn tolerance_window(interval: i64, tolerance: f64) -> RangeInclusive<i64> {
+    let delta = ((interval as f64 * tolerance.clamp(0.0, MAX_TOLERANCE)).round() as i64).max(1);
+    (interval - delta).max(1)..=interval + delta
+}
+
+/// The interval to schedule in place of the `interval` an algorithm computed. Load balancing keeps
+/// to the least busy days of the tolerance window, preferring the computed interval itself; fuzz
+/// then picks among them at random, so cards answered together do not all come back together.
+pub fn adjust_interval(card: &Card, interval: i64, settings: &AppSettings, now: DateTime<Utc>, load: &ReviewLoad) -> i64 {
+    if !(settings.interval_fuzz || settings.load_balancing) || interval < MIN_ADJUSTED_INTERVAL {
+        return interval;
+    }
+
+    let mut candidates: Vec<i64> = tolerance_window(interval, settings.fuzz_tolerance).collect();
+    if settings.load_balancing {
+        let due_on = |days: i64| load.due_on((now + Duration::days(days)).with_timezone(&Local).date_naive());
+        let lightest = candidates.iter().map(|&days| due_on(days)).min().unwrap_or(0);
+        candidates.retain(|&days| due_on(days) == lightest);
+    }
+
+    if settings.interval_fuzz {
+        candidates[(fuzz_seed(card) % candidates.len() as u64) as usize]
+    } else {
+        // Closest to the computed interval, the earlier day on a tie
+        candidates
+            .into_iter()
+            .min_by_key(|&days| ((days - interval).abs(), days))
+            .unwrap_or(interval)
+    }
+}
+
+/// Stable for a card and review, so answering the same card the same way gives the same interval
+fn fuzz_seed(card: &Card) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    card.id.hash(&mut hasher);
+    card.review_count.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::collections::BTreeSet;
+
+    fn create_test_card(id: &str, next_review: DateTime<Utc>) -> Card {
+        Card {
+            created_at: next_review,
+            next_review,
+            interval: 10,
+            review_count: 3,
+            correct_count: 3,
+            state: CardState::Review,
+            ..Card::new_for_test(id)
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()
+    }
+
+    fn settings(interval_fuzz: bool, load_balancing: bool) -> AppSettings {
+        AppSettings {
+            interval_fuzz,
+            load_balancing,
+            fuzz_tolerance: 0.1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_tolerance_window() {
+        assert_eq!(tolerance_window(20, 0.1), 18..=22);
+        assert_eq!(tolerance_window(3, 0.05), 2..=4); // At least a day either way
+        assert_eq!(tolerance_window(100, 0.9), 75..=125); // Capped at a quarter
+    }
+
+    #[test]
+    fn test_disabled_or_short_intervals_are_kept() {
+        let card = create_test_card("a", now());
+        let load = ReviewLoad::default();
+        assert_eq!(adjust_interval(&card, 20, &settings(false, false), now(), &load), 20);
+        assert_eq!(adjust_interval(&card, 2, &settings(true, true), now(), &load), 2);
+    }
+
+    #[test]
+    fn test_fuzz_stays_within_window_and_spreads_cards() {
+        let settings = settings(true, false);
+        let intervals: BTreeSet<i64> = (0..50)
+            .map(|i| {
+                adjust_interval(
+                    &create_test_card(&i.to_string(), now()),
+                    20,
+                    &settings,
+                    now(),
+                    &ReviewLoad::default(),
+                )
+            })
+            .collect();
+
+        assert!(intervals.iter().all(|interval| tolerance_window(20, 0.1).contains(interval)));
+        assert!(intervals.len() > 1);
+
+        // The same card and review always get the same interval
+        let card = create_test_card("a", now());
+        let first = adjust_interval(&card, 20, &settings, now(), &ReviewLoad::default());
+        assert_eq!(adjust_interval(&card, 20, &settings, now(), &ReviewLoad::default()), first);
+    }
+
+    #[test]
+    fn test_load_balancing_picks_the_lightest_day() {
+        let busy: Vec<Card> = [18, 19, 20, 20, 21]
+            .iter()
+            .enumerate()
+            .map(|(i, &days)| create_test_card(&i.to_string(), now() + Duration::days(days)))
+            .collect();
+        let load = ReviewLoad::from_cards(&busy);
+        let card = create_test_card("answered", now());
+
+        // Day 22 is the only empty day in the window
+        assert_eq!(adjust_interval(&card, 20, &settings(false, true), now(), &load), 22);
+        assert_eq!(adjust_interval(&card, 20, &settings(true, true), now(), &load), 22);
+
+        // With an even load the computed interval is kept
+        assert_eq!(
+            adjust_interval(&card, 20, &settings(false, true), now(), &ReviewLoad::default()),
+            20
+        );
+    }
+
+    #[test]
+    fn test_review_load_counts() {
+        let mut due = create_test_card("due", now());
+        let mut load = ReviewLoad::from_cards([&due]);
+        let today = now().with_timezone(&Local).date_naive();
+        assert_eq!(load.due_on(today), 1);
+
+        load.remove(&due);
+        assert_eq!(load.due_on(today), 0);
+        load.remove(&due); // Never below zero
+        assert_eq!(load.due_on(today), 0);
+
+        due.state = CardState::New;
+        load.add(&due);
+        assert_eq!(load.due_on(today), 0);
+    }
+}
@@ -1,12 +1,17 @@
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Card {
     pub id: String,
     pub front: String,
     pub back: String,
-    pub tag: Option<String>,
+    // Cards saved before multiple tags were supported have a single, optional `tag`
+    #[serde(default, alias = "tag", deserialize_with = "deserialize_tags")]
+    pub tags: BTreeSet<String>,
+    #[serde(default)]
+    pub deck_id: Option<String>, // Cards outside any deck use the global settings
     pub created_at: DateTime<Utc>,
     pub last_reviewed: Option<DateTime<Utc>>,
     pub next_review: DateTime<Utc>,
@@ -14,13 +19,112 @@ pub struct Card {
     pub ease_factor: f64, // SM-2 ease factor
     pub review_count: u32,
     pub correct_count: u32,
+    #[serde(default)]
+    pub lapses: u32, // Times the card was forgotten after graduating
+    #[serde(default)]
+    pub last_answer_ms: Option<u64>, // Time spent on the last answer, when reported
     // Leitner system fields
     pub leitner_box: u32, // Current box (0-based)
     // Exponential algorithm fields
     pub exponential_factor: f64, // Current exponential factor
+    // FSRS fields (0.0 until the card is first reviewed with FSRS)
+    #[serde(default)]
+    pub fsrs_stability: f64, // Days until recall probability drops to 90%
+    #[serde(default)]
+    pub fsrs_difficulty: f64, // Intrinsic difficulty (1.0 - 10.0)
+    // State of algorithms without fields of their own, by algorithm name
+    #[serde(default)]
+    pub scheduler_state: BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub state: CardState,
+    #[serde(default)]
+    pub learning_step: u32, // Index into the learning or relearning steps while in those states
+    #[serde(default)]
+    pub suspended: bool, // Left out of reviews until unsuspended
+    #[serde(default)]
+    pub buried_until: Option<DateTime<Utc>>, // Left out of reviews until this time, the start of the next local day
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Card {
+    /// Buried cards come back on their own once the day they were buried on is over
+    pub fn is_buried(&self, now: DateTime<Utc>) -> bool {
+        self.buried_until.is_some_and(|until| now < until)
+    }
+
+    /// Cards saved before learning steps existed load as `New`; those already reviewed are in review
+    pub fn infer_legacy_state(&mut self) {
+        if self.state == CardState::New && self.review_count > 0 {
+            self.state = CardState::Review;
+        }
+    }
+
+    /// A fresh new card for tests; override the fields a test cares about with struct update syntax
+    #[cfg(test)]
+    pub fn new_for_test(id: &str) -> Card {
+        let now = Utc::now();
+        Card {
+            id: id.to_string(),
+            front: format!("Question {}", id),
+            back: format!("Answer {}", id),
+            tags: BTreeSet::new(),
+            deck_id: None,
+            created_at: now,
+            last_reviewed: None,
+            next_review: now,
+            interval: 0,
+            ease_factor: 2.5,
+            review_count: 0,
+            correct_count: 0,
+            lapses: 0,
+            last_answer_ms: None,
+            leitner_box: 0,
+            exponential_factor: 1.0,
+            fsrs_stability: 0.0,
+            fsrs_difficulty: 0.0,
+            scheduler_state: BTreeMap::new(),
+            state: CardState::New,
+            learning_step: 0,
+            suspended: false,
+            buried_until: None,
+        }
+    }
+}
+
+/// Where a card is in its life cycle. New cards go through the learning steps before their first
+/// interval in days; a review card that is forgotten goes through the relearning steps.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CardState {
+    #[default]
+    New,
+    Learning,
+    Review,
+    Relearning,
+}
+
+impl CardState {
+    /// Learning and relearning cards are scheduled in minutes rather than days
+    pub fn is_learning(self) -> bool {
+        matches!(self, CardState::Learning | CardState::Relearning)
+    }
+}
+
+/// Accept both the current list of tags and the legacy single `tag`, which may be null
+fn deserialize_tags<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BTreeSet<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum TagsField {
+        Many(BTreeSet<String>),
+        Single(Option<String>),
+    }
+
+    let tags = match TagsField::deserialize(deserializer)? {
+        TagsField::Many(tags) => tags,
+        TagsField::Single(tag) => tag.into_iter().collect(),
+    };
+    Ok(tags.into_iter().filter(|tag| !tag.trim().is_empty()).collect())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[repr(u8)]
 pub enum ReviewDifficulty {
     Again = 0, // Complete failure
@@ -41,6 +145,61 @@ impl ReviewDifficulty {
     }
 }
 
+/// A single entry in the append-only review history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewLog {
+    pub id: String,
+    pub card_id: String,
+    pub reviewed_at: DateTime<Utc>,
+    pub difficulty: ReviewDifficulty,
+    pub previous_interval: i64, // days
+    pub new_interval: i64,      // days
+    pub previous_ease_factor: f64,
+    pub new_ease_factor: f64,
+    pub algorithm: SpacedRepetitionAlgorithm,
+    pub time_taken_ms: Option<u64>, // Time spent on the card, when reported
+    #[serde(default)]
+    pub was_new: bool, // First review of the card, counted against the daily new-card limit
+    #[serde(default)]
+    pub learning: bool, // Answered during learning or relearning steps, which no daily limit counts
+    #[serde(default)]
+    pub scheduled_as: Option<ReviewDifficulty>, // Answer used for scheduling when it differs, e.g. a slow Good scheduled as Hard
+}
+
+impl ReviewLog {
+    /// A Good SM-2 review taking a card from 0 to 1 day, for tests; override the fields a test cares
+    /// about with struct update syntax
+    #[cfg(test)]
+    pub fn new_for_test(card_id: &str) -> ReviewLog {
+        ReviewLog {
+            id: format!("log-{}", card_id),
+            card_id: card_id.to_string(),
+            reviewed_at: Utc::now(),
+            difficulty: ReviewDifficulty::Good,
+            previous_interval: 0,
+            new_interval: 1,
+            previous_ease_factor: 2.5,
+            new_ease_factor: 2.5,
+            algorithm: SpacedRepetitionAlgorithm::SM2,
+            time_taken_ms: None,
+            was_new: false,
+            learning: false,
+            scheduled_as: None,
+        }
+    }
+}
+
+/// Describes a data file that was damaged and had to be recovered at startup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageRecoveryReport {
+    pub file: String,                   // Name of the damaged file, e.g. "cards.json"
+    pub error: String,                  // Parse error that triggered the recovery
+    pub quarantined_to: Option<String>, // Preserved copy of the damaged file, if it could be made
+    pub recovered_items: usize,         // Cards (or settings fields) that were salvaged
+    pub lost_items: usize,              // Entries that could not be read
+    pub detected_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ReviewStats {
     pub total_cards: usize,
@@ -48,33 +207,250 @@ pub struct ReviewStats {
     pub cards_new: usize,
     pub cards_learning: usize,
     pub cards_mature: usize,
+    pub cards_suspended: usize,
+    pub cards_buried: usize,
+    pub new_cards_remaining: usize, // New cards still available today under the daily limits
+    pub reviews_remaining: usize,   // Reviews still available today under the daily limits
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateCardRequest {
     pub front: String,
     pub back: String,
-    pub tag: Option<String>,
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    #[serde(default)]
+    pub deck_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateCardRequest {
     pub front: String,
     pub back: String,
-    pub tag: Option<String>,
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    #[serde(default)]
+    pub deck_id: Option<String>,
+}
+
+/// How the tags of a search are combined
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum TagMatch {
+    #[default]
+    Any, // Cards with at least one of the tags
+    All, // Cards with every one of the tags
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchRequest {
     pub query: Option<String>,
-    pub tag: Option<String>,
     pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub tag_match: TagMatch,
+    #[serde(default)]
+    pub deck_id: Option<String>, // Includes the cards of nested decks
 }
 
+/// Replace the tags of the given cards
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BulkUpdateRequest {
     pub card_ids: Vec<String>,
+    pub tags: BTreeSet<String>,
+}
+
+/// Add a tag to, or remove it from, the given cards
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkTagRequest {
+    pub card_ids: Vec<String>,
+    pub tag: String,
+}
+
+/// A deck of cards. Nesting is part of the name, e.g. `Languages::Spanish::Verbs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Deck {
+    pub id: String,
+    pub name: String,
+    pub preset_id: Option<String>, // Inherited from the parent deck when not set
+    pub created_at: DateTime<Utc>,
+}
+
+/// Named scheduling options that any number of decks can share
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeckPreset {
+    pub id: String,
+    pub name: String,
+    pub options: AppSettings,
+}
+
+/// All decks and option presets, stored together
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeckCollection {
+    pub decks: Vec<Deck>,
+    pub presets: Vec<DeckPreset>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateDeckRequest {
+    pub name: String, // Missing parent decks are created as well
+    pub preset_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresetRequest {
+    pub name: String,
+    pub options: AppSettings,
+}
+
+/// Move cards into a deck, or out of every deck when `deck_id` is not set
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoveCardsRequest {
+    pub card_ids: Vec<String>,
+    pub deck_id: Option<String>,
+}
+
+/// Make cards due on the days `from_days` to `to_days` from today, 0 being today
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetDueRequest {
+    pub card_ids: Vec<String>,
+    pub from_days: u32,
+    pub to_days: u32,
+}
+
+/// Move the due dates of cards `days` later, or earlier when negative
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShiftDueRequest {
+    pub card_ids: Vec<String>,
+    pub days: i64,
+}
+
+/// Count the cards coming due on each of the next `days` days, optionally only those with
+/// `tag` or in `deck_id` and its nested decks
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForecastRequest {
+    pub days: u32,
+    #[serde(default)]
     pub tag: Option<String>,
+    #[serde(default)]
+    pub deck_id: Option<String>,
+}
+
+/// Cards due on one local calendar day; the first day also holds every overdue card
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForecastDay {
+    pub date: NaiveDate,
+    pub cards_due: usize,
+}
+
+/// What happens to a card that becomes a leech. Either way it gets the `leech` tag.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LeechAction {
+    #[default]
+    Tag,
+    Suspend,
+}
+
+/// Order in which a review session shows its cards
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SessionOrder {
+    #[default]
+    DueDate, // Cards in learning, then reviews most overdue first, then new cards
+    Random,
+    Interleave, // New cards spread evenly among the reviews
+    ByTag,      // Grouped by tag, untagged cards last
+}
+
+/// The card to show next, `None` once the session has run out of cards
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionProgress {
+    pub card: Option<Card>,
+    pub answered: u32,
+    pub remaining: usize, // Including the current card
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub cards_studied: usize, // Different cards answered
+    pub answers: u32,
+    pub again: u32,
+    pub hard: u32,
+    pub good: u32,
+    pub easy: u32,
+    pub cards_left: usize, // Still queued when the session was finished
+}
+
+/// How saving new settings would reschedule the cards that follow the global settings when the
+/// algorithm changes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MigrationPreview {
+    pub cards_migrated: usize, // Cards given scheduling state for the new algorithm
+    pub due_earlier: usize,
+    pub due_later: usize,
+    pub cards_due_before: usize, // Of the migrated cards, those due now
+    pub cards_due_after: usize,  // Of the migrated cards, those due now once migrated
+}
+
+/// Simulate the cards of a deck (or of the whole collection) with its settings for a number of days
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimulationRequest {
+    pub days: u32,
+    #[serde(default)]
+    pub deck_id: Option<String>,
+}
+
+/// Projected workload of one algorithm over a simulated period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationResult {
+    pub algorithm: SpacedRepetitionAlgorithm,
+    pub daily_reviews: Vec<u32>, // Cards answered on each simulated day, learning steps included
+    pub total_reviews: u32,
+    pub retention: Option<f64>, // Share of review cards remembered; None when no card came up for review
+    pub total_minutes: f64,     // Estimated time spent answering cards
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CsvImportRequest {
+    pub path: String,
+    pub delimiter: Option<char>, // Defaults to tab for .tsv files and comma otherwise
+    pub has_headers: bool,
+    pub columns: Option<HashMap<String, usize>>, // Card field -> zero-based column; defaults to the header names
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CsvExportRequest {
+    pub path: String,
+    pub delimiter: Option<char>,
+    pub card_ids: Option<Vec<String>>, // Exports every card when not set
+}
+
+/// What can currently be undone or redone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoState {
+    pub can_undo: bool,
+    pub can_redo: bool,
+    pub undo_description: Option<String>, // e.g. "Review card" or "Delete 3 cards"
+    pub redo_description: Option<String>,
+}
+
+/// A backup archive on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub path: String,
+    pub created_at: DateTime<Utc>,
+    pub card_count: usize,
+    pub review_log_count: usize,
+    pub automatic: bool, // Taken by the app rather than requested by the user
+}
+
+/// Outcome of importing cards from another application
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub imported: usize, // New cards created
+    pub skipped: usize,  // Empty entries and cards that were already present
+    pub merged: usize,   // Existing cards updated from a matching entry
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -84,13 +460,70 @@ pub struct TagStats {
     pub cards_due: usize,
     pub cards_new: usize,
     pub cards_mature: usize,
+    pub cards_suspended: usize,
+    pub cards_buried: usize,
+}
+
+/// Time spent answering, over the answers that reported it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnswerTime {
+    pub answers: usize,
+    pub total_ms: u64,
+    pub average_ms: u64,
+    pub median_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardAnswerTime {
+    pub card_id: String,
+    pub front: String,
+    #[serde(flatten)]
+    pub time: AnswerTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagAnswerTime {
+    pub tag: String,
+    #[serde(flatten)]
+    pub time: AnswerTime,
+}
+
+/// Where review time goes: overall, and per card and tag with the most total time first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnswerTimeReport {
+    pub overall: Option<AnswerTime>, // None until an answer has reported its time
+    pub cards: Vec<CardAnswerTime>,
+    pub tags: Vec<TagAnswerTime>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum SpacedRepetitionAlgorithm {
     SM2,               // SuperMemo 2 algorithm (current)
     Leitner,           // Leitner box system
     SimpleExponential, // Simple exponential spacing
+    FSRS,              // Free Spaced Repetition Scheduler
+    Custom(String),    // Registered at runtime, by name
+}
+
+impl SpacedRepetitionAlgorithm {
+    pub const ALL: [SpacedRepetitionAlgorithm; 4] = [
+        SpacedRepetitionAlgorithm::SM2,
+        SpacedRepetitionAlgorithm::Leitner,
+        SpacedRepetitionAlgorithm::SimpleExponential,
+        SpacedRepetitionAlgorithm::FSRS,
+    ];
+
+    /// Name the algorithm's scheduler and its state on a card go by
+    pub fn name(&self) -> &str {
+        match self {
+            SpacedRepetitionAlgorithm::SM2 => "SM2",
+            SpacedRepetitionAlgorithm::Leitner => "Leitner",
+            SpacedRepetitionAlgorithm::SimpleExponential => "SimpleExponential",
+            SpacedRepetitionAlgorithm::FSRS => "FSRS",
+            SpacedRepetitionAlgorithm::Custom(name) => name,
+        }
+    }
 }
 
 impl Default for SpacedRepetitionAlgorithm {
@@ -99,11 +532,25 @@ impl Default for SpacedRepetitionAlgorithm {
     }
 }
 
+// Fields missing from older settings files fall back to their defaults
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppSettings {
     pub algorithm: SpacedRepetitionAlgorithm,
     pub leitner_intervals: Vec<i64>, // Custom intervals for Leitner system
     pub exponential_base: f64,       // Base multiplier for exponential algorithm
+    pub desired_retention: f64,      // Target recall probability for FSRS (0.0 - 1.0)
+    pub new_cards_per_day: u32,      // Never-reviewed cards introduced per local day
+    pub reviews_per_day: u32,        // Reviews of already-seen cards per local day
+    pub learning_steps: Vec<u32>,    // Minutes between answers of a new card before it graduates
+    pub relearning_steps: Vec<u32>,  // Minutes between answers of a forgotten review card
+    pub interval_fuzz: bool,         // Spread due dates at random within the tolerance window
+    pub load_balancing: bool,        // Move due dates to the least busy days within the tolerance window
+    pub fuzz_tolerance: f64,         // Share of an interval a due date may move either way, at least one day
+    pub overdue_bonus: f64,          // Share of the days a successful review was late added to its interval (0.0 - 1.0)
+    pub slow_answer_seconds: u32,    // Good answers slower than this are scheduled as Hard, but logged as Good; 0 turns this off
+    pub leech_threshold: u32,        // Lapses that make a card a leech; 0 turns leech detection off
+    pub leech_action: LeechAction,   // Tag leeches only, or also suspend them
 }
 
 impl Default for AppSettings {
@@ -112,6 +559,18 @@ impl Default for AppSettings {
             algorithm: SpacedRepetitionAlgorithm::SM2,
             leitner_intervals: vec![1, 3, 7, 14, 30], // 5-box Leitner system
             exponential_base: 2.0,
+            desired_retention: 0.9,
+            new_cards_per_day: 20,
+            reviews_per_day: 200,
+            learning_steps: vec![1, 10],
+            relearning_steps: vec![10],
+            interval_fuzz: false,
+            load_balancing: false,
+            fuzz_tolerance: 0.05,
+            overdue_bonus: 0.5,
+            slow_answer_seconds: 0,
+            leech_threshold: 8,
+            leech_action: LeechAction::Tag,
         }
     }
 }
@@ -119,7 +578,6 @@ impl Default for AppSettings {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
 
     #[test]
     fn test_review_difficulty_from_u8() {
@@ -141,50 +599,111 @@ mod tests {
     #[test]
     fn test_card_creation() {
         let card = Card {
-            id: "test-id".to_string(),
             front: "What is 2+2?".to_string(),
             back: "4".to_string(),
-            tag: Some("Math".to_string()),
-            created_at: Utc::now(),
-            last_reviewed: None,
-            next_review: Utc::now(),
-            interval: 0,
-            ease_factor: 2.5,
-            review_count: 0,
-            correct_count: 0,
-            leitner_box: 0,
-            exponential_factor: 1.0,
+            tags: BTreeSet::from(["Math".to_string()]),
+            ..Card::new_for_test("test-id")
         };
 
         assert_eq!(card.id, "test-id");
         assert_eq!(card.front, "What is 2+2?");
         assert_eq!(card.back, "4");
-        assert_eq!(card.tag, Some("Math".to_string()));
+        assert!(card.tags.contains("Math"));
         assert_eq!(card.interval, 0);
         assert_eq!(card.ease_factor, 2.5);
         assert_eq!(card.review_count, 0);
         assert_eq!(card.correct_count, 0);
         assert_eq!(card.leitner_box, 0);
         assert_eq!(card.exponential_factor, 1.0);
+        assert_eq!(card.fsrs_stability, 0.0);
+        assert_eq!(card.fsrs_difficulty, 0.0);
         assert!(card.last_reviewed.is_none());
     }
 
+    #[test]
+    fn test_card_deserialization_without_fsrs_fields() {
+        // Cards saved before FSRS existed must still load
+        let json = r#"{
+            "id": "old-card",
+            "front": "Question",
+            "back": "Answer",
+            "tag": null,
+            "created_at": "2024-01-01T00:00:00Z",
+            "last_reviewed": null,
+            "next_review": "2024-01-01T00:00:00Z",
+            "interval": 0,
+            "ease_factor": 2.5,
+            "review_count": 0,
+            "correct_count": 0,
+            "leitner_box": 0,
+            "exponential_factor": 1.0
+        }"#;
+
+        let card: Card = serde_json::from_str(json).unwrap();
+        assert_eq!(card.id, "old-card");
+        assert!(card.tags.is_empty());
+        assert_eq!(card.fsrs_stability, 0.0);
+        assert_eq!(card.fsrs_difficulty, 0.0);
+    }
+
+    #[test]
+    fn test_card_deserialization_migrates_single_tag() {
+        let mut value = serde_json::to_value(Card {
+            front: "Question".to_string(),
+            back: "Answer".to_string(),
+            ..Card::new_for_test("old-card")
+        })
+        .unwrap();
+        let fields = value.as_object_mut().unwrap();
+        fields.remove("tags");
+        fields.insert("tag".to_string(), serde_json::json!("Spanish"));
+
+        let card: Card = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(card.tags, BTreeSet::from(["Spanish".to_string()]));
+
+        // Saved again, the card uses the new field
+        let saved = serde_json::to_value(&card).unwrap();
+        assert!(saved.get("tag").is_none());
+        assert_eq!(saved["tags"], serde_json::json!(["Spanish"]));
+
+        value["tag"] = serde_json::json!("");
+        let card: Card = serde_json::from_value(value).unwrap();
+        assert!(card.tags.is_empty());
+    }
+
+    #[test]
+    fn test_infer_legacy_state() {
+        let json = r#"{"id":"old","front":"Q","back":"A","created_at":"2024-01-01T00:00:00Z","last_reviewed":null,
+            "next_review":"2024-01-02T00:00:00Z","interval":1,"ease_factor":2.5,"review_count":0,"correct_count":0,
+            "leitner_box":0,"exponential_factor":1.0}"#;
+        let mut card: Card = serde_json::from_str(json).unwrap();
+        assert_eq!(card.state, CardState::New);
+        card.infer_legacy_state();
+        assert_eq!(card.state, CardState::New);
+
+        card.review_count = 3;
+        card.infer_legacy_state();
+        assert_eq!(card.state, CardState::Review);
+        assert_eq!(card.learning_step, 0);
+    }
+
+    #[test]
+    fn test_settings_deserialization_without_desired_retention() {
+        let json = r#"{"algorithm": "Leitner", "leitner_intervals": [1, 2, 4], "exponential_base": 2.0}"#;
+
+        let settings: AppSettings = serde_json::from_str(json).unwrap();
+        assert_eq!(settings.algorithm, SpacedRepetitionAlgorithm::Leitner);
+        assert_eq!(settings.leitner_intervals, vec![1, 2, 4]);
+        assert_eq!(settings.desired_retention, 0.9);
+    }
+
     #[test]
     fn test_card_serialization() {
         let card = Card {
-            id: "test-id".to_string(),
             front: "Question".to_string(),
             back: "Answer".to_string(),
-            tag: None,
-            created_at: Utc::now(),
-            last_reviewed: None,
-            next_review: Utc::now(),
             interval: 1,
-            ease_factor: 2.5,
-            review_count: 0,
-            correct_count: 0,
-            leitner_box: 0,
-            exponential_factor: 1.0,
+            ..Card::new_for_test("test-id")
         };
 
         let serialized = serde_json::to_string(&card).unwrap();
@@ -193,7 +712,7 @@ mod tests {
         assert_eq!(card.id, deserialized.id);
         assert_eq!(card.front, deserialized.front);
         assert_eq!(card.back, deserialized.back);
-        assert_eq!(card.tag, deserialized.tag);
+        assert_eq!(card.tags, deserialized.tags);
         assert_eq!(card.interval, deserialized.interval);
         assert_eq!(card.ease_factor, deserialized.ease_factor);
     }
@@ -203,12 +722,13 @@ mod tests {
         let request = CreateCardRequest {
             front: "Question".to_string(),
             back: "Answer".to_string(),
-            tag: Some("Test".to_string()),
+            tags: BTreeSet::from(["Test".to_string()]),
+            deck_id: None,
         };
 
         assert_eq!(request.front, "Question");
         assert_eq!(request.back, "Answer");
-        assert_eq!(request.tag, Some("Test".to_string()));
+        assert!(request.tags.contains("Test"));
     }
 
     #[test]
@@ -216,38 +736,44 @@ mod tests {
         let request = UpdateCardRequest {
             front: "Updated Question".to_string(),
             back: "Updated Answer".to_string(),
-            tag: None,
+            tags: BTreeSet::new(),
+            deck_id: None,
         };
 
         assert_eq!(request.front, "Updated Question");
         assert_eq!(request.back, "Updated Answer");
-        assert_eq!(request.tag, None);
+        assert!(request.tags.is_empty());
     }
 
     #[test]
     fn test_search_request() {
         let request = SearchRequest {
             query: Some("test".to_string()),
-            tag: Some("Math".to_string()),
-            tags: None,
+            tags: Some(vec!["Math".to_string()]),
+            tag_match: TagMatch::All,
+            deck_id: None,
         };
 
         assert_eq!(request.query, Some("test".to_string()));
-        assert_eq!(request.tag, Some("Math".to_string()));
-        assert_eq!(request.tags, None);
+        assert_eq!(request.tags, Some(vec!["Math".to_string()]));
+        assert_eq!(request.tag_match, TagMatch::All);
+
+        // Searches without a match mode find cards with any of the tags
+        let request: SearchRequest = serde_json::from_str(r#"{"query": null, "tags": ["Math"]}"#).unwrap();
+        assert_eq!(request.tag_match, TagMatch::Any);
     }
 
     #[test]
     fn test_bulk_update_request() {
         let request = BulkUpdateRequest {
             card_ids: vec!["id1".to_string(), "id2".to_string()],
-            tag: Some("New Tag".to_string()),
+            tags: BTreeSet::from(["New Tag".to_string()]),
         };
 
         assert_eq!(request.card_ids.len(), 2);
         assert_eq!(request.card_ids[0], "id1");
         assert_eq!(request.card_ids[1], "id2");
-        assert_eq!(request.tag, Some("New Tag".to_string()));
+        assert!(request.tags.contains("New Tag"));
     }
 
     #[test]
@@ -258,6 +784,8 @@ mod tests {
             cards_due: 3,
             cards_new: 2,
             cards_mature: 5,
+            cards_suspended: 1,
+            cards_buried: 0,
         };
 
         assert_eq!(stats.name, "Math");
@@ -267,6 +795,31 @@ mod tests {
         assert_eq!(stats.cards_mature, 5);
     }
 
+    #[test]
+    fn test_review_log_serialization() {
+        let entry = ReviewLog {
+            id: "log-id".to_string(),
+            difficulty: ReviewDifficulty::Hard,
+            previous_interval: 6,
+            new_interval: 8,
+            new_ease_factor: 2.35,
+            time_taken_ms: Some(4200),
+            ..ReviewLog::new_for_test("card-id")
+        };
+
+        let serialized = serde_json::to_string(&entry).unwrap();
+        let deserialized: ReviewLog = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(deserialized.id, "log-id");
+        assert_eq!(deserialized.card_id, "card-id");
+        assert!(matches!(deserialized.difficulty, ReviewDifficulty::Hard));
+        assert_eq!(deserialized.previous_interval, 6);
+        assert_eq!(deserialized.new_interval, 8);
+        assert_eq!(deserialized.new_ease_factor, 2.35);
+        assert_eq!(deserialized.algorithm, SpacedRepetitionAlgorithm::SM2);
+        assert_eq!(deserialized.time_taken_ms, Some(4200));
+    }
+
     #[test]
     fn test_review_stats() {
         let stats = ReviewStats {
@@ -275,6 +828,10 @@ mod tests {
             cards_new: 20,
             cards_learning: 30,
             cards_mature: 35,
+            cards_suspended: 4,
+            cards_buried: 2,
+            new_cards_remaining: 20,
+            reviews_remaining: 15,
         };
 
         assert_eq!(stats.total_cards, 100);
@@ -282,5 +839,26 @@ mod tests {
         assert_eq!(stats.cards_new, 20);
         assert_eq!(stats.cards_learning, 30);
         assert_eq!(stats.cards_mature, 35);
+        assert_eq!(stats.cards_suspended, 4);
+        assert_eq!(stats.cards_buried, 2);
+    }
+
+    #[test]
+    fn test_settings_without_daily_limits_use_defaults() {
+        let settings: AppSettings = serde_json::from_str(r#"{"algorithm":"Leitner","exponential_base":3.0}"#).unwrap();
+
+        assert_eq!(settings.algorithm, SpacedRepetitionAlgorithm::Leitner);
+        assert_eq!(settings.new_cards_per_day, 20);
+        assert_eq!(settings.reviews_per_day, 200);
+        assert_eq!(settings.overdue_bonus, 0.5);
+        assert_eq!(settings.slow_answer_seconds, 0);
+        assert_eq!(settings.leech_threshold, 8);
+        assert_eq!(settings.leech_action, LeechAction::Tag);
+
+        let entry: ReviewLog = serde_json::from_str(
+            r#"{"id":"a","card_id":"b","reviewed_at":"2024-01-01T00:00:00Z","difficulty":"Good","previous_interval":0,"new_interval":1,"previous_ease_factor":2.5,"new_ease_factor":2.5,"algorithm":"SM2","time_taken_ms":null}"#,
+        )
+        .unwrap();
+        assert!(!entry.was_new);
     }
 }
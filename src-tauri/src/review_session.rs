@@ -0,0 +1,273 @@
+use crate::models::{Card, ReviewDifficulty, SessionOrder, SessionSummary};
+use crate::spaced_repetition::SpacedRepetition;
+use chrono::{DateTime, Utc};
+use std::collections::{HashSet, VecDeque};
+use uuid::Uuid;
+
+/// A forgotten card, or one that is due again within the session, comes back after this many other cards
+const REINSERT_GAP: usize = 3;
+
+/// One answer of the session, kept so it can be taken back
+#[derive(Debug)]
+struct Answer {
+    card_id: String,
+    difficulty: ReviewDifficulty,
+    reinserted: bool,
+}
+
+/// The queue of cards of one sitting. A forgotten card goes back into the queue, whether or not it
+/// has relearning steps, and so does a card that is due again right after its answer, such as a
+/// new card between learning steps.
+#[derive(Debug)]
+pub struct ReviewSession {
+    queue: VecDeque<String>,
+    answers: Vec<Answer>,
+    started_at: DateTime<Utc>,
+}
+
+impl ReviewSession {
+    /// A session over `cards`, which come in the order of the daily limits
+    pub fn new(cards: Vec<Card>, order: SessionOrder, now: DateTime<Utc>) -> Self {
+        ReviewSession {
+            queue: order_cards(cards, order).into_iter().map(|card| card.id).collect(),
+            answers: Vec::new(),
+            started_at: now,
+        }
+    }
+
+    /// Id of the card to show now
+    pub fn current(&self) -> Option<&str> {
+        self.queue.front().map(String::as_str)
+    }
+
+    /// Drop the current card without an answer, e.g. because it was deleted
+    pub fn skip_current(&mut self) {
+        self.queue.pop_front();
+    }
+
+    pub fn answered(&self) -> u32 {
+        self.answers.len() as u32
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Id of the card answered last
+    pub fn last_answered(&self) -> Option<&str> {
+        self.answers.last().map(|answer| answer.card_id.as_str())
+    }
+
+    /// Move past the current card, answered with `difficulty`. `card` is the card after the answer;
+    /// it is queued again if it was forgotten or is already due at `now`.
+    pub fn record_answer(&mut self, card: &Card, difficulty: ReviewDifficulty, now: DateTime<Utc>) {
+        self.queue.pop_front();
+        let reinserted = difficulty == ReviewDifficulty::Again || SpacedRepetition::is_due(card, now);
+        if reinserted {
+            self.queue.insert(REINSERT_GAP.min(self.queue.len()), card.id.clone());
+        }
+        self.answers.push(Answer {
+            card_id: card.id.clone(),
+            difficulty,
+            reinserted,
+        });
+    }
+
+    /// Take back the last answer, making its card the current card again
+    pub fn undo_answer(&mut self) {
+        if let Some(answer) = self.answers.pop() {
+            if answer.reinserted {
+                if let Some(index) = self.queue.iter().position(|id| *id == answer.card_id) {
+                    self.queue.remove(index);
+                }
+            }
+            self.queue.push_front(answer.card_id);
+        }
+    }
+
+    pub fn summary(&self, now: DateTime<Utc>) -> SessionSummary {
+        let count = |difficulty: ReviewDifficulty| self.answers.iter().filter(|answer| answer.difficulty == difficulty).count() as u32;
+        let cards_studied: HashSet<&str> = self.answers.iter().map(|answer| answer.card_id.as_str()).collect();
+
+        SessionSummary {
+            started_at: self.started_at,
+            finished_at: now,
+            cards_studied: cards_studied.len(),
+            answers: self.answered(),
+            again: count(ReviewDifficulty::Again),
+            hard: count(ReviewDifficulty::Hard),
+            good: count(ReviewDifficulty::Good),
+            easy: count(ReviewDifficulty::Easy),
+            cards_left: self.queue.len(),
+        }
+    }
+}
+
+/// Put `cards`, which come in the order of the daily limits, in the order of the session
+fn order_cards(mut cards: Vec<Card>, order: SessionOrder) -> Vec<Card> {
+    match order {
+        SessionOrder::DueDate => {}
+        SessionOrder::Random => cards.sort_by_cached_key(|_| Uuid::new_v4()),
+        SessionOrder::Interleave => {
+            // Cards in learning are waiting on their next step, so they keep coming first
+            let (mut learning, rest): (Vec<Card>, Vec<Card>) = cards.into_iter().partition(|card| card.state.is_learning());
+            let (new_cards, reviews): (Vec<Card>, Vec<Card>) = rest.into_iter().partition(|card| card.review_count == 0);
+            learning.extend(spread(reviews, new_cards));
+            cards = learning;
+        }
+        SessionOrder::ByTag => cards.sort_by_cached_key(|card| (card.tags.is_empty(), card.tags.iter().next().cloned())),
+    }
+    cards
+}
+
+/// Merge two lists, each in its own order, taking from whichever is proportionally further behind
+fn spread(first: Vec<Card>, second: Vec<Card>) -> Vec<Card> {
+    let (first_len, second_len) = (first.len(), second.len());
+    let (mut first, mut second) = (first.into_iter(), second.into_iter());
+    let mut merged = Vec::with_capacity(first_len + second_len);
+
+    let (mut first_taken, mut second_taken) = (0, 0);
+    while first_taken < first_len || second_taken < second_len {
+        let take_first = second_taken == second_len || (first_taken < first_len && first_taken * second_len <= second_taken * first_len);
+        if take_first {
+            merged.extend(first.next());
+            first_taken += 1;
+        } else {
+            merged.extend(second.next());
+            second_taken += 1;
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_balancer::ReviewLoad;
+    use crate::models::{AppSettings, CardState};
+    use chrono::{Duration, TimeZone};
+    use std::collections::BTreeSet;
+
+    fn create_test_card(id: &str, review_count: u32, tag: Option<&str>) -> Card {
+        Card {
+            tags: tag.into_iter().map(str::to_string).collect::<BTreeSet<_>>(),
+            created_at: now(),
+            next_review: now(),
+            review_count,
+            state: if review_count > 0 { CardState::Review } else { CardState::New },
+            ..Card::new_for_test(id)
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap()
+    }
+
+    fn ids(cards: &[Card]) -> Vec<&str> {
+        cards.iter().map(|card| card.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_interleave_spreads_new_cards() {
+        let cards = vec![
+            create_test_card("r1", 3, None),
+            create_test_card("r2", 3, None),
+            create_test_card("r3", 3, None),
+            create_test_card("r4", 3, None),
+            create_test_card("n1", 0, None),
+            create_test_card("n2", 0, None),
+        ];
+
+        let ordered = order_cards(cards.clone(), SessionOrder::Interleave);
+        assert_eq!(ids(&ordered), vec!["r1", "n1", "r2", "r3", "n2", "r4"]);
+
+        // Due date order keeps the order of the daily limits
+        assert_eq!(ids(&order_cards(cards.clone(), SessionOrder::DueDate)), ids(&cards));
+
+        let mut shuffled = ids(&order_cards(cards.clone(), SessionOrder::Random))
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        shuffled.sort();
+        assert_eq!(shuffled, vec!["n1", "n2", "r1", "r2", "r3", "r4"]);
+    }
+
+    #[test]
+    fn test_by_tag_groups_cards() {
+        let cards = vec![
+            create_test_card("a", 3, None),
+            create_test_card("b", 3, Some("Verbs")),
+            create_test_card("c", 3, Some("Nouns")),
+            create_test_card("d", 3, Some("Verbs")),
+        ];
+
+        assert_eq!(ids(&order_cards(cards, SessionOrder::ByTag)), vec!["c", "b", "d", "a"]);
+    }
+
+    #[test]
+    fn test_cards_due_again_are_reinserted() {
+        let cards: Vec<Card> = (1..=5).map(|i| create_test_card(&i.to_string(), 3, None)).collect();
+        let mut session = ReviewSession::new(cards, SessionOrder::DueDate, now());
+
+        // Forgotten and relearning ten minutes from now: back after three other cards
+        let mut forgotten = create_test_card("1", 4, None);
+        forgotten.state = CardState::Relearning;
+        forgotten.next_review = now() + Duration::minutes(10);
+        session.record_answer(&forgotten, ReviewDifficulty::Again, now());
+        assert_eq!(session.queue, vec!["2", "3", "4", "1", "5"]);
+
+        // Remembered and scheduled days ahead: done for the session
+        let mut remembered = create_test_card("2", 4, None);
+        remembered.next_review = now() + Duration::days(3);
+        session.record_answer(&remembered, ReviewDifficulty::Good, now());
+        assert_eq!(session.queue, vec!["3", "4", "1", "5"]);
+        assert_eq!(session.remaining(), 4);
+
+        let summary = session.summary(now());
+        assert_eq!(summary.answers, 2);
+        assert_eq!(summary.cards_studied, 2);
+        assert_eq!((summary.again, summary.good), (1, 1));
+        assert_eq!(summary.cards_left, 4);
+    }
+
+    #[test]
+    fn test_forgotten_cards_are_reinserted_without_relearning_steps() {
+        let settings = AppSettings {
+            relearning_steps: vec![],
+            ..Default::default()
+        };
+        let cards: Vec<Card> = (1..=5).map(|i| create_test_card(&i.to_string(), 3, None)).collect();
+        let mut session = ReviewSession::new(cards, SessionOrder::DueDate, now());
+
+        // Forgotten, the card goes straight back to review a day later, but is still seen again today
+        let mut forgotten = Card {
+            interval: 10,
+            ..create_test_card("1", 3, None)
+        };
+        SpacedRepetition::answer_card(&mut forgotten, &ReviewDifficulty::Again, &settings, now(), &ReviewLoad::default());
+        assert_eq!(forgotten.state, CardState::Review);
+        assert!(!SpacedRepetition::is_due(&forgotten, now()));
+        session.record_answer(&forgotten, ReviewDifficulty::Again, now());
+        assert_eq!(session.queue, vec!["2", "3", "4", "1", "5"]);
+
+        session.undo_answer();
+        assert_eq!(session.queue, vec!["1", "2", "3", "4", "5"]);
+    }
+
+    #[test]
+    fn test_undo_answer() {
+        let cards: Vec<Card> = (1..=3).map(|i| create_test_card(&i.to_string(), 3, None)).collect();
+        let mut session = ReviewSession::new(cards, SessionOrder::DueDate, now());
+
+        let mut forgotten = create_test_card("1", 4, None);
+        forgotten.state = CardState::Relearning;
+        session.record_answer(&forgotten, ReviewDifficulty::Again, now());
+        assert_eq!(session.queue, vec!["2", "3", "1"]);
+        assert_eq!(session.last_answered(), Some("1"));
+
+        session.undo_answer();
+        assert_eq!(session.queue, vec!["1", "2", "3"]);
+        assert_eq!(session.answered(), 0);
+        assert_eq!(session.current(), Some("1"));
+    }
+}
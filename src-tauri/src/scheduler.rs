@@ -0,0 +1,271 @@
+use crate::models::{AppSettings, Card, ReviewDifficulty, SpacedRepetitionAlgorithm};
+use crate::spaced_repetition::{ExponentialScheduler, FsrsScheduler, LeitnerScheduler, Sm2Scheduler};
+use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// A spaced repetition algorithm: how many days until a card is shown again after an answer, and
+/// how the algorithm's own memory of the card changes. Learning steps, fuzz and load balancing are
+/// applied around it, so a scheduler only deals with the days between reviews.
+pub trait Scheduler: Send + Sync {
+    /// What the algorithm remembers about a card between reviews
+    type State: SchedulingState;
+
+    /// Schedule `card`, which has `state`, answered with `difficulty` at `now`
+    fn schedule(
+        &self,
+        card: &Card,
+        state: &Self::State,
+        difficulty: &ReviewDifficulty,
+        settings: &AppSettings,
+        now: DateTime<Utc>,
+    ) -> Scheduled<Self::State>;
+
+    /// Take over a reviewed card that another algorithm scheduled until now, deriving the state from
+    /// the interval it has. By default the card keeps its interval.
+    fn migrate(&self, card: &Card, state: &Self::State, _settings: &AppSettings) -> Scheduled<Self::State> {
+        Scheduled {
+            interval: card.interval.max(1),
+            state: state.clone(),
+        }
+    }
+}
+
+/// Days until the next review and the state to keep until then
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scheduled<S> {
+    pub interval: i64,
+    pub state: S,
+}
+
+/// A scheduler's state as kept on the card. It is stored as JSON in `Card::scheduler_state` under
+/// the algorithm's name, so an algorithm can be added without a new card field; the built-in
+/// algorithms keep theirs in the card fields that predate it.
+pub trait SchedulingState: Clone + Default + Serialize + DeserializeOwned {
+    /// The state saved on `card`; a card this algorithm has not scheduled yet gets the default
+    fn load(card: &Card, key: &str) -> Self {
+        card.scheduler_state
+            .get(key)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    fn store(self, card: &mut Card, key: &str) {
+        if let Ok(value) = serde_json::to_value(self) {
+            card.scheduler_state.insert(key.to_string(), value);
+        }
+    }
+}
+
+/// A `Scheduler` with its state type hidden, so schedulers with different states share the registry.
+/// Every `Scheduler` is one.
+pub trait CardScheduler: Send + Sync {
+    /// The card with its interval, due date and state after the answer
+    fn schedule_card(&self, card: &Card, difficulty: &ReviewDifficulty, settings: &AppSettings, now: DateTime<Utc>) -> Card;
+
+    /// The card with its interval and state taken over by this scheduler; the due date is left as is
+    fn migrate_card(&self, card: &Card, settings: &AppSettings) -> Card;
+}
+
+impl<T: Scheduler> CardScheduler for T {
+    fn schedule_card(&self, card: &Card, difficulty: &ReviewDifficulty, settings: &AppSettings, now: DateTime<Utc>) -> Card {
+        let key = settings.algorithm.name();
+        let scheduled = self.schedule(card, &T::State::load(card, key), difficulty, settings, now);
+
+        let mut card = card.clone();
+        card.interval = scheduled.interval;
+        card.next_review = now + Duration::days(scheduled.interval);
+        scheduled.state.store(&mut card, key);
+        card
+    }
+
+    fn migrate_card(&self, card: &Card, settings: &AppSettings) -> Card {
+        let key = settings.algorithm.name();
+        let migrated = self.migrate(card, &T::State::load(card, key), settings);
+
+        let mut card = card.clone();
+        card.interval = migrated.interval;
+        migrated.state.store(&mut card, key);
+        card
+    }
+}
+
+/// The scheduler of every algorithm, the built-in ones and those registered at runtime
+pub struct SchedulerRegistry {
+    schedulers: Vec<(SpacedRepetitionAlgorithm, Arc<dyn CardScheduler>)>,
+}
+
+impl SchedulerRegistry {
+    fn builtin() -> Self {
+        let schedulers: Vec<(SpacedRepetitionAlgorithm, Arc<dyn CardScheduler>)> = vec![
+            (SpacedRepetitionAlgorithm::SM2, Arc::new(Sm2Scheduler)),
+            (SpacedRepetitionAlgorithm::Leitner, Arc::new(LeitnerScheduler)),
+            (SpacedRepetitionAlgorithm::SimpleExponential, Arc::new(ExponentialScheduler)),
+            (SpacedRepetitionAlgorithm::FSRS, Arc::new(FsrsScheduler)),
+        ];
+        SchedulerRegistry { schedulers }
+    }
+
+    fn global() -> &'static RwLock<SchedulerRegistry> {
+        static REGISTRY: OnceLock<RwLock<SchedulerRegistry>> = OnceLock::new();
+        REGISTRY.get_or_init(|| RwLock::new(SchedulerRegistry::builtin()))
+    }
+
+    /// Schedule cards of `algorithm` with `scheduler` from now on, replacing any scheduler it had.
+    /// User-defined algorithms are registered as `SpacedRepetitionAlgorithm::Custom`.
+    pub fn register<S: Scheduler + 'static>(algorithm: SpacedRepetitionAlgorithm, scheduler: S) {
+        let mut registry = Self::global().write().unwrap_or_else(|e| e.into_inner());
+        registry.schedulers.retain(|(registered, _)| *registered != algorithm);
+        registry.schedulers.push((algorithm, Arc::new(scheduler)));
+    }
+
+    /// The scheduler of `algorithm`. Cards of an algorithm that is not registered, such as one
+    /// saved by a build that had it, are scheduled with SM-2.
+    pub fn get(algorithm: &SpacedRepetitionAlgorithm) -> Arc<dyn CardScheduler> {
+        let registry = Self::global().read().unwrap_or_else(|e| e.into_inner());
+        let find = |algorithm: &SpacedRepetitionAlgorithm| {
+            registry
+                .schedulers
+                .iter()
+                .find(|(registered, _)| registered == algorithm)
+                .map(|(_, scheduler)| scheduler.clone())
+        };
+        find(algorithm).unwrap_or_else(|| {
+            log::warn!("No scheduler registered for {}, using SM-2", algorithm.name());
+            find(&SpacedRepetitionAlgorithm::SM2).unwrap_or_else(|| Arc::new(Sm2Scheduler))
+        })
+    }
+
+    /// Every algorithm with a scheduler, the built-in ones first
+    pub fn algorithms() -> Vec<SpacedRepetitionAlgorithm> {
+        let registry = Self::global().read().unwrap_or_else(|e| e.into_inner());
+        registry.schedulers.iter().map(|(algorithm, _)| algorithm.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_balancer::ReviewLoad;
+    use crate::models::CardState;
+    use crate::spaced_repetition::SpacedRepetition;
+    use chrono::TimeZone;
+    use serde::Deserialize;
+
+    /// Doubles the interval on every successful answer and counts the answers it has seen
+    struct Doubling;
+
+    #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+    struct DoublingState {
+        answers: u32,
+    }
+
+    impl SchedulingState for DoublingState {}
+
+    impl Scheduler for Doubling {
+        type State = DoublingState;
+
+        fn schedule(
+            &self,
+            card: &Card,
+            state: &DoublingState,
+            difficulty: &ReviewDifficulty,
+            _settings: &AppSettings,
+            _now: DateTime<Utc>,
+        ) -> Scheduled<DoublingState> {
+            let interval = match difficulty {
+                ReviewDifficulty::Again => 1,
+                _ => (card.interval * 2).max(1),
+            };
+            Scheduled {
+                interval,
+                state: DoublingState {
+                    answers: state.answers + 1,
+                },
+            }
+        }
+    }
+
+    fn create_test_card(id: &str, interval: i64) -> Card {
+        let now = now();
+        Card {
+            created_at: now,
+            last_reviewed: Some(now - Duration::days(interval)),
+            next_review: now,
+            interval,
+            review_count: 3,
+            correct_count: 3,
+            state: CardState::Review,
+            ..Card::new_for_test(id)
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()
+    }
+
+    fn custom(name: &str) -> AppSettings {
+        AppSettings {
+            algorithm: SpacedRepetitionAlgorithm::Custom(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_registered_scheduler_schedules_cards() {
+        SchedulerRegistry::register(SpacedRepetitionAlgorithm::Custom("doubling".to_string()), Doubling);
+        assert!(SchedulerRegistry::algorithms().contains(&SpacedRepetitionAlgorithm::Custom("doubling".to_string())));
+
+        let settings = custom("doubling");
+        let mut card = create_test_card("a", 4);
+        SpacedRepetition::answer_card(&mut card, &ReviewDifficulty::Good, &settings, now(), &ReviewLoad::default());
+        SpacedRepetition::answer_card(&mut card, &ReviewDifficulty::Good, &settings, now(), &ReviewLoad::default());
+
+        assert_eq!(card.interval, 16);
+        assert_eq!(card.next_review, now() + Duration::days(16));
+        assert_eq!(DoublingState::load(&card, "doubling"), DoublingState { answers: 2 });
+        assert_eq!(card.scheduler_state["doubling"], serde_json::json!({ "answers": 2 }));
+        // The built-in state is left alone
+        assert_eq!(card.ease_factor, 2.5);
+    }
+
+    #[test]
+    fn test_default_migration_keeps_the_interval() {
+        SchedulerRegistry::register(SpacedRepetitionAlgorithm::Custom("doubling".to_string()), Doubling);
+
+        let card = create_test_card("a", 9);
+        let migrated = SpacedRepetition::migrate_card(&card, &custom("doubling"), now());
+
+        assert_eq!(migrated.interval, 9);
+        assert_eq!(migrated.next_review, card.next_review);
+        assert_eq!(migrated.scheduler_state["doubling"], serde_json::json!({ "answers": 0 }));
+    }
+
+    #[test]
+    fn test_unknown_algorithm_falls_back_to_sm2() {
+        let mut unknown = create_test_card("a", 10);
+        let mut sm2 = unknown.clone();
+
+        SpacedRepetition::answer_card(
+            &mut unknown,
+            &ReviewDifficulty::Good,
+            &custom("missing"),
+            now(),
+            &ReviewLoad::default(),
+        );
+        SpacedRepetition::answer_card(
+            &mut sm2,
+            &ReviewDifficulty::Good,
+            &AppSettings::default(),
+            now(),
+            &ReviewLoad::default(),
+        );
+
+        assert_eq!(unknown.interval, sm2.interval);
+        assert_eq!(unknown.interval, 25);
+        // Only SM-2's own state is kept, not one under the unknown name
+        assert_eq!(unknown.scheduler_state, sm2.scheduler_state);
+        assert!(!unknown.scheduler_state.contains_key("missing"));
+    }
+}
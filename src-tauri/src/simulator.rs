@@ -0,0 +1,375 @@
+use crate::load_balancer::ReviewLoad;
+use crate::models::{AppSettings, Card, CardState, ReviewDifficulty, SimulationResult, SpacedRepetitionAlgorithm};
+use crate::spaced_repetition::SpacedRepetition;
+use chrono::{DateTime, Duration, Utc};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+// The synthetic learner: how likely a card is recalled and how its memory changes with each answer
+const FIRST_RECALL_PROBABILITY: f64 = 0.6; // Chance of knowing a card the first time it is seen
+const INITIAL_STABILITY: f64 = 1.0; // Days until recall drops to 90% after the first exposure
+const SPACING_GAIN: f64 = 10.0; // How much more a harder successful recall strengthens the memory
+const LAPSE_STABILITY_FACTOR: f64 = 0.4; // Memory left after a card is forgotten
+const MIN_STABILITY: f64 = 0.5;
+const RECALL_DECAY: f64 = -0.5;
+const RECALL_FACTOR: f64 = 19.0 / 81.0;
+
+// Seconds the learner spends answering a card they remember or forget
+const SECONDS_PER_RECALL: i64 = 8;
+const SECONDS_PER_LAPSE: i64 = 20;
+
+// The same seed for every algorithm, so they are compared on the same learner
+const SEED: u64 = 0x5eed_cafe_f00d_d00d;
+
+/// Longest period that can be simulated, ten years
+pub const MAX_SIMULATION_DAYS: u32 = 3650;
+
+/// Simulate the cards, each with the scheduling options of its deck, under each algorithm with
+/// otherwise the same options. The daily limits come from `settings`.
+pub fn simulate_algorithms(
+    cards: &[(&Card, &AppSettings)],
+    settings: &AppSettings,
+    days: u32,
+    start: DateTime<Utc>,
+) -> Vec<SimulationResult> {
+    // The distinct options of the cards; each card refers to its options by index
+    let mut deck_options: Vec<&AppSettings> = Vec::new();
+    let mut option_indexes = Vec::with_capacity(cards.len());
+    for (_, options) in cards {
+        let index = deck_options
+            .iter()
+            .position(|known| std::ptr::eq(*known, *options))
+            .unwrap_or_else(|| {
+                deck_options.push(options);
+                deck_options.len() - 1
+            });
+        option_indexes.push(index);
+    }
+
+    SpacedRepetitionAlgorithm::ALL
+        .iter()
+        .map(|algorithm| {
+            let switched = |options: &AppSettings| AppSettings {
+                algorithm: algorithm.clone(),
+                ..options.clone()
+            };
+            let options: Vec<AppSettings> = deck_options.iter().map(|options| switched(options)).collect();
+
+            // Cards reviewed under another algorithm are migrated first, as when switching for real
+            let simulated = cards
+                .iter()
+                .zip(&option_indexes)
+                .map(|((card, card_options), &index)| {
+                    let card = if card.review_count > 0 && card_options.algorithm != *algorithm {
+                        SpacedRepetition::migrate_card(card, &options[index], start)
+                    } else {
+                        (*card).clone()
+                    };
+                    SimulatedCard::new(card, index)
+                })
+                .collect();
+            run(simulated, &options, &switched(settings), days, start)
+        })
+        .collect()
+}
+
+/// Replay `days` days of study from `start`: every day the due cards are answered within the daily
+/// limits, with the synthetic learner deciding whether each card is remembered
+#[cfg(test)]
+pub fn simulate(cards: &[Card], settings: &AppSettings, days: u32, start: DateTime<Utc>) -> SimulationResult {
+    let simulated = cards.iter().map(|card| SimulatedCard::new(card.clone(), 0)).collect();
+    run(simulated, std::slice::from_ref(settings), settings, days, start)
+}
+
+/// Simulate cards that are scheduled with `options[card.options]`, within the limits of `settings`
+fn run(
+    mut simulated: Vec<SimulatedCard>,
+    options: &[AppSettings],
+    settings: &AppSettings,
+    days: u32,
+    start: DateTime<Utc>,
+) -> SimulationResult {
+    let mut rng = SplitMix64(SEED);
+    let mut load = ReviewLoad::from_cards(simulated.iter().map(|card| &card.card));
+    let mut daily_reviews = Vec::with_capacity(days as usize);
+    let (mut review_answers, mut recalled, mut seconds) = (0u32, 0u32, 0i64);
+
+    for day in 0..days {
+        let day_start = start + Duration::days(day as i64);
+        let day_end = day_start + Duration::days(1);
+
+        let mut queue: BinaryHeap<Reverse<(DateTime<Utc>, usize)>> = todays_cards(&simulated, settings, day_end)
+            .into_iter()
+            .map(|index| Reverse((simulated[index].card.next_review, index)))
+            .collect();
+        let mut now = day_start;
+        let mut reviews = 0u32;
+
+        while let Some(Reverse((due, index))) = queue.pop() {
+            let answered_at = now.max(due);
+            let card = &mut simulated[index];
+            let was_review = card.card.state == CardState::Review;
+            load.remove(&card.card);
+            let remembered = card.answer(&mut rng, &options[card.options], answered_at, &load);
+            load.add(&card.card);
+
+            reviews += 1;
+            if was_review {
+                review_answers += 1;
+                recalled += remembered as u32;
+            }
+            let cost = if remembered { SECONDS_PER_RECALL } else { SECONDS_PER_LAPSE };
+            seconds += cost;
+            now = answered_at + Duration::seconds(cost);
+
+            // Learning steps that fall within the day bring the card back today
+            if card.card.state.is_learning() && card.card.next_review < day_end {
+                queue.push(Reverse((card.card.next_review, index)));
+            }
+        }
+        daily_reviews.push(reviews);
+    }
+
+    SimulationResult {
+        algorithm: settings.algorithm.clone(),
+        total_reviews: daily_reviews.iter().sum(),
+        daily_reviews,
+        retention: (review_answers > 0).then(|| recalled as f64 / review_answers as f64),
+        total_minutes: seconds as f64 / 60.0,
+    }
+}
+
+/// Indexes of the cards studied on a day: cards in (re)learning, then reviews, most overdue first,
+/// then new cards in the order they were created, each within the daily limits
+fn todays_cards(cards: &[SimulatedCard], settings: &AppSettings, day_end: DateTime<Utc>) -> Vec<usize> {
+    let due: Vec<usize> = (0..cards.len()).filter(|&index| cards[index].card.next_review < day_end).collect();
+    let (mut learning, due): (Vec<usize>, Vec<usize>) = due.into_iter().partition(|&index| cards[index].card.state.is_learning());
+    let (mut new_cards, mut reviews): (Vec<usize>, Vec<usize>) = due.into_iter().partition(|&index| cards[index].card.review_count == 0);
+
+    reviews.sort_by_key(|&index| cards[index].card.next_review);
+    reviews.truncate(settings.reviews_per_day as usize);
+    new_cards.sort_by_key(|&index| cards[index].card.created_at);
+    new_cards.truncate(settings.new_cards_per_day as usize);
+
+    learning.extend(reviews);
+    learning.extend(new_cards);
+    learning
+}
+
+/// A card together with the learner's actual memory of it
+struct SimulatedCard {
+    card: Card,
+    options: usize, // Index of the card's scheduling options
+    stability: f64, // 0.0 until the card has been seen
+}
+
+impl SimulatedCard {
+    /// Cards that were already studied start out remembered about as well as they are scheduled
+    fn new(card: Card, options: usize) -> Self {
+        let stability = if card.review_count == 0 {
+            0.0
+        } else if card.fsrs_stability > 0.0 {
+            card.fsrs_stability
+        } else {
+            card.interval.max(1) as f64
+        };
+        SimulatedCard { card, options, stability }
+    }
+
+    /// Answer the card at `now`, returning whether it was remembered
+    fn answer(&mut self, rng: &mut SplitMix64, settings: &AppSettings, now: DateTime<Utc>, load: &ReviewLoad) -> bool {
+        let recall_probability = match self.card.last_reviewed {
+            Some(last) if self.stability > 0.0 => {
+                let elapsed_days = ((now - last).num_seconds() as f64 / 86_400.0).max(0.0);
+                (1.0 + RECALL_FACTOR * elapsed_days / self.stability).powf(RECALL_DECAY)
+            }
+            _ => FIRST_RECALL_PROBABILITY,
+        };
+        let remembered = rng.next_f64() < recall_probability;
+
+        self.stability = if self.stability <= 0.0 {
+            INITIAL_STABILITY
+        } else if remembered {
+            // Recalling a card that was nearly forgotten strengthens it the most
+            self.stability * (1.0 + SPACING_GAIN * (1.0 - recall_probability))
+        } else {
+            (self.stability * LAPSE_STABILITY_FACTOR).max(MIN_STABILITY)
+        };
+
+        let difficulty = if remembered {
+            ReviewDifficulty::Good
+        } else {
+            ReviewDifficulty::Again
+        };
+        SpacedRepetition::answer_card(&mut self.card, &difficulty, settings, now, load);
+        remembered
+    }
+}
+
+/// Small deterministic random number generator, so a simulation gives the same result every time
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn create_test_card(id: &str, created_at: DateTime<Utc>) -> Card {
+        Card {
+            created_at,
+            next_review: created_at,
+            ..Card::new_for_test(id)
+        }
+    }
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap()
+    }
+
+    fn create_test_cards(count: usize) -> Vec<Card> {
+        (0..count)
+            .map(|i| create_test_card(&i.to_string(), start() - Duration::days(1) + Duration::seconds(i as i64)))
+            .collect()
+    }
+
+    #[test]
+    fn test_simulation_respects_new_card_limit() {
+        let settings = AppSettings {
+            new_cards_per_day: 5,
+            learning_steps: Vec::new(),
+            ..Default::default()
+        };
+
+        let result = simulate(&create_test_cards(12), &settings, 3, start());
+
+        // Five new cards on each of the first days; the rest are reviews of cards seen before
+        assert_eq!(result.daily_reviews.len(), 3);
+        assert_eq!(result.daily_reviews[0], 5);
+        assert!(result.daily_reviews[1] >= 5);
+        assert_eq!(result.total_reviews, result.daily_reviews.iter().sum::<u32>());
+        assert!(result.total_minutes > 0.0);
+    }
+
+    #[test]
+    fn test_learning_steps_are_repeated_within_the_day() {
+        let settings = AppSettings::default(); // Steps of 1 and 10 minutes
+
+        let result = simulate(&create_test_cards(1), &settings, 1, start());
+
+        // The first answer and at least one more step on the same day
+        assert!(result.daily_reviews[0] >= 2);
+        assert_eq!(result.retention, None); // No card reached review yet
+    }
+
+    #[test]
+    fn test_simulation_is_deterministic() {
+        let cards = create_test_cards(30);
+        let settings = AppSettings::default();
+        let cards: Vec<(&Card, &AppSettings)> = cards.iter().map(|card| (card, &settings)).collect();
+
+        let first = simulate_algorithms(&cards, &settings, 60, start());
+        let second = simulate_algorithms(&cards, &settings, 60, start());
+
+        assert_eq!(first.len(), SpacedRepetitionAlgorithm::ALL.len());
+        for (a, b) in first.iter().zip(&second) {
+            assert_eq!(a.algorithm, b.algorithm);
+            assert_eq!(a.daily_reviews, b.daily_reviews);
+            assert_eq!(a.retention, b.retention);
+        }
+
+        for result in &first {
+            let retention = result.retention.unwrap();
+            assert!(retention > 0.0 && retention <= 1.0, "{:?}: {}", result.algorithm, retention);
+        }
+    }
+
+    #[test]
+    fn test_cards_are_scheduled_with_their_own_options() {
+        let limits = AppSettings::default(); // Steps of 1 and 10 minutes
+        let no_steps = AppSettings {
+            learning_steps: Vec::new(),
+            ..Default::default()
+        };
+        let cards = create_test_cards(4);
+        let cards: Vec<(&Card, &AppSettings)> = cards.iter().map(|card| (card, &no_steps)).collect();
+
+        // The cards' own options have no learning steps, so each is answered once on the first day
+        for result in simulate_algorithms(&cards, &limits, 1, start()) {
+            assert_eq!(result.daily_reviews, vec![4], "{:?}", result.algorithm);
+        }
+    }
+
+    #[test]
+    fn test_reviewed_cards_are_migrated_to_each_algorithm() {
+        let settings = AppSettings {
+            learning_steps: Vec::new(),
+            ..Default::default()
+        };
+        let cards: Vec<Card> = create_test_cards(20)
+            .into_iter()
+            .enumerate()
+            .map(|(i, card)| Card {
+                state: CardState::Review,
+                interval: 10 + i as i64,
+                review_count: 3,
+                correct_count: 3,
+                last_reviewed: Some(start() - Duration::days(10)),
+                next_review: start() + Duration::days(i as i64),
+                ..card
+            })
+            .collect();
+        let pairs: Vec<(&Card, &AppSettings)> = cards.iter().map(|card| (card, &settings)).collect();
+
+        // Each algorithm starts from the state migrating the cards to it would give them
+        for result in simulate_algorithms(&pairs, &settings, 60, start()) {
+            let options = AppSettings {
+                algorithm: result.algorithm.clone(),
+                ..settings.clone()
+            };
+            let migrated: Vec<Card> = cards
+                .iter()
+                .map(|card| match options.algorithm {
+                    SpacedRepetitionAlgorithm::SM2 => card.clone(),
+                    _ => SpacedRepetition::migrate_card(card, &options, start()),
+                })
+                .collect();
+            let expected = simulate(&migrated, &options, 60, start());
+            assert_eq!(result.daily_reviews, expected.daily_reviews, "{:?}", result.algorithm);
+            assert_eq!(result.retention, expected.retention, "{:?}", result.algorithm);
+        }
+    }
+
+    #[test]
+    fn test_no_days_or_cards() {
+        let settings = AppSettings::default();
+        let empty = simulate(&[], &settings, 10, start());
+        assert_eq!(empty.daily_reviews, vec![0; 10]);
+        assert_eq!(empty.total_minutes, 0.0);
+
+        assert!(simulate(&create_test_cards(3), &settings, 0, start()).daily_reviews.is_empty());
+    }
+
+    #[test]
+    fn test_random_numbers_are_in_range() {
+        let mut rng = SplitMix64(1);
+        for _ in 0..1000 {
+            let value = rng.next_f64();
+            assert!((0.0..1.0).contains(&value));
+        }
+    }
+}
@@ -1,226 +1,706 @@
-use crate::models::{AppSettings, Card, ReviewDifficulty, SpacedRepetitionAlgorithm};
-use chrono::{Duration, Utc};
+use crate::load_balancer::{self, ReviewLoad};
+use crate::models::{AppSettings, Card, CardState, ForecastDay, ReviewDifficulty};
+use crate::scheduler::{Scheduled, Scheduler, SchedulerRegistry, SchedulingState};
+use chrono::{DateTime, Duration, Local, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Default FSRS-4.5 model weights
+const FSRS_WEIGHTS: [f64; 17] = [
+    0.4872, 1.4003, 3.7145, 13.8206, 5.1618, 1.2298, 0.8975, 0.031, 1.6474, 0.1367, 1.0461, 2.1072, 0.0793, 0.3246, 1.587, 0.2272, 2.8755,
+];
+const FSRS_DECAY: f64 = -0.5;
+const FSRS_FACTOR: f64 = 19.0 / 81.0;
+const FSRS_MAX_INTERVAL: i64 = 36500;
+
+/// Cards in (re)learning are shown this many minutes early rather than ending a session
+/// while they wait for their next step
+const LEARN_AHEAD_MINUTES: i64 = 20;
+
+/// Longest review forecast, one year
+pub const MAX_FORECAST_DAYS: u32 = 365;
+
+/// Tag given to cards that become leeches
+pub const LEECH_TAG: &str = "leech";
 
 /// Multi-algorithm spaced repetition system
 pub struct SpacedRepetition;
 
 impl SpacedRepetition {
-    /// Calculate next review parameters based on performance and algorithm
+    /// Answer a card at `now`, moving it through its states and updating its scheduling fields and
+    /// review counts. New cards go through the learning steps and a forgotten review card through
+    /// the relearning steps, a few minutes apart; the algorithm only schedules the days between reviews.
+    /// `load` holds the due dates of the other cards, for load balancing.
+    pub fn answer_card(card: &mut Card, difficulty: &ReviewDifficulty, settings: &AppSettings, now: DateTime<Utc>, load: &ReviewLoad) {
+        // Forgetting a card in learning or relearning is not a lapse; it has not been learned yet
+        if card.state == CardState::Review && matches!(difficulty, ReviewDifficulty::Again) {
+            card.lapses += 1;
+        }
+        Self::schedule_answer(card, difficulty, settings, now, load);
+
+        card.last_reviewed = Some(now);
+        card.review_count += 1;
+        // Good and Easy count as correct
+        if matches!(difficulty, ReviewDifficulty::Good | ReviewDifficulty::Easy) {
+            card.correct_count += 1;
+        }
+    }
+
+    fn schedule_answer(card: &mut Card, difficulty: &ReviewDifficulty, settings: &AppSettings, now: DateTime<Utc>, load: &ReviewLoad) {
+        match card.state {
+            CardState::New | CardState::Learning => match Self::next_step(card.learning_step, &settings.learning_steps, difficulty) {
+                Some(step) => Self::enter_step(card, CardState::Learning, step, &settings.learning_steps, now),
+                None => {
+                    // Graduating is scheduled as the card's first review
+                    let first_review = Card {
+                        review_count: 0,
+                        ..card.clone()
+                    };
+                    *card = Card {
+                        review_count: card.review_count,
+                        ..Self::calculate_next_review(&first_review, difficulty, settings, now, load)
+                    };
+                    card.state = CardState::Review;
+                    card.learning_step = 0;
+                }
+            },
+            CardState::Review => {
+                *card = Self::calculate_next_review(card, difficulty, settings, now, load);
+                if matches!(difficulty, ReviewDifficulty::Again) && !settings.relearning_steps.is_empty() {
+                    // The lapse has already shortened the interval, which applies once relearning is done
+                    Self::enter_step(card, CardState::Relearning, 0, &settings.relearning_steps, now);
+                }
+            }
+            CardState::Relearning => match Self::next_step(card.learning_step, &settings.relearning_steps, difficulty) {
+                Some(step) => Self::enter_step(card, CardState::Relearning, step, &settings.relearning_steps, now),
+                None => {
+                    // The interval was shortened at the lapse; fuzz and load balancing still apply,
+                    // so cards relearned together do not all come back on the same day
+                    let interval = load_balancer::adjust_interval(card, card.interval.max(1), settings, now, load);
+                    card.state = CardState::Review;
+                    card.learning_step = 0;
+                    card.interval = interval;
+                    card.next_review = now + Duration::days(interval);
+                }
+            },
+        }
+    }
+
+    /// Step to show the card at next, or `None` once it leaves the steps: Again starts over,
+    /// Hard repeats the current step, Good moves to the next step and Easy skips the rest
+    fn next_step(current: u32, steps: &[u32], difficulty: &ReviewDifficulty) -> Option<u32> {
+        let last = steps.len().checked_sub(1)? as u32;
+        let current = current.min(last);
+        match difficulty {
+            ReviewDifficulty::Again => Some(0),
+            ReviewDifficulty::Hard => Some(current),
+            ReviewDifficulty::Good => (current < last).then_some(current + 1),
+            ReviewDifficulty::Easy => None,
+        }
+    }
+
+    fn enter_step(card: &mut Card, state: CardState, step: u32, steps: &[u32], now: DateTime<Utc>) {
+        card.state = state;
+        card.learning_step = step;
+        card.next_review = now + Duration::minutes(steps[step as usize] as i64);
+    }
+
+    /// The card scheduled by `settings.algorithm` after an answer at `now`. With interval fuzz or
+    /// load balancing on, the interval is then moved within the tolerance window.
     pub fn calculate_next_review(
         card: &Card,
         difficulty: &ReviewDifficulty,
         settings: &AppSettings,
-    ) -> (i64, f64, chrono::DateTime<Utc>, u32, f64) {
-        match settings.algorithm {
-            SpacedRepetitionAlgorithm::SM2 => Self::calculate_sm2(card, difficulty),
-            SpacedRepetitionAlgorithm::Leitner => Self::calculate_leitner(card, difficulty, &settings.leitner_intervals),
-            SpacedRepetitionAlgorithm::SimpleExponential => Self::calculate_exponential(card, difficulty, settings.exponential_base),
+        now: DateTime<Utc>,
+        load: &ReviewLoad,
+    ) -> Card {
+        let mut scheduled = SchedulerRegistry::get(&settings.algorithm).schedule_card(card, difficulty, settings, now);
+
+        let interval = load_balancer::adjust_interval(card, scheduled.interval, settings, now, load);
+        if interval != scheduled.interval {
+            scheduled.interval = interval;
+            scheduled.next_review = now + Duration::days(interval);
+        }
+        scheduled
+    }
+
+    /// Extra days credited to a card answered after its due date: `overdue_bonus` of the days it
+    /// was late, as of `now`. Zero for cards not reviewed before.
+    fn late_bonus(card: &Card, overdue_bonus: f64, now: DateTime<Utc>) -> f64 {
+        let days_late = card
+            .last_reviewed
+            .map(|last| (now - last).num_seconds() as f64 / 86_400.0 - card.interval as f64)
+            .unwrap_or(0.0)
+            .max(0.0);
+        days_late * overdue_bonus.clamp(0.0, 1.0)
+    }
+
+    /// The card with the scheduling state of `settings.algorithm` derived from the interval it has
+    /// now, for switching algorithms. A review card whose interval changes is rescheduled from its
+    /// last review; cards not yet reviewed, and those in (re)learning, keep their due date.
+    pub fn migrate_card(card: &Card, settings: &AppSettings, now: DateTime<Utc>) -> Card {
+        if card.review_count == 0 {
+            return card.clone();
+        }
+
+        let mut migrated = SchedulerRegistry::get(&settings.algorithm).migrate_card(card, settings);
+        if card.state == CardState::Review && migrated.interval != card.interval {
+            migrated.next_review = card.last_reviewed.unwrap_or(now) + Duration::days(migrated.interval);
+        }
+        migrated
+    }
+
+    /// Make `card` a new card again, as if it had never been reviewed. Its review history is kept.
+    pub fn reset_to_new(card: &mut Card, now: DateTime<Utc>) {
+        Self::reset_memory(card);
+        card.state = CardState::New;
+        card.learning_step = 0;
+        card.interval = 0;
+        card.next_review = now;
+        card.last_reviewed = None;
+        card.review_count = 0;
+        card.correct_count = 0;
+        card.lapses = 0;
+        card.last_answer_ms = None;
+    }
+
+    /// Treat a reviewed card as forgotten: it stays a review card, due at `now`, but every algorithm
+    /// starts over as after a first review. New cards have nothing to forget and are left as they are.
+    pub fn forget(card: &mut Card, now: DateTime<Utc>) {
+        if card.review_count == 0 {
+            return;
+        }
+        Self::reset_memory(card);
+        Sm2State {
+            repetitions: 1,
+            ..Sm2State::default()
+        }
+        .store(card, Sm2State::KEY);
+        card.state = CardState::Review;
+        card.learning_step = 0;
+        card.interval = 1;
+        card.next_review = now;
+    }
+
+    /// The state of every algorithm back to that of a card never reviewed
+    fn reset_memory(card: &mut Card) {
+        card.ease_factor = Sm2State::default().ease_factor;
+        card.leitner_box = 0;
+        card.exponential_factor = ExponentialState::default().factor;
+        card.fsrs_stability = 0.0;
+        card.fsrs_difficulty = 0.0;
+        card.scheduler_state.clear();
+    }
+
+    /// Check if a card is due for review at `now`. Cards in (re)learning count as due a little
+    /// early so they come back within the same session.
+    pub fn is_due(card: &Card, now: DateTime<Utc>) -> bool {
+        if card.suspended || card.is_buried(now) {
+            return false;
         }
+        let learn_ahead = if card.state.is_learning() {
+            Duration::minutes(LEARN_AHEAD_MINUTES)
+        } else {
+            Duration::zero()
+        };
+        card.next_review <= now + learn_ahead
+    }
+
+    /// Whether a card that just lapsed for the `lapses`th time turns out a leech: at `threshold`
+    /// lapses, then again every half threshold for as long as it keeps lapsing
+    pub fn is_leech_lapse(lapses: u32, threshold: u32) -> bool {
+        if threshold == 0 || lapses < threshold {
+            return false;
+        }
+        (lapses - threshold) % (threshold / 2).max(1) == 0
+    }
+
+    /// Get cards that are due for review
+    pub fn get_due_cards(cards: &std::collections::HashMap<String, Card>, now: DateTime<Utc>) -> Vec<Card> {
+        cards.values().filter(|card| Self::is_due(card, now)).cloned().collect()
+    }
+
+    /// Get cards that are due for review from a vector
+    pub fn get_due_cards_from_vec(cards: &[Card], now: DateTime<Utc>) -> Vec<Card> {
+        cards.iter().filter(|card| Self::is_due(card, now)).cloned().collect()
+    }
+
+    /// Calculate review statistics. The remaining counts cover every due card; daily limits are
+    /// applied by the caller.
+    pub fn calculate_stats(cards: &std::collections::HashMap<String, Card>, now: DateTime<Utc>) -> crate::models::ReviewStats {
+        let total_cards = cards.len();
+        let cards_due = cards.values().filter(|card| Self::is_due(card, now)).count();
+        let new_cards_remaining = cards
+            .values()
+            .filter(|card| card.review_count == 0 && Self::is_due(card, now))
+            .count();
+        let cards_new = cards.values().filter(|card| card.review_count == 0).count();
+        let cards_learning = cards.values().filter(|card| card.review_count > 0 && card.interval < 21).count();
+        let cards_mature = cards.values().filter(|card| card.interval >= 21).count();
+        let cards_suspended = cards.values().filter(|card| card.suspended).count();
+        let cards_buried = cards.values().filter(|card| !card.suspended && card.is_buried(now)).count();
+
+        crate::models::ReviewStats {
+            total_cards,
+            cards_due,
+            cards_new,
+            cards_learning,
+            cards_mature,
+            cards_suspended,
+            cards_buried,
+            new_cards_remaining,
+            reviews_remaining: cards_due - new_cards_remaining,
+        }
+    }
+
+    /// Cards due on each local calendar day from today, overdue cards counted today. New cards
+    /// are left out: when they are shown depends on the daily limits rather than on a due date.
+    /// So are suspended cards, which are not shown at all.
+    pub fn forecast<'a>(cards: impl IntoIterator<Item = &'a Card>, days: u32, now: DateTime<Utc>) -> Vec<ForecastDay> {
+        let today = now.with_timezone(&Local).date_naive();
+        let mut forecast: Vec<ForecastDay> = (0..days)
+            .map(|day| ForecastDay {
+                date: today + Duration::days(day as i64),
+                cards_due: 0,
+            })
+            .collect();
+
+        for card in cards.into_iter().filter(|card| card.state != CardState::New && !card.suspended) {
+            let day = (card.next_review.with_timezone(&Local).date_naive() - today).num_days().max(0) as usize;
+            if let Some(entry) = forecast.get_mut(day) {
+                entry.cards_due += 1;
+            }
+        }
+        forecast
     }
+}
+
+/// SM-2 (original). A successful late review was remembered for longer than its interval, so
+/// `overdue_bonus` of the days it was late count towards it.
+pub struct Sm2Scheduler;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Sm2State {
+    pub ease_factor: f64,
+    /// SM-2's repetition number: the answers SM-2 has scheduled since the card graduated. Answers
+    /// in the learning steps are not repetitions.
+    pub repetitions: u32,
+}
 
-    /// SM-2 algorithm implementation (original)
-    fn calculate_sm2(card: &Card, difficulty: &ReviewDifficulty) -> (i64, f64, chrono::DateTime<Utc>, u32, f64) {
+impl Sm2State {
+    /// The repetition number is kept with the state of other algorithms; the ease factor has its own field
+    const KEY: &'static str = "SM2";
+}
+
+impl Default for Sm2State {
+    fn default() -> Self {
+        Sm2State {
+            ease_factor: 2.5,
+            repetitions: 0,
+        }
+    }
+}
+
+impl SchedulingState for Sm2State {
+    fn load(card: &Card, _key: &str) -> Self {
+        let saved = card
+            .scheduler_state
+            .get(Self::KEY)
+            .and_then(|state| state.get("repetitions"))
+            .and_then(serde_json::Value::as_u64);
+        Sm2State {
+            ease_factor: card.ease_factor,
+            // Until the repetition number was kept, every answer of a review card counted as one
+            repetitions: saved.map_or(if card.state == CardState::Review { card.review_count } else { 0 }, |n| n as u32),
+        }
+    }
+
+    fn store(self, card: &mut Card, _key: &str) {
+        card.ease_factor = self.ease_factor;
+        card.scheduler_state
+            .insert(Self::KEY.to_string(), serde_json::json!({ "repetitions": self.repetitions }));
+    }
+}
+
+impl Scheduler for Sm2Scheduler {
+    type State = Sm2State;
+
+    fn schedule(
+        &self,
+        card: &Card,
+        state: &Sm2State,
+        difficulty: &ReviewDifficulty,
+        settings: &AppSettings,
+        now: DateTime<Utc>,
+    ) -> Scheduled<Sm2State> {
         let new_interval;
-        let mut new_ease_factor = card.ease_factor;
+        let mut new_ease_factor = state.ease_factor;
+        let remembered_for = card.interval as f64 + SpacedRepetition::late_bonus(card, settings.overdue_bonus, now);
 
         match difficulty {
             ReviewDifficulty::Again => {
                 // Reset interval, reduce ease factor
                 new_interval = 1;
-                new_ease_factor = (card.ease_factor - 0.2).max(1.3);
+                new_ease_factor = (state.ease_factor - 0.2).max(1.3);
             }
             ReviewDifficulty::Hard => {
                 // Slightly increase interval, reduce ease factor
                 new_interval = ((card.interval as f64) * 1.2).ceil() as i64;
-                new_ease_factor = (card.ease_factor - 0.15).max(1.3);
+                new_ease_factor = (state.ease_factor - 0.15).max(1.3);
             }
             ReviewDifficulty::Good => {
                 // Normal progression
-                if card.review_count == 0 {
+                if state.repetitions == 0 {
                     new_interval = 1;
-                } else if card.review_count == 1 {
+                } else if state.repetitions == 1 {
                     new_interval = 6;
                 } else {
-                    new_interval = ((card.interval as f64) * card.ease_factor).ceil() as i64;
+                    new_interval = (remembered_for * state.ease_factor).ceil() as i64;
                 }
             }
             ReviewDifficulty::Easy => {
                 // Faster progression, increase ease factor
-                if card.review_count == 0 {
+                if state.repetitions == 0 {
                     new_interval = 4;
-                } else if card.review_count == 1 {
+                } else if state.repetitions == 1 {
                     new_interval = 6;
                 } else {
-                    new_interval = ((card.interval as f64) * card.ease_factor * 1.3).ceil() as i64;
+                    new_interval = (remembered_for * state.ease_factor * 1.3).ceil() as i64;
                 }
-                new_ease_factor = card.ease_factor + 0.15;
+                new_ease_factor = state.ease_factor + 0.15;
             }
         }
 
-        let next_review = Utc::now() + Duration::days(new_interval);
-        (
-            new_interval,
-            new_ease_factor,
-            next_review,
-            card.leitner_box,
-            card.exponential_factor,
-        )
+        Scheduled {
+            interval: new_interval,
+            state: Sm2State {
+                ease_factor: new_ease_factor,
+                repetitions: state.repetitions + 1,
+            },
+        }
+    }
+}
+
+/// Leitner system: a card moves up a box when remembered and back when not, and each box has
+/// its interval in `leitner_intervals`
+pub struct LeitnerScheduler;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LeitnerState {
+    pub leitner_box: u32,
+}
+
+impl SchedulingState for LeitnerState {
+    fn load(card: &Card, _key: &str) -> Self {
+        LeitnerState {
+            leitner_box: card.leitner_box,
+        }
+    }
+
+    fn store(self, card: &mut Card, _key: &str) {
+        card.leitner_box = self.leitner_box;
     }
+}
+
+impl LeitnerScheduler {
+    fn box_interval(intervals: &[i64], leitner_box: u32) -> i64 {
+        intervals.get(leitner_box as usize).or(intervals.last()).copied().unwrap_or(1)
+    }
+}
 
-    /// Leitner system implementation
-    fn calculate_leitner(card: &Card, difficulty: &ReviewDifficulty, intervals: &[i64]) -> (i64, f64, chrono::DateTime<Utc>, u32, f64) {
+impl Scheduler for LeitnerScheduler {
+    type State = LeitnerState;
+
+    fn schedule(
+        &self,
+        _card: &Card,
+        state: &LeitnerState,
+        difficulty: &ReviewDifficulty,
+        settings: &AppSettings,
+        _now: DateTime<Utc>,
+    ) -> Scheduled<LeitnerState> {
+        let intervals = &settings.leitner_intervals;
         let max_box = intervals.len().saturating_sub(1) as u32;
-        let new_leitner_box;
+        let new_leitner_box = match difficulty {
+            // Move back to first box
+            ReviewDifficulty::Again => 0,
+            // Stay in current box or move back one
+            ReviewDifficulty::Hard => state.leitner_box.saturating_sub(1),
+            // Move to next box
+            ReviewDifficulty::Good => (state.leitner_box + 1).min(max_box),
+            // Skip a box if possible
+            ReviewDifficulty::Easy => (state.leitner_box + 2).min(max_box),
+        };
 
-        match difficulty {
-            ReviewDifficulty::Again => {
-                // Move back to first box
-                new_leitner_box = 0;
-            }
-            ReviewDifficulty::Hard => {
-                // Stay in current box or move back one
-                if card.leitner_box > 0 {
-                    new_leitner_box = card.leitner_box - 1;
-                } else {
-                    new_leitner_box = 0;
-                }
-            }
-            ReviewDifficulty::Good => {
-                // Move to next box
-                new_leitner_box = (card.leitner_box + 1).min(max_box);
-            }
-            ReviewDifficulty::Easy => {
-                // Skip a box if possible
-                new_leitner_box = (card.leitner_box + 2).min(max_box);
-            }
+        Scheduled {
+            interval: Self::box_interval(intervals, new_leitner_box),
+            state: LeitnerState {
+                leitner_box: new_leitner_box,
+            },
+        }
+    }
+
+    /// The card goes in the last box it has already outgrown
+    fn migrate(&self, card: &Card, _state: &LeitnerState, settings: &AppSettings) -> Scheduled<LeitnerState> {
+        let interval = card.interval.max(1);
+        let intervals = &settings.leitner_intervals;
+        let leitner_box = intervals.iter().rposition(|&box_interval| box_interval <= interval).unwrap_or(0);
+        Scheduled {
+            interval: intervals.get(leitner_box).copied().unwrap_or(interval),
+            state: LeitnerState {
+                leitner_box: leitner_box as u32,
+            },
         }
+    }
+}
 
-        let new_interval = intervals
-            .get(new_leitner_box as usize)
-            .copied()
-            .unwrap_or(intervals[intervals.len() - 1]);
-        let next_review = Utc::now() + Duration::days(new_interval);
+/// Simple exponential spacing: the factor, in days, grows by `exponential_base` with each
+/// successful answer, with the same late review bonus as SM-2
+pub struct ExponentialScheduler;
 
-        (
-            new_interval,
-            card.ease_factor,
-            next_review,
-            new_leitner_box,
-            card.exponential_factor,
-        )
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExponentialState {
+    pub factor: f64,
+}
+
+impl Default for ExponentialState {
+    fn default() -> Self {
+        ExponentialState { factor: 1.0 }
     }
+}
 
-    /// Simple exponential algorithm implementation
-    fn calculate_exponential(card: &Card, difficulty: &ReviewDifficulty, base: f64) -> (i64, f64, chrono::DateTime<Utc>, u32, f64) {
-        let new_exponential_factor;
-        let new_interval;
+impl SchedulingState for ExponentialState {
+    fn load(card: &Card, _key: &str) -> Self {
+        ExponentialState {
+            factor: card.exponential_factor,
+        }
+    }
 
-        match difficulty {
-            ReviewDifficulty::Again => {
-                // Reset to beginning
-                new_exponential_factor = 1.0;
-                new_interval = 1;
-            }
-            ReviewDifficulty::Hard => {
-                // Smaller increase
-                new_exponential_factor = (card.exponential_factor * (base * 0.8)).max(1.0);
-                new_interval = new_exponential_factor.ceil() as i64;
-            }
-            ReviewDifficulty::Good => {
-                // Normal exponential increase
-                new_exponential_factor = card.exponential_factor * base;
-                new_interval = new_exponential_factor.ceil() as i64;
+    fn store(self, card: &mut Card, _key: &str) {
+        card.exponential_factor = self.factor;
+    }
+}
+
+impl Scheduler for ExponentialScheduler {
+    type State = ExponentialState;
+
+    fn schedule(
+        &self,
+        card: &Card,
+        state: &ExponentialState,
+        difficulty: &ReviewDifficulty,
+        settings: &AppSettings,
+        now: DateTime<Utc>,
+    ) -> Scheduled<ExponentialState> {
+        let base = settings.exponential_base;
+        let remembered_for = state.factor + SpacedRepetition::late_bonus(card, settings.overdue_bonus, now);
+
+        let new_factor = match difficulty {
+            // Reset to beginning
+            ReviewDifficulty::Again => 1.0,
+            // Smaller increase
+            ReviewDifficulty::Hard => (state.factor * (base * 0.8)).max(1.0),
+            // Normal exponential increase
+            ReviewDifficulty::Good => remembered_for * base,
+            // Larger increase
+            ReviewDifficulty::Easy => remembered_for * base * 1.5,
+        };
+
+        Scheduled {
+            interval: new_factor.ceil() as i64,
+            state: ExponentialState { factor: new_factor },
+        }
+    }
+
+    fn migrate(&self, card: &Card, _state: &ExponentialState, _settings: &AppSettings) -> Scheduled<ExponentialState> {
+        let interval = card.interval.max(1);
+        Scheduled {
+            interval,
+            state: ExponentialState { factor: interval as f64 },
+        }
+    }
+}
+
+/// FSRS (Free Spaced Repetition Scheduler)
+pub struct FsrsScheduler;
+
+/// Both are 0.0 until the card is first reviewed with FSRS
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FsrsState {
+    pub stability: f64,
+    pub difficulty: f64,
+}
+
+impl SchedulingState for FsrsState {
+    fn load(card: &Card, _key: &str) -> Self {
+        FsrsState {
+            stability: card.fsrs_stability,
+            difficulty: card.fsrs_difficulty,
+        }
+    }
+
+    fn store(self, card: &mut Card, _key: &str) {
+        card.fsrs_stability = self.stability;
+        card.fsrs_difficulty = self.difficulty;
+    }
+}
+
+impl Scheduler for FsrsScheduler {
+    type State = FsrsState;
+
+    fn schedule(
+        &self,
+        card: &Card,
+        state: &FsrsState,
+        difficulty: &ReviewDifficulty,
+        settings: &AppSettings,
+        now: DateTime<Utc>,
+    ) -> Scheduled<FsrsState> {
+        let grade = Self::grade(difficulty);
+        let new_state = if state.stability <= 0.0 {
+            // First FSRS review: initialise the memory state from the rating
+            FsrsState {
+                stability: Self::initial_stability(grade),
+                difficulty: Self::initial_difficulty(grade),
             }
-            ReviewDifficulty::Easy => {
-                // Larger increase
-                new_exponential_factor = card.exponential_factor * base * 1.5;
-                new_interval = new_exponential_factor.ceil() as i64;
+        } else {
+            let elapsed_days = card
+                .last_reviewed
+                .map(|last| (now - last).num_seconds() as f64 / 86_400.0)
+                .unwrap_or(card.interval as f64)
+                .max(0.0);
+            let retrievability = Self::retrievability(elapsed_days, state.stability);
+
+            FsrsState {
+                stability: match difficulty {
+                    ReviewDifficulty::Again => Self::forget_stability(state.difficulty, state.stability, retrievability),
+                    _ => Self::recall_stability(state.difficulty, state.stability, retrievability, difficulty),
+                },
+                difficulty: Self::next_difficulty(state.difficulty, grade),
             }
+        };
+
+        Scheduled {
+            interval: Self::interval(new_state.stability, settings.desired_retention),
+            state: new_state,
         }
+    }
 
-        let next_review = Utc::now() + Duration::days(new_interval);
-        (
-            new_interval,
-            card.ease_factor,
-            next_review,
-            card.leitner_box,
-            new_exponential_factor,
-        )
+    /// Other algorithms aim for roughly 90% recall, which FSRS calls the stability; difficulty
+    /// grows with the share of answers that were not Good or Easy
+    fn migrate(&self, card: &Card, state: &FsrsState, settings: &AppSettings) -> Scheduled<FsrsState> {
+        let stability = card.interval.max(1) as f64;
+        let difficulty = if state.difficulty > 0.0 {
+            state.difficulty
+        } else {
+            let missed = 1.0 - card.correct_count as f64 / card.review_count.max(1) as f64;
+            let good = Self::initial_difficulty(3.0);
+            (good + missed * (10.0 - good)).clamp(1.0, 10.0)
+        };
+        Scheduled {
+            interval: Self::interval(stability, settings.desired_retention),
+            state: FsrsState { stability, difficulty },
+        }
     }
+}
 
-    /// Check if a card is due for review
-    pub fn is_due(card: &Card) -> bool {
-        card.next_review <= Utc::now()
+impl FsrsScheduler {
+    /// FSRS rating scale (Again = 1 ... Easy = 4)
+    fn grade(difficulty: &ReviewDifficulty) -> f64 {
+        match difficulty {
+            ReviewDifficulty::Again => 1.0,
+            ReviewDifficulty::Hard => 2.0,
+            ReviewDifficulty::Good => 3.0,
+            ReviewDifficulty::Easy => 4.0,
+        }
     }
 
-    /// Get cards that are due for review
-    pub fn get_due_cards(cards: &std::collections::HashMap<String, Card>) -> Vec<Card> {
-        cards.values().filter(|card| Self::is_due(card)).cloned().collect()
+    fn initial_stability(grade: f64) -> f64 {
+        FSRS_WEIGHTS[grade as usize - 1].max(0.1)
     }
 
-    /// Get cards that are due for review from a vector
-    pub fn get_due_cards_from_vec(cards: &[Card]) -> Vec<Card> {
-        cards.iter().filter(|card| Self::is_due(card)).cloned().collect()
+    fn initial_difficulty(grade: f64) -> f64 {
+        (FSRS_WEIGHTS[4] - (grade - 3.0) * FSRS_WEIGHTS[5]).clamp(1.0, 10.0)
     }
 
-    /// Calculate review statistics
-    pub fn calculate_stats(cards: &std::collections::HashMap<String, Card>) -> crate::models::ReviewStats {
-        let total_cards = cards.len();
-        let cards_due = cards.values().filter(|card| Self::is_due(card)).count();
-        let cards_new = cards.values().filter(|card| card.review_count == 0).count();
-        let cards_learning = cards.values().filter(|card| card.review_count > 0 && card.interval < 21).count();
-        let cards_mature = cards.values().filter(|card| card.interval >= 21).count();
+    /// Probability of recalling a card `elapsed_days` after its last review
+    fn retrievability(elapsed_days: f64, stability: f64) -> f64 {
+        (1.0 + FSRS_FACTOR * elapsed_days / stability).powf(FSRS_DECAY)
+    }
 
-        crate::models::ReviewStats {
-            total_cards,
-            cards_due,
-            cards_new,
-            cards_learning,
-            cards_mature,
-        }
+    fn next_difficulty(difficulty: f64, grade: f64) -> f64 {
+        let next = difficulty - FSRS_WEIGHTS[6] * (grade - 3.0);
+        // Mean reversion towards the default difficulty of a "Good" first answer
+        let reverted = FSRS_WEIGHTS[7] * Self::initial_difficulty(3.0) + (1.0 - FSRS_WEIGHTS[7]) * next;
+        reverted.clamp(1.0, 10.0)
+    }
+
+    fn recall_stability(difficulty: f64, stability: f64, retrievability: f64, rating: &ReviewDifficulty) -> f64 {
+        let hard_penalty = if matches!(rating, ReviewDifficulty::Hard) {
+            FSRS_WEIGHTS[15]
+        } else {
+            1.0
+        };
+        let easy_bonus = if matches!(rating, ReviewDifficulty::Easy) {
+            FSRS_WEIGHTS[16]
+        } else {
+            1.0
+        };
+
+        stability
+            * (1.0
+                + FSRS_WEIGHTS[8].exp()
+                    * (11.0 - difficulty)
+                    * stability.powf(-FSRS_WEIGHTS[9])
+                    * ((FSRS_WEIGHTS[10] * (1.0 - retrievability)).exp() - 1.0)
+                    * hard_penalty
+                    * easy_bonus)
+    }
+
+    fn forget_stability(difficulty: f64, stability: f64, retrievability: f64) -> f64 {
+        let forgotten = FSRS_WEIGHTS[11]
+            * difficulty.powf(-FSRS_WEIGHTS[12])
+            * ((stability + 1.0).powf(FSRS_WEIGHTS[13]) - 1.0)
+            * (FSRS_WEIGHTS[14] * (1.0 - retrievability)).exp();
+        // A lapse never makes a memory more stable than it was
+        forgotten.min(stability).max(0.1)
+    }
+
+    /// Days until recall probability falls to the desired retention
+    fn interval(stability: f64, desired_retention: f64) -> i64 {
+        let retention = desired_retention.clamp(0.7, 0.99);
+        let interval = stability / FSRS_FACTOR * (retention.powf(1.0 / FSRS_DECAY) - 1.0);
+        (interval.round() as i64).clamp(1, FSRS_MAX_INTERVAL)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{Card, ReviewDifficulty};
+    use crate::models::{Card, CardState, ReviewDifficulty, SpacedRepetitionAlgorithm};
     use chrono::{Duration, Utc};
-    use std::collections::HashMap;
+    use std::collections::{BTreeSet, HashMap};
 
     fn create_test_card(id: &str, review_count: u32, interval: i64, ease_factor: f64) -> Card {
         Card {
-            id: id.to_string(),
-            front: format!("Question {}", id),
-            back: format!("Answer {}", id),
-            tag: None,
-            created_at: Utc::now(),
             last_reviewed: if review_count > 0 { Some(Utc::now()) } else { None },
             next_review: Utc::now() + Duration::days(interval),
             interval,
             ease_factor,
             review_count,
             correct_count: review_count / 2, // Assume half correct
-            leitner_box: 0,
-            exponential_factor: 1.0,
+            state: if review_count > 0 { CardState::Review } else { CardState::New },
+            ..Card::new_for_test(id)
         }
     }
 
     fn create_due_card(id: &str) -> Card {
         Card {
-            id: id.to_string(),
-            front: format!("Question {}", id),
-            back: format!("Answer {}", id),
-            tag: None,
-            created_at: Utc::now(),
             last_reviewed: Some(Utc::now() - Duration::days(1)),
             next_review: Utc::now() - Duration::hours(1), // Due 1 hour ago
             interval: 1,
-            ease_factor: 2.5,
             review_count: 1,
-            correct_count: 0,
-            leitner_box: 0,
-            exponential_factor: 1.0,
+            state: CardState::Review,
+            ..Card::new_for_test(id)
         }
     }
 
@@ -232,131 +712,169 @@ mod tests {
     fn test_calculate_next_review_again() {
         let card = create_test_card("1", 5, 10, 2.5);
         let settings = default_settings();
-        let (new_interval, new_ease_factor, next_review, _, _) =
-            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Again, &settings);
+        let scheduled =
+            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Again, &settings, Utc::now(), &ReviewLoad::default());
 
-        assert_eq!(new_interval, 1);
-        assert_eq!(new_ease_factor, 2.3); // 2.5 - 0.2
-        assert!(next_review > Utc::now());
-        assert!(next_review <= Utc::now() + Duration::days(1) + Duration::seconds(1));
+        assert_eq!(scheduled.interval, 1);
+        assert_eq!(scheduled.ease_factor, 2.3); // 2.5 - 0.2
+        assert!(scheduled.next_review > Utc::now());
+        assert!(scheduled.next_review <= Utc::now() + Duration::days(1) + Duration::seconds(1));
     }
 
     #[test]
     fn test_calculate_next_review_hard() {
         let card = create_test_card("1", 5, 10, 2.5);
         let settings = default_settings();
-        let (new_interval, new_ease_factor, next_review, _, _) =
-            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Hard, &settings);
+        let scheduled =
+            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Hard, &settings, Utc::now(), &ReviewLoad::default());
 
-        assert_eq!(new_interval, 12); // ceil(10 * 1.2)
-        assert_eq!(new_ease_factor, 2.35); // 2.5 - 0.15
-        assert!(next_review > Utc::now() + Duration::days(11));
-        assert!(next_review <= Utc::now() + Duration::days(12) + Duration::seconds(1));
+        assert_eq!(scheduled.interval, 12); // ceil(10 * 1.2)
+        assert_eq!(scheduled.ease_factor, 2.35); // 2.5 - 0.15
+        assert!(scheduled.next_review > Utc::now() + Duration::days(11));
+        assert!(scheduled.next_review <= Utc::now() + Duration::days(12) + Duration::seconds(1));
     }
 
     #[test]
     fn test_calculate_next_review_good_new_card() {
         let card = create_test_card("1", 0, 0, 2.5);
         let settings = default_settings();
-        let (new_interval, new_ease_factor, next_review, _, _) =
-            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Good, &settings);
+        let scheduled =
+            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Good, &settings, Utc::now(), &ReviewLoad::default());
 
-        assert_eq!(new_interval, 1);
-        assert_eq!(new_ease_factor, 2.5);
-        assert!(next_review > Utc::now());
-        assert!(next_review <= Utc::now() + Duration::days(1) + Duration::seconds(1));
+        assert_eq!(scheduled.interval, 1);
+        assert_eq!(scheduled.ease_factor, 2.5);
+        assert!(scheduled.next_review > Utc::now());
+        assert!(scheduled.next_review <= Utc::now() + Duration::days(1) + Duration::seconds(1));
     }
 
     #[test]
     fn test_calculate_next_review_good_second_review() {
         let card = create_test_card("1", 1, 1, 2.5);
         let settings = default_settings();
-        let (new_interval, new_ease_factor, next_review, _, _) =
-            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Good, &settings);
+        let scheduled =
+            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Good, &settings, Utc::now(), &ReviewLoad::default());
 
-        assert_eq!(new_interval, 6);
-        assert_eq!(new_ease_factor, 2.5);
-        assert!(next_review > Utc::now() + Duration::days(5));
-        assert!(next_review <= Utc::now() + Duration::days(6) + Duration::seconds(1));
+        assert_eq!(scheduled.interval, 6);
+        assert_eq!(scheduled.ease_factor, 2.5);
+        assert!(scheduled.next_review > Utc::now() + Duration::days(5));
+        assert!(scheduled.next_review <= Utc::now() + Duration::days(6) + Duration::seconds(1));
     }
 
     #[test]
     fn test_calculate_next_review_good_mature_card() {
         let card = create_test_card("1", 5, 10, 2.5);
         let settings = default_settings();
-        let (new_interval, new_ease_factor, next_review, _, _) =
-            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Good, &settings);
+        let scheduled =
+            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Good, &settings, Utc::now(), &ReviewLoad::default());
+
+        assert_eq!(scheduled.interval, 25); // ceil(10 * 2.5)
+        assert_eq!(scheduled.ease_factor, 2.5);
+        assert!(scheduled.next_review > Utc::now() + Duration::days(24));
+        assert!(scheduled.next_review <= Utc::now() + Duration::days(25) + Duration::seconds(1));
+    }
+
+    #[test]
+    fn test_late_reviews_get_a_bonus() {
+        let now = Utc::now();
+        let mut card = create_test_card("1", 5, 10, 2.5);
+        card.last_reviewed = Some(now - Duration::days(70)); // 60 days late
+        let settings = default_settings();
+        let interval_for = |card: &Card, difficulty, settings: &AppSettings| {
+            SpacedRepetition::calculate_next_review(card, &difficulty, settings, now, &ReviewLoad::default()).interval
+        };
+
+        assert_eq!(interval_for(&card, ReviewDifficulty::Good, &settings), 100); // (10 + 60 * 0.5) * 2.5
+        assert_eq!(interval_for(&card, ReviewDifficulty::Easy, &settings), 130); // (10 + 60 * 0.5) * 2.5 * 1.3
+        assert_eq!(interval_for(&card, ReviewDifficulty::Hard, &settings), 12); // Hard gets no bonus
+        assert_eq!(interval_for(&card, ReviewDifficulty::Again, &settings), 1);
 
-        assert_eq!(new_interval, 25); // ceil(10 * 2.5)
-        assert_eq!(new_ease_factor, 2.5);
-        assert!(next_review > Utc::now() + Duration::days(24));
-        assert!(next_review <= Utc::now() + Duration::days(25) + Duration::seconds(1));
+        let no_bonus = AppSettings {
+            overdue_bonus: 0.0,
+            ..default_settings()
+        };
+        assert_eq!(interval_for(&card, ReviewDifficulty::Good, &no_bonus), 25);
+
+        card.exponential_factor = 10.0;
+        let exponential = AppSettings {
+            algorithm: SpacedRepetitionAlgorithm::SimpleExponential,
+            ..default_settings()
+        };
+        assert_eq!(interval_for(&card, ReviewDifficulty::Good, &exponential), 80);
+        // (10 + 60 * 0.5) * 2
     }
 
     #[test]
     fn test_calculate_next_review_easy_new_card() {
         let card = create_test_card("1", 0, 0, 2.5);
         let settings = default_settings();
-        let (new_interval, new_ease_factor, next_review, _, _) =
-            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Easy, &settings);
+        let scheduled =
+            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Easy, &settings, Utc::now(), &ReviewLoad::default());
 
-        assert_eq!(new_interval, 4);
-        assert_eq!(new_ease_factor, 2.65); // 2.5 + 0.15
-        assert!(next_review > Utc::now() + Duration::days(3));
-        assert!(next_review <= Utc::now() + Duration::days(4) + Duration::seconds(1));
+        assert_eq!(scheduled.interval, 4);
+        assert_eq!(scheduled.ease_factor, 2.65); // 2.5 + 0.15
+        assert!(scheduled.next_review > Utc::now() + Duration::days(3));
+        assert!(scheduled.next_review <= Utc::now() + Duration::days(4) + Duration::seconds(1));
     }
 
     #[test]
     fn test_calculate_next_review_easy_mature_card() {
         let card = create_test_card("1", 5, 10, 2.5);
         let settings = default_settings();
-        let (new_interval, new_ease_factor, next_review, _, _) =
-            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Easy, &settings);
+        let scheduled =
+            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Easy, &settings, Utc::now(), &ReviewLoad::default());
 
-        assert_eq!(new_interval, 33); // ceil(10 * 2.5 * 1.3)
-        assert_eq!(new_ease_factor, 2.65); // 2.5 + 0.15
-        assert!(next_review > Utc::now() + Duration::days(32));
-        assert!(next_review <= Utc::now() + Duration::days(33) + Duration::seconds(1));
+        assert_eq!(scheduled.interval, 33); // ceil(10 * 2.5 * 1.3)
+        assert_eq!(scheduled.ease_factor, 2.65); // 2.5 + 0.15
+        assert!(scheduled.next_review > Utc::now() + Duration::days(32));
+        assert!(scheduled.next_review <= Utc::now() + Duration::days(33) + Duration::seconds(1));
     }
 
     #[test]
     fn test_ease_factor_minimum() {
         let mut card = create_test_card("1", 5, 10, 1.3); // Already at minimum
         let settings = default_settings();
-        let (_, new_ease_factor, _, _, _) = SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Again, &settings);
+        let scheduled =
+            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Again, &settings, Utc::now(), &ReviewLoad::default());
 
-        assert_eq!(new_ease_factor, 1.3); // Should not go below 1.3
+        assert_eq!(scheduled.ease_factor, 1.3); // Should not go below 1.3
 
         // Multiple "Again" responses should not decrease below 1.3
         card.ease_factor = 1.4;
-        let (_, new_ease_factor, _, _, _) = SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Again, &settings);
-        assert_eq!(new_ease_factor, 1.3);
+        let scheduled =
+            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Again, &settings, Utc::now(), &ReviewLoad::default());
+        assert_eq!(scheduled.ease_factor, 1.3);
     }
 
     #[test]
     fn test_is_due() {
         let due_card = create_due_card("1");
-        assert!(SpacedRepetition::is_due(&due_card));
+        assert!(SpacedRepetition::is_due(&due_card, Utc::now()));
 
         let future_card = create_test_card("2", 1, 5, 2.5);
-        assert!(!SpacedRepetition::is_due(&future_card));
+        assert!(!SpacedRepetition::is_due(&future_card, Utc::now()));
 
         let now_card = Card {
-            id: "3".to_string(),
             front: "Question".to_string(),
             back: "Answer".to_string(),
-            tag: None,
-            created_at: Utc::now(),
-            last_reviewed: None,
-            next_review: Utc::now(),
-            interval: 0,
-            ease_factor: 2.5,
-            review_count: 0,
-            correct_count: 0,
-            leitner_box: 0,
-            exponential_factor: 1.0,
+            ..Card::new_for_test("3")
         };
-        assert!(SpacedRepetition::is_due(&now_card));
+        assert!(SpacedRepetition::is_due(&now_card, Utc::now()));
+
+        let suspended_card = Card {
+            suspended: true,
+            ..now_card
+        };
+        assert!(!SpacedRepetition::is_due(&suspended_card, Utc::now()));
+    }
+
+    #[test]
+    fn test_is_leech_lapse() {
+        let leech_lapses: Vec<u32> = (1..=16).filter(|&lapses| SpacedRepetition::is_leech_lapse(lapses, 8)).collect();
+        assert_eq!(leech_lapses, vec![8, 12, 16]);
+
+        assert!(SpacedRepetition::is_leech_lapse(1, 1));
+        assert!(SpacedRepetition::is_leech_lapse(2, 1));
+        assert!(!SpacedRepetition::is_leech_lapse(5, 0)); // Turned off
     }
 
     #[test]
@@ -371,7 +889,7 @@ mod tests {
         cards.insert("2".to_string(), future_card);
         cards.insert("3".to_string(), another_due_card);
 
-        let due_cards = SpacedRepetition::get_due_cards(&cards);
+        let due_cards = SpacedRepetition::get_due_cards(&cards, Utc::now());
         assert_eq!(due_cards.len(), 2);
 
         let due_ids: Vec<String> = due_cards.iter().map(|c| c.id.clone()).collect();
@@ -387,7 +905,7 @@ mod tests {
         let another_due_card = create_due_card("3");
 
         let cards = vec![due_card, future_card, another_due_card];
-        let due_cards = SpacedRepetition::get_due_cards_from_vec(&cards);
+        let due_cards = SpacedRepetition::get_due_cards_from_vec(&cards, Utc::now());
 
         assert_eq!(due_cards.len(), 2);
         let due_ids: Vec<String> = due_cards.iter().map(|c| c.id.clone()).collect();
@@ -412,7 +930,7 @@ mod tests {
         // Due card
         cards.insert("4".to_string(), create_due_card("4"));
 
-        let stats = SpacedRepetition::calculate_stats(&cards);
+        let stats = SpacedRepetition::calculate_stats(&cards, Utc::now());
 
         assert_eq!(stats.total_cards, 4);
         assert_eq!(stats.cards_due, 2); // Cards "1" (new but due) and "4" (due)
@@ -424,7 +942,7 @@ mod tests {
     #[test]
     fn test_calculate_stats_empty() {
         let cards = HashMap::new();
-        let stats = SpacedRepetition::calculate_stats(&cards);
+        let stats = SpacedRepetition::calculate_stats(&cards, Utc::now());
 
         assert_eq!(stats.total_cards, 0);
         assert_eq!(stats.cards_due, 0);
@@ -433,101 +951,282 @@ mod tests {
         assert_eq!(stats.cards_mature, 0);
     }
 
+    #[test]
+    fn test_migrate_card() {
+        let now = Utc::now();
+        let mut mature = create_test_card("mature", 10, 200, 2.5);
+        mature.last_reviewed = Some(now - Duration::days(50));
+        mature.next_review = now + Duration::days(150);
+        let settings_for = |algorithm| AppSettings {
+            algorithm,
+            ..default_settings()
+        };
+
+        // A mature card goes to the last Leitner box instead of the first
+        let leitner = SpacedRepetition::migrate_card(&mature, &settings_for(SpacedRepetitionAlgorithm::Leitner), now);
+        assert_eq!(leitner.leitner_box, 4);
+        assert_eq!(leitner.interval, 30);
+        assert_eq!(leitner.next_review, now - Duration::days(20)); // Already overdue
+
+        let exponential = SpacedRepetition::migrate_card(&mature, &settings_for(SpacedRepetitionAlgorithm::SimpleExponential), now);
+        assert_eq!(exponential.exponential_factor, 200.0);
+        assert_eq!(exponential.next_review, mature.next_review);
+
+        let fsrs = SpacedRepetition::migrate_card(&mature, &settings_for(SpacedRepetitionAlgorithm::FSRS), now);
+        assert_eq!(fsrs.fsrs_stability, 200.0);
+        assert_eq!(fsrs.interval, 200);
+        assert!(fsrs.fsrs_difficulty > 1.0 && fsrs.fsrs_difficulty < 10.0);
+        let strict = AppSettings {
+            desired_retention: 0.95,
+            ..settings_for(SpacedRepetitionAlgorithm::FSRS)
+        };
+        assert!(SpacedRepetition::migrate_card(&mature, &strict, now).next_review < mature.next_review);
+
+        // New cards have nothing to migrate
+        let new_card = create_test_card("new", 0, 0, 2.5);
+        let migrated = SpacedRepetition::migrate_card(&new_card, &settings_for(SpacedRepetitionAlgorithm::Leitner), now);
+        assert_eq!(migrated.leitner_box, 0);
+        assert_eq!(migrated.next_review, new_card.next_review);
+    }
+
+    #[test]
+    fn test_forecast() {
+        let now = Utc::now();
+        let mut overdue = create_test_card("overdue", 3, 0, 2.5);
+        overdue.next_review = now - Duration::days(5);
+        let mut learning = create_test_card("learning", 1, 0, 2.5);
+        learning.state = CardState::Learning;
+        learning.next_review = now + Duration::minutes(1);
+        let cards = vec![
+            overdue,
+            learning,
+            create_test_card("new", 0, 0, 2.5),
+            create_test_card("tomorrow", 2, 1, 2.5),
+            create_test_card("in_three_days", 2, 3, 2.5),
+            create_test_card("later", 5, 30, 2.5),
+        ];
+
+        let forecast = SpacedRepetition::forecast(&cards, 7, now);
+
+        let counts: Vec<usize> = forecast.iter().map(|day| day.cards_due).collect();
+        assert_eq!(counts.iter().sum::<usize>(), 4); // The new card and the card due after a week are left out
+        assert!(counts[0] >= 1 && counts[0] + counts[1] == 3); // A learning card may fall after midnight
+        assert_eq!(counts[3], 1);
+        assert_eq!(forecast[0].date, now.with_timezone(&Local).date_naive());
+        assert_eq!(forecast[6].date, forecast[0].date + Duration::days(6));
+        assert!(SpacedRepetition::forecast(&cards, 0, now).is_empty());
+    }
+
     // Algorithm-specific tests
+    fn leitner_settings() -> AppSettings {
+        AppSettings {
+            leitner_intervals: vec![1, 3, 7, 14, 30],
+            ..Default::default()
+        }
+    }
+
+    fn exponential_settings(exponential_base: f64) -> AppSettings {
+        AppSettings {
+            exponential_base,
+            ..Default::default()
+        }
+    }
+
     #[test]
-    fn test_calculate_leitner_good_review() {
-        let mut card = create_test_card("1", 2, 1, 2.5);
-        card.leitner_box = 1;
+    fn test_leitner_good_review() {
+        let card = create_test_card("1", 2, 1, 2.5);
+        let scheduled = LeitnerScheduler.schedule(
+            &card,
+            &LeitnerState { leitner_box: 1 },
+            &ReviewDifficulty::Good,
+            &leitner_settings(),
+            Utc::now(),
+        );
+
+        assert_eq!(scheduled.interval, 7); // intervals[2]
+        assert_eq!(scheduled.state.leitner_box, 2);
+    }
 
-        let intervals = vec![1, 3, 7, 14, 30];
-        let (new_interval, _, _, new_leitner_box, _) = SpacedRepetition::calculate_leitner(&card, &ReviewDifficulty::Good, &intervals);
+    #[test]
+    fn test_leitner_again_review() {
+        let card = create_test_card("1", 2, 1, 2.5);
+        let scheduled = LeitnerScheduler.schedule(
+            &card,
+            &LeitnerState { leitner_box: 3 },
+            &ReviewDifficulty::Again,
+            &leitner_settings(),
+            Utc::now(),
+        );
+
+        assert_eq!(scheduled.interval, 1); // intervals[0]
+        assert_eq!(scheduled.state.leitner_box, 0);
+    }
 
-        assert_eq!(new_interval, 7); // intervals[2]
-        assert_eq!(new_leitner_box, 2);
+    #[test]
+    fn test_leitner_hard_review() {
+        let card = create_test_card("1", 2, 1, 2.5);
+        let scheduled = LeitnerScheduler.schedule(
+            &card,
+            &LeitnerState { leitner_box: 2 },
+            &ReviewDifficulty::Hard,
+            &leitner_settings(),
+            Utc::now(),
+        );
+
+        assert_eq!(scheduled.interval, 3); // intervals[1] (moved back one box)
+        assert_eq!(scheduled.state.leitner_box, 1);
     }
 
     #[test]
-    fn test_calculate_leitner_again_review() {
-        let mut card = create_test_card("1", 2, 1, 2.5);
-        card.leitner_box = 3;
+    fn test_leitner_max_box() {
+        let card = create_test_card("1", 2, 1, 2.5);
+        let last_box = LeitnerState { leitner_box: 4 };
+        let scheduled = LeitnerScheduler.schedule(&card, &last_box, &ReviewDifficulty::Good, &leitner_settings(), Utc::now());
 
-        let intervals = vec![1, 3, 7, 14, 30];
-        let (new_interval, _, _, new_leitner_box, _) = SpacedRepetition::calculate_leitner(&card, &ReviewDifficulty::Again, &intervals);
+        assert_eq!(scheduled.interval, 30); // intervals[4] (stays in last box)
+        assert_eq!(scheduled.state.leitner_box, 4);
+    }
 
-        assert_eq!(new_interval, 1); // intervals[0]
-        assert_eq!(new_leitner_box, 0);
+    #[test]
+    fn test_exponential_good_review() {
+        let card = create_test_card("1", 2, 1, 2.5);
+        let state = ExponentialState { factor: 1.5 };
+        let scheduled = ExponentialScheduler.schedule(&card, &state, &ReviewDifficulty::Good, &exponential_settings(2.0), Utc::now());
+
+        assert_eq!(scheduled.interval, 3); // ceil(1.5 * 2.0)
+        assert_eq!(scheduled.state.factor, 3.0); // 1.5 * 2.0
     }
 
     #[test]
-    fn test_calculate_leitner_hard_review() {
-        let mut card = create_test_card("1", 2, 1, 2.5);
-        card.leitner_box = 2;
+    fn test_exponential_again_review() {
+        let card = create_test_card("1", 2, 1, 2.5);
+        let state = ExponentialState { factor: 2.0 };
+        let scheduled = ExponentialScheduler.schedule(&card, &state, &ReviewDifficulty::Again, &exponential_settings(2.0), Utc::now());
+
+        assert_eq!(scheduled.interval, 1);
+        assert_eq!(scheduled.state.factor, 1.0); // Reset to 1.0
+    }
 
-        let intervals = vec![1, 3, 7, 14, 30];
-        let (new_interval, _, _, new_leitner_box, _) = SpacedRepetition::calculate_leitner(&card, &ReviewDifficulty::Hard, &intervals);
+    #[test]
+    fn test_exponential_hard_review() {
+        let card = create_test_card("1", 2, 1, 2.5);
+        let state = ExponentialState { factor: 2.0 };
+        let scheduled = ExponentialScheduler.schedule(&card, &state, &ReviewDifficulty::Hard, &exponential_settings(2.0), Utc::now());
 
-        assert_eq!(new_interval, 3); // intervals[1] (moved back one box)
-        assert_eq!(new_leitner_box, 1);
+        assert_eq!(scheduled.interval, 4); // ceil(2.0 * (2.0 * 0.8)) = ceil(3.2) = 4
+        assert_eq!(scheduled.state.factor, 3.2); // 2.0 * (2.0 * 0.8)
     }
 
     #[test]
-    fn test_calculate_leitner_max_box() {
-        let mut card = create_test_card("1", 2, 1, 2.5);
-        card.leitner_box = 4; // Last box
+    fn test_exponential_easy_review() {
+        let card = create_test_card("1", 2, 1, 2.5);
+        let state = ExponentialState { factor: 1.5 };
+        let scheduled = ExponentialScheduler.schedule(&card, &state, &ReviewDifficulty::Easy, &exponential_settings(2.5), Utc::now());
 
-        let intervals = vec![1, 3, 7, 14, 30];
-        let (new_interval, _, _, new_leitner_box, _) = SpacedRepetition::calculate_leitner(&card, &ReviewDifficulty::Good, &intervals);
+        assert_eq!(scheduled.interval, 6); // ceil(1.5 * 2.5 * 1.5) = ceil(5.625) = 6
+        assert_eq!(scheduled.state.factor, 5.625); // 1.5 * 2.5 * 1.5
+    }
 
-        assert_eq!(new_interval, 30); // intervals[4] (stays in last box)
-        assert_eq!(new_leitner_box, 4);
+    fn create_fsrs_card(id: &str, stability: f64, difficulty: f64, days_since_review: i64) -> Card {
+        let mut card = create_test_card(id, 3, days_since_review, 2.5);
+        card.last_reviewed = Some(Utc::now() - Duration::days(days_since_review));
+        card.next_review = Utc::now();
+        card.fsrs_stability = stability;
+        card.fsrs_difficulty = difficulty;
+        card
+    }
+
+    fn schedule_fsrs(card: &Card, difficulty: ReviewDifficulty, desired_retention: f64) -> Scheduled<FsrsState> {
+        let settings = AppSettings {
+            desired_retention,
+            ..Default::default()
+        };
+        FsrsScheduler.schedule(card, &FsrsState::load(card, "FSRS"), &difficulty, &settings, Utc::now())
     }
 
     #[test]
-    fn test_calculate_exponential_good_review() {
-        let mut card = create_test_card("1", 2, 1, 2.5);
-        card.exponential_factor = 1.5;
+    fn test_fsrs_first_review_good() {
+        let card = create_test_card("1", 0, 0, 2.5);
+        let scheduled = schedule_fsrs(&card, ReviewDifficulty::Good, 0.9);
 
-        let base = 2.0;
-        let (new_interval, _, _, _, new_factor) = SpacedRepetition::calculate_exponential(&card, &ReviewDifficulty::Good, base);
+        assert_eq!(scheduled.state.stability, FSRS_WEIGHTS[2]);
+        assert_eq!(scheduled.state.difficulty, FSRS_WEIGHTS[4]);
+        assert_eq!(scheduled.interval, 4); // At 90% retention the interval equals the stability
+    }
 
-        assert_eq!(new_interval, 3); // ceil(1.5 * 2.0)
-        assert_eq!(new_factor, 3.0); // 1.5 * 2.0
+    #[test]
+    fn test_fsrs_first_review_again() {
+        let card = create_test_card("1", 0, 0, 2.5);
+        let scheduled = schedule_fsrs(&card, ReviewDifficulty::Again, 0.9);
+
+        assert_eq!(scheduled.state.stability, FSRS_WEIGHTS[0]);
+        assert!((scheduled.state.difficulty - (FSRS_WEIGHTS[4] + 2.0 * FSRS_WEIGHTS[5])).abs() < 1e-9);
+        assert_eq!(scheduled.interval, 1);
     }
 
     #[test]
-    fn test_calculate_exponential_again_review() {
-        let mut card = create_test_card("1", 2, 1, 2.5);
-        card.exponential_factor = 2.0;
+    fn test_fsrs_successful_review_grows_stability() {
+        let card = create_fsrs_card("1", 10.0, 5.0, 10);
+        let scheduled = schedule_fsrs(&card, ReviewDifficulty::Good, 0.9);
+
+        assert!(scheduled.state.stability > 10.0);
+        assert!(scheduled.interval > 10);
+        assert!((1.0..=10.0).contains(&scheduled.state.difficulty));
+    }
+
+    #[test]
+    fn test_fsrs_rating_order() {
+        let card = create_fsrs_card("1", 10.0, 5.0, 10);
+        let stability_for = |difficulty| schedule_fsrs(&card, difficulty, 0.9).state.stability;
+
+        let again = stability_for(ReviewDifficulty::Again);
+        let hard = stability_for(ReviewDifficulty::Hard);
+        let good = stability_for(ReviewDifficulty::Good);
+        let easy = stability_for(ReviewDifficulty::Easy);
+
+        assert!(again < 10.0); // Lapse reduces stability
+        assert!(hard < good);
+        assert!(good < easy);
+    }
 
-        let base = 2.0;
-        let (new_interval, _, _, _, new_factor) = SpacedRepetition::calculate_exponential(&card, &ReviewDifficulty::Again, base);
+    #[test]
+    fn test_fsrs_difficulty_bounds() {
+        let hard_card = create_fsrs_card("1", 5.0, 10.0, 5);
+        assert!(schedule_fsrs(&hard_card, ReviewDifficulty::Again, 0.9).state.difficulty <= 10.0);
 
-        assert_eq!(new_interval, 1);
-        assert_eq!(new_factor, 1.0); // Reset to 1.0
+        let easy_card = create_fsrs_card("2", 5.0, 1.0, 5);
+        assert!(schedule_fsrs(&easy_card, ReviewDifficulty::Easy, 0.9).state.difficulty >= 1.0);
     }
 
     #[test]
-    fn test_calculate_exponential_hard_review() {
-        let mut card = create_test_card("1", 2, 1, 2.5);
-        card.exponential_factor = 2.0;
+    fn test_fsrs_desired_retention() {
+        let card = create_fsrs_card("1", 20.0, 5.0, 20);
 
-        let base = 2.0;
-        let (new_interval, _, _, _, new_factor) = SpacedRepetition::calculate_exponential(&card, &ReviewDifficulty::Hard, base);
+        let relaxed_interval = schedule_fsrs(&card, ReviewDifficulty::Good, 0.8).interval;
+        let strict_interval = schedule_fsrs(&card, ReviewDifficulty::Good, 0.95).interval;
 
-        assert_eq!(new_interval, 4); // ceil(2.0 * (2.0 * 0.8)) = ceil(3.2) = 4
-        assert_eq!(new_factor, 3.2); // 2.0 * (2.0 * 0.8)
+        // Asking for higher retention schedules reviews sooner
+        assert!(strict_interval < relaxed_interval);
     }
 
     #[test]
-    fn test_calculate_exponential_easy_review() {
-        let mut card = create_test_card("1", 2, 1, 2.5);
-        card.exponential_factor = 1.5;
+    fn test_calculate_next_review_fsrs_settings() {
+        let card = create_test_card("1", 0, 0, 2.5);
+        let settings = crate::models::AppSettings {
+            algorithm: crate::models::SpacedRepetitionAlgorithm::FSRS,
+            ..Default::default()
+        };
 
-        let base = 2.5;
-        let (new_interval, _, _, _, new_factor) = SpacedRepetition::calculate_exponential(&card, &ReviewDifficulty::Easy, base);
+        let now = Utc::now();
+        let scheduled = SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Easy, &settings, now, &ReviewLoad::default());
 
-        assert_eq!(new_interval, 6); // ceil(1.5 * 2.5 * 1.5) = ceil(5.625) = 6
-        assert_eq!(new_factor, 5.625); // 1.5 * 2.5 * 1.5
+        assert_eq!(scheduled.fsrs_stability, FSRS_WEIGHTS[3]);
+        assert_eq!(scheduled.interval, 14); // round(13.8206)
+        assert_eq!(scheduled.next_review, now + Duration::days(14));
+        // The state of the other algorithms is untouched
+        assert_eq!(scheduled.ease_factor, card.ease_factor);
+        assert_eq!(scheduled.leitner_box, card.leitner_box);
+        assert_eq!(scheduled.exponential_factor, card.exponential_factor);
     }
 
     #[test]
@@ -538,19 +1237,25 @@ mod tests {
         let mut sm2_settings = crate::models::AppSettings::default();
         sm2_settings.algorithm = crate::models::SpacedRepetitionAlgorithm::SM2;
 
-        let (sm2_interval, _sm2_ease, _, _, _) = SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Good, &sm2_settings);
+        let sm2 =
+            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Good, &sm2_settings, Utc::now(), &ReviewLoad::default());
 
         // Leitner calculation
         let mut leitner_settings = crate::models::AppSettings::default();
         leitner_settings.algorithm = crate::models::SpacedRepetitionAlgorithm::Leitner;
 
-        let (leitner_interval, _, _, leitner_box, _) =
-            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Good, &leitner_settings);
+        let leitner = SpacedRepetition::calculate_next_review(
+            &card,
+            &ReviewDifficulty::Good,
+            &leitner_settings,
+            Utc::now(),
+            &ReviewLoad::default(),
+        );
 
         // Different algorithms should produce different results
         // SM2: 6 * 2.5 = 15, Leitner: intervals[1] = 3
-        assert_ne!(sm2_interval, leitner_interval);
-        assert_eq!(leitner_box, 1); // Leitner should advance to box 1
+        assert_ne!(sm2.interval, leitner.interval);
+        assert_eq!(leitner.leitner_box, 1); // Leitner should advance to box 1
     }
 
     #[test]
@@ -562,17 +1267,183 @@ mod tests {
         custom_settings.algorithm = crate::models::SpacedRepetitionAlgorithm::Leitner;
         custom_settings.leitner_intervals = vec![2, 5, 12, 25, 50];
 
-        let (interval, _, _, _, _) = SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Good, &custom_settings);
+        let scheduled =
+            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Good, &custom_settings, Utc::now(), &ReviewLoad::default());
 
-        assert_eq!(interval, 5); // custom_settings.leitner_intervals[1]
+        assert_eq!(scheduled.interval, 5); // custom_settings.leitner_intervals[1]
 
         // Custom exponential base
         let mut exp_settings = crate::models::AppSettings::default();
         exp_settings.algorithm = crate::models::SpacedRepetitionAlgorithm::SimpleExponential;
         exp_settings.exponential_base = 3.0;
 
-        let (exp_interval, _, _, _, _) = SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Good, &exp_settings);
+        let scheduled =
+            SpacedRepetition::calculate_next_review(&card, &ReviewDifficulty::Good, &exp_settings, Utc::now(), &ReviewLoad::default());
+
+        assert_eq!(scheduled.interval, 3); // ceil(1.0 * 3.0)
+    }
 
-        assert_eq!(exp_interval, 3); // ceil(1.0 * 3.0)
+    #[test]
+    fn test_answer_card_learning_steps() {
+        let now = Utc::now();
+        let mut card = create_test_card("1", 0, 0, 2.5);
+        let settings = default_settings(); // Steps of 1 and 10 minutes
+
+        SpacedRepetition::answer_card(&mut card, &ReviewDifficulty::Good, &settings, now, &ReviewLoad::default());
+        assert_eq!(card.state, CardState::Learning);
+        assert_eq!(card.learning_step, 1);
+        assert_eq!(card.interval, 0);
+        assert_eq!(card.next_review, now + Duration::minutes(10));
+
+        // Again starts the steps over, Hard repeats the current one
+        SpacedRepetition::answer_card(&mut card, &ReviewDifficulty::Again, &settings, now, &ReviewLoad::default());
+        assert_eq!(card.learning_step, 0);
+        assert_eq!(card.next_review, now + Duration::minutes(1));
+        SpacedRepetition::answer_card(&mut card, &ReviewDifficulty::Hard, &settings, now, &ReviewLoad::default());
+        assert_eq!(card.learning_step, 0);
+
+        SpacedRepetition::answer_card(&mut card, &ReviewDifficulty::Good, &settings, now, &ReviewLoad::default());
+        SpacedRepetition::answer_card(&mut card, &ReviewDifficulty::Good, &settings, now, &ReviewLoad::default());
+        assert_eq!(card.state, CardState::Review);
+        assert_eq!(card.interval, 1); // SM-2 interval of a first review
+        assert_eq!(card.next_review, now + Duration::days(1));
+    }
+
+    #[test]
+    fn test_answer_card_easy_and_empty_steps_graduate() {
+        let now = Utc::now();
+        let settings = default_settings();
+        let mut card = create_test_card("1", 0, 0, 2.5);
+        SpacedRepetition::answer_card(&mut card, &ReviewDifficulty::Easy, &settings, now, &ReviewLoad::default());
+        assert_eq!(card.state, CardState::Review);
+        assert_eq!(card.interval, 4);
+
+        let no_steps = crate::models::AppSettings {
+            learning_steps: Vec::new(),
+            ..Default::default()
+        };
+        let mut card = create_test_card("2", 0, 0, 2.5);
+        SpacedRepetition::answer_card(&mut card, &ReviewDifficulty::Again, &no_steps, now, &ReviewLoad::default());
+        assert_eq!(card.state, CardState::Review);
+        assert_eq!(card.interval, 1);
+    }
+
+    #[test]
+    fn test_answer_card_lapse_relearns() {
+        let now = Utc::now();
+        let settings = default_settings(); // One relearning step of 10 minutes
+        let mut card = create_test_card("1", 5, 10, 2.5);
+
+        SpacedRepetition::answer_card(&mut card, &ReviewDifficulty::Again, &settings, now, &ReviewLoad::default());
+        assert_eq!(card.state, CardState::Relearning);
+        assert_eq!(card.interval, 1);
+        assert_eq!(card.ease_factor, 2.3);
+        assert_eq!(card.next_review, now + Duration::minutes(10));
+        assert_eq!(card.lapses, 1);
+
+        // Failing a relearning step is not another lapse
+        let mut failed_step = card.clone();
+        SpacedRepetition::answer_card(&mut failed_step, &ReviewDifficulty::Again, &settings, now, &ReviewLoad::default());
+        assert_eq!(failed_step.lapses, 1);
+
+        // Passing the step brings the card back to review at its shortened interval
+        let later = now + Duration::minutes(10);
+        SpacedRepetition::answer_card(&mut card, &ReviewDifficulty::Good, &settings, later, &ReviewLoad::default());
+        assert_eq!(card.state, CardState::Review);
+        assert_eq!(card.interval, 1);
+        assert_eq!(card.ease_factor, 2.3);
+        assert_eq!(card.next_review, later + Duration::days(1));
+
+        // Without relearning steps a lapse is scheduled in days straight away
+        let no_steps = crate::models::AppSettings {
+            relearning_steps: Vec::new(),
+            ..Default::default()
+        };
+        let mut card = create_test_card("2", 5, 10, 2.5);
+        SpacedRepetition::answer_card(&mut card, &ReviewDifficulty::Again, &no_steps, now, &ReviewLoad::default());
+        assert_eq!(card.state, CardState::Review);
+        assert_eq!(card.next_review, now + Duration::days(1));
+    }
+
+    #[test]
+    fn test_relearned_cards_are_fuzzed() {
+        let now = Utc::now();
+        let settings = crate::models::AppSettings {
+            interval_fuzz: true,
+            fuzz_tolerance: 0.25,
+            ..Default::default()
+        };
+
+        // Lapsed cards leave relearning with the same shortened interval, but not on the same day
+        let intervals: BTreeSet<i64> = (0..20)
+            .map(|i| {
+                let mut card = Card {
+                    state: CardState::Relearning,
+                    ..create_test_card(&i.to_string(), 5, 8, 2.5)
+                };
+                SpacedRepetition::answer_card(&mut card, &ReviewDifficulty::Good, &settings, now, &ReviewLoad::default());
+                assert_eq!(card.state, CardState::Review);
+                assert_eq!(card.next_review, now + Duration::days(card.interval));
+                card.interval
+            })
+            .collect();
+        assert!(intervals.len() > 1);
+        assert!(intervals.iter().all(|interval| (6..=10).contains(interval)));
+    }
+
+    #[test]
+    fn test_reset_and_forget() {
+        let now = Utc::now();
+        let mut card = create_test_card("1", 6, 30, 1.9);
+        card.leitner_box = 4;
+        card.exponential_factor = 8.0;
+        card.fsrs_stability = 25.0;
+        card.fsrs_difficulty = 6.0;
+        card.lapses = 2;
+        card.scheduler_state
+            .insert("custom".to_string(), serde_json::json!({ "answers": 6 }));
+
+        let mut forgotten = card.clone();
+        SpacedRepetition::forget(&mut forgotten, now);
+        assert_eq!(
+            (forgotten.state, forgotten.interval, forgotten.next_review),
+            (CardState::Review, 1, now)
+        );
+        assert_eq!(
+            (forgotten.ease_factor, forgotten.leitner_box, forgotten.exponential_factor),
+            (2.5, 0, 1.0)
+        );
+        assert_eq!((forgotten.fsrs_stability, forgotten.fsrs_difficulty), (0.0, 0.0));
+        // The custom state is gone; SM-2 counts the card as reviewed once
+        assert_eq!(forgotten.scheduler_state.keys().collect::<Vec<_>>(), vec!["SM2"]);
+        assert_eq!(Sm2State::load(&forgotten, "SM2").repetitions, 1);
+        assert_eq!((forgotten.review_count, forgotten.lapses), (6, 2));
+
+        SpacedRepetition::reset_to_new(&mut card, now);
+        assert_eq!((card.state, card.interval, card.next_review), (CardState::New, 0, now));
+        assert_eq!((card.ease_factor, card.leitner_box, card.exponential_factor), (2.5, 0, 1.0));
+        assert_eq!((card.review_count, card.correct_count, card.lapses), (0, 0, 0));
+        assert_eq!(card.last_reviewed, None);
+
+        // A new card has nothing to forget
+        let before = card.clone();
+        SpacedRepetition::forget(&mut card, now);
+        assert_eq!(card.next_review, before.next_review);
+        assert_eq!(card.state, CardState::New);
+    }
+
+    #[test]
+    fn test_learning_cards_are_due_early() {
+        let now = Utc::now();
+        let mut card = create_test_card("1", 1, 0, 2.5);
+        card.next_review = now + Duration::minutes(10);
+        assert!(!SpacedRepetition::is_due(&card, now));
+
+        card.state = CardState::Learning;
+        assert!(SpacedRepetition::is_due(&card, now));
+        assert!(!SpacedRepetition::is_due(&card, now - Duration::minutes(11)));
+
+        card.next_review = now + Duration::hours(1);
+        assert!(!SpacedRepetition::is_due(&card, now));
     }
 }
@@ -0,0 +1,651 @@
+use crate::models::{AppSettings, Card, DeckCollection, ReviewLog, StorageRecoveryReport};
+use crate::storage::{app_data_dir, salvage_settings, JsonStorage, Storage};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+// Bumped whenever the schema changes; 0 means the database was just created
+const SCHEMA_VERSION: i32 = 3;
+
+/// Stores each card as its own row, so a review only rewrites the card that changed
+pub struct SqliteStorage {
+    conn: Mutex<Connection>,
+    data_dir: PathBuf,
+    recovery_reports: Mutex<Vec<StorageRecoveryReport>>,
+}
+
+impl SqliteStorage {
+    pub fn new(app_handle: AppHandle) -> Result<Self, Box<dyn std::error::Error>> {
+        let data_dir = app_data_dir(&app_handle)?;
+        Self::in_dir(&data_dir)
+    }
+
+    /// Open `cards.db` inside `data_dir`, importing the JSON files found there the first time
+    pub fn in_dir(data_dir: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let conn = Connection::open(data_dir.join("cards.db"))?;
+        let storage = Self::from_connection(conn, data_dir)?;
+
+        let legacy = JsonStorage::in_dir(data_dir);
+        if storage.needs_migration()? && legacy.has_data() {
+            storage.migrate_from(&legacy)?;
+            legacy.mark_migrated();
+        }
+        storage.set_schema_version()?;
+
+        Ok(storage)
+    }
+
+    fn from_connection(conn: Connection, data_dir: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA synchronous = NORMAL;
+             CREATE TABLE IF NOT EXISTS cards (
+                 id TEXT PRIMARY KEY,
+                 data TEXT NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS settings (
+                 id INTEGER PRIMARY KEY CHECK (id = 1),
+                 data TEXT NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS decks (
+                 id INTEGER PRIMARY KEY CHECK (id = 1),
+                 data TEXT NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS review_log (
+                 seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                 id TEXT NOT NULL,
+                 card_id TEXT NOT NULL,
+                 data TEXT NOT NULL,
+                 reviewed_at INTEGER
+             );
+             CREATE INDEX IF NOT EXISTS review_log_card_id ON review_log (card_id);",
+        )?;
+        add_review_log_reviewed_at(&conn)?;
+        conn.execute_batch("CREATE INDEX IF NOT EXISTS review_log_reviewed_at ON review_log (reviewed_at);")?;
+
+        Ok(SqliteStorage {
+            conn: Mutex::new(conn),
+            data_dir: data_dir.to_path_buf(),
+            recovery_reports: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>, Box<dyn std::error::Error>> {
+        self.conn.lock().map_err(|_| "Failed to lock database".into())
+    }
+
+    fn needs_migration(&self) -> Result<bool, Box<dyn std::error::Error>> {
+        let conn = self.lock()?;
+        let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        Ok(version == 0)
+    }
+
+    fn set_schema_version(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let conn = self.lock()?;
+        conn.execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION))?;
+        Ok(())
+    }
+
+    /// Copy cards, settings and review history from the JSON files in a single transaction.
+    /// The schema version is set in the same transaction, so a launch interrupted before the
+    /// JSON files are renamed does not import them a second time.
+    fn migrate_from(&self, legacy: &JsonStorage) -> Result<(), Box<dyn std::error::Error>> {
+        let cards = legacy.load_cards()?;
+        let settings = legacy.load_settings()?;
+        let decks = legacy.load_decks()?;
+        let review_log = legacy.load_review_log()?;
+
+        let mut conn = self.lock()?;
+        let tx = conn.transaction()?;
+        for card in cards.values() {
+            upsert_card(&tx, card)?;
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO settings (id, data) VALUES (1, ?1)",
+            params![serde_json::to_string(&settings)?],
+        )?;
+        tx.execute(
+            "INSERT OR REPLACE INTO decks (id, data) VALUES (1, ?1)",
+            params![serde_json::to_string(&decks)?],
+        )?;
+        for entry in &review_log {
+            insert_review_log(&tx, entry)?;
+        }
+        tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        tx.commit()?;
+
+        log::info!(
+            "Migrated {} cards and {} review log entries from JSON storage",
+            cards.len(),
+            review_log.len()
+        );
+
+        if let Ok(mut reports) = self.recovery_reports.lock() {
+            reports.extend(legacy.take_recovery_reports());
+        }
+        Ok(())
+    }
+
+    fn record_recovery(&self, report: StorageRecoveryReport) {
+        if let Ok(mut reports) = self.recovery_reports.lock() {
+            reports.push(report);
+        }
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load_cards(&self) -> Result<HashMap<String, Card>, Box<dyn std::error::Error>> {
+        let conn = self.lock()?;
+        let mut statement = conn.prepare("SELECT id, data FROM cards")?;
+        let rows = statement.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+
+        let mut cards = HashMap::new();
+        let mut first_error = None;
+        let mut lost_items = 0;
+        for row in rows {
+            let (id, data) = row?;
+            match serde_json::from_str::<Card>(&data) {
+                Ok(card) => {
+                    cards.insert(id, card);
+                }
+                Err(e) => {
+                    // The row is left in place; only a full save_cards would remove it
+                    log::error!("Failed to parse card {}: {}", id, e);
+                    first_error.get_or_insert(e.to_string());
+                    lost_items += 1;
+                }
+            }
+        }
+
+        if let Some(error) = first_error {
+            self.record_recovery(StorageRecoveryReport {
+                file: "cards.db".to_string(),
+                error,
+                quarantined_to: None,
+                recovered_items: cards.len(),
+                lost_items,
+                detected_at: Utc::now(),
+            });
+        }
+
+        Ok(cards)
+    }
+
+    fn save_cards(&self, cards: &HashMap<String, Card>) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = self.lock()?;
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM cards", [])?;
+        for card in cards.values() {
+            upsert_card(&tx, card)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn upsert_cards(&self, cards: &HashMap<String, Card>, ids: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = self.lock()?;
+        let tx = conn.transaction()?;
+        for card in ids.iter().filter_map(|id| cards.get(id)) {
+            upsert_card(&tx, card)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn delete_cards(&self, _cards: &HashMap<String, Card>, ids: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = self.lock()?;
+        let tx = conn.transaction()?;
+        for id in ids {
+            tx.execute("DELETE FROM cards WHERE id = ?1", params![id])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn load_settings(&self) -> Result<AppSettings, Box<dyn std::error::Error>> {
+        let conn = self.lock()?;
+        let data: Option<String> = conn
+            .query_row("SELECT data FROM settings WHERE id = 1", [], |row| row.get(0))
+            .optional()?;
+
+        let Some(data) = data else {
+            return Ok(AppSettings::default());
+        };
+
+        match serde_json::from_str(&data) {
+            Ok(settings) => Ok(settings),
+            Err(e) => {
+                log::error!("Failed to parse stored settings: {}", e);
+                let (settings, recovered_items, lost_items) = salvage_settings(data.as_bytes());
+                self.record_recovery(StorageRecoveryReport {
+                    file: "cards.db".to_string(),
+                    error: e.to_string(),
+                    quarantined_to: None,
+                    recovered_items,
+                    lost_items,
+                    detected_at: Utc::now(),
+                });
+                Ok(settings)
+            }
+        }
+    }
+
+    fn save_settings(&self, settings: &AppSettings) -> Result<(), Box<dyn std::error::Error>> {
+        let conn = self.lock()?;
+        conn.execute(
+            "INSERT OR REPLACE INTO settings (id, data) VALUES (1, ?1)",
+            params![serde_json::to_string(settings)?],
+        )?;
+        Ok(())
+    }
+
+    fn load_decks(&self) -> Result<DeckCollection, Box<dyn std::error::Error>> {
+        let conn = self.lock()?;
+        let data: Option<String> = conn
+            .query_row("SELECT data FROM decks WHERE id = 1", [], |row| row.get(0))
+            .optional()?;
+
+        let Some(data) = data else {
+            return Ok(DeckCollection::default());
+        };
+
+        match serde_json::from_str(&data) {
+            Ok(decks) => Ok(decks),
+            Err(e) => {
+                log::error!("Failed to parse stored decks: {}", e);
+                self.record_recovery(StorageRecoveryReport {
+                    file: "cards.db".to_string(),
+                    error: e.to_string(),
+                    quarantined_to: None,
+                    recovered_items: 0,
+                    lost_items: 1,
+                    detected_at: Utc::now(),
+                });
+                Ok(DeckCollection::default())
+            }
+        }
+    }
+
+    fn save_decks(&self, decks: &DeckCollection) -> Result<(), Box<dyn std::error::Error>> {
+        let conn = self.lock()?;
+        conn.execute(
+            "INSERT OR REPLACE INTO decks (id, data) VALUES (1, ?1)",
+            params![serde_json::to_string(decks)?],
+        )?;
+        Ok(())
+    }
+
+    fn append_review_log(&self, entry: &ReviewLog) -> Result<(), Box<dyn std::error::Error>> {
+        let conn = self.lock()?;
+        insert_review_log(&conn, entry)
+    }
+
+    fn load_review_log(&self) -> Result<Vec<ReviewLog>, Box<dyn std::error::Error>> {
+        let conn = self.lock()?;
+        let mut statement = conn.prepare("SELECT data FROM review_log ORDER BY seq")?;
+        let rows = statement.query_map([], |row| row.get::<_, String>(0))?;
+        parse_review_log(rows)
+    }
+
+    fn load_review_log_since(&self, since: DateTime<Utc>) -> Result<Vec<ReviewLog>, Box<dyn std::error::Error>> {
+        let conn = self.lock()?;
+        let mut statement = conn.prepare("SELECT data FROM review_log WHERE reviewed_at >= ?1 ORDER BY seq")?;
+        let rows = statement.query_map(params![since.timestamp_millis()], |row| row.get::<_, String>(0))?;
+        parse_review_log(rows)
+    }
+
+    fn remove_review_log_entry(&self, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let conn = self.lock()?;
+        conn.execute("DELETE FROM review_log WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    fn replace_review_log(&self, entries: &[ReviewLog]) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = self.lock()?;
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM review_log", [])?;
+        for entry in entries {
+            insert_review_log(&tx, entry)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn backup_dir(&self) -> PathBuf {
+        self.data_dir.join("backups")
+    }
+
+    fn take_recovery_reports(&self) -> Vec<StorageRecoveryReport> {
+        self.recovery_reports
+            .lock()
+            .map(|mut reports| std::mem::take(&mut *reports))
+            .unwrap_or_default()
+    }
+}
+
+fn upsert_card(conn: &Connection, card: &Card) -> Result<(), Box<dyn std::error::Error>> {
+    conn.execute(
+        "INSERT INTO cards (id, data) VALUES (?1, ?2)
+         ON CONFLICT (id) DO UPDATE SET data = excluded.data",
+        params![card.id, serde_json::to_string(card)?],
+    )?;
+    Ok(())
+}
+
+fn insert_review_log(conn: &Connection, entry: &ReviewLog) -> Result<(), Box<dyn std::error::Error>> {
+    conn.execute(
+        "INSERT INTO review_log (id, card_id, data, reviewed_at) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            entry.id,
+            entry.card_id,
+            serde_json::to_string(entry)?,
+            entry.reviewed_at.timestamp_millis()
+        ],
+    )?;
+    Ok(())
+}
+
+fn parse_review_log(rows: impl Iterator<Item = rusqlite::Result<String>>) -> Result<Vec<ReviewLog>, Box<dyn std::error::Error>> {
+    let mut entries = Vec::new();
+    for row in rows {
+        match serde_json::from_str::<ReviewLog>(&row?) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => log::warn!("Skipping unreadable review log entry: {}", e),
+        }
+    }
+    Ok(entries)
+}
+
+/// Databases from before version 3 lack the indexed review time; fill it in from each entry's data
+fn add_review_log_reviewed_at(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+    let has_column: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('review_log') WHERE name = 'reviewed_at'",
+        [],
+        |row| row.get(0),
+    )?;
+    if has_column {
+        return Ok(());
+    }
+
+    conn.execute_batch("ALTER TABLE review_log ADD COLUMN reviewed_at INTEGER;")?;
+    let mut statement = conn.prepare("SELECT seq, data FROM review_log")?;
+    let rows = statement.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (seq, data) = row?;
+        if let Ok(entry) = serde_json::from_str::<ReviewLog>(&data) {
+            conn.execute(
+                "UPDATE review_log SET reviewed_at = ?1 WHERE seq = ?2",
+                params![entry.reviewed_at.timestamp_millis(), seq],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SpacedRepetitionAlgorithm;
+    use chrono::Duration;
+    use std::collections::BTreeSet;
+    use tempfile::TempDir;
+
+    fn create_test_card(id: &str) -> Card {
+        Card {
+            tags: BTreeSet::from(["Test".to_string()]),
+            ..Card::new_for_test(id)
+        }
+    }
+
+    fn create_test_review_log(card_id: &str) -> ReviewLog {
+        ReviewLog {
+            was_new: true,
+            ..ReviewLog::new_for_test(card_id)
+        }
+    }
+
+    fn create_test_storage() -> (SqliteStorage, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = SqliteStorage::in_dir(temp_dir.path()).unwrap();
+        (storage, temp_dir)
+    }
+
+    /// Tests that an empty database loads as an empty deck with default settings.
+    #[test]
+    fn test_load_from_empty_database() {
+        let (storage, _temp_dir) = create_test_storage();
+
+        assert!(storage.load_cards().unwrap().is_empty());
+        assert_eq!(storage.load_settings().unwrap().algorithm, AppSettings::default().algorithm);
+        assert!(storage.load_review_log().unwrap().is_empty());
+    }
+
+    /// Verifies that upserting writes only the listed cards and updates them in place.
+    #[test]
+    fn test_upsert_cards() {
+        let (storage, _temp_dir) = create_test_storage();
+
+        let mut cards = HashMap::new();
+        cards.insert("1".to_string(), create_test_card("1"));
+        cards.insert("2".to_string(), create_test_card("2"));
+        storage.upsert_cards(&cards, &["1".to_string()]).unwrap();
+
+        let loaded = storage.load_cards().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert!(loaded.contains_key("1"));
+
+        cards.get_mut("1").unwrap().front = "Updated".to_string();
+        storage.upsert_cards(&cards, &["1".to_string(), "2".to_string()]).unwrap();
+
+        let loaded = storage.load_cards().unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded["1"].front, "Updated");
+    }
+
+    /// Tests that deleting removes only the listed cards.
+    #[test]
+    fn test_delete_cards() {
+        let (storage, _temp_dir) = create_test_storage();
+
+        let mut cards = HashMap::new();
+        cards.insert("1".to_string(), create_test_card("1"));
+        cards.insert("2".to_string(), create_test_card("2"));
+        storage.save_cards(&cards).unwrap();
+
+        cards.remove("1");
+        storage.delete_cards(&cards, &["1".to_string()]).unwrap();
+
+        let loaded = storage.load_cards().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert!(loaded.contains_key("2"));
+    }
+
+    /// Verifies that save_cards replaces the whole stored collection.
+    #[test]
+    fn test_save_cards_replaces_collection() {
+        let (storage, _temp_dir) = create_test_storage();
+
+        let mut cards = HashMap::new();
+        cards.insert("1".to_string(), create_test_card("1"));
+        storage.save_cards(&cards).unwrap();
+
+        let mut replacement = HashMap::new();
+        replacement.insert("2".to_string(), create_test_card("2"));
+        storage.save_cards(&replacement).unwrap();
+
+        let loaded = storage.load_cards().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert!(loaded.contains_key("2"));
+    }
+
+    /// Tests settings and review log persistence across reopening the database.
+    #[test]
+    fn test_data_persists_after_reopen() {
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let storage = SqliteStorage::in_dir(temp_dir.path()).unwrap();
+            let settings = AppSettings {
+                algorithm: SpacedRepetitionAlgorithm::Leitner,
+                ..Default::default()
+            };
+            storage.save_settings(&settings).unwrap();
+            storage.append_review_log(&create_test_review_log("a")).unwrap();
+            storage.append_review_log(&create_test_review_log("b")).unwrap();
+        }
+
+        let storage = SqliteStorage::in_dir(temp_dir.path()).unwrap();
+        assert_eq!(storage.load_settings().unwrap().algorithm, SpacedRepetitionAlgorithm::Leitner);
+
+        let log = storage.load_review_log().unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].card_id, "a");
+        assert_eq!(log[1].card_id, "b");
+    }
+
+    /// Ensures existing JSON data is imported on first launch and the JSON files are renamed
+    /// so they are not imported again.
+    #[test]
+    fn test_migrates_json_files_on_first_launch() {
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let legacy = JsonStorage::in_dir(temp_dir.path());
+            let mut cards = HashMap::new();
+            cards.insert("1".to_string(), create_test_card("1"));
+            cards.insert("2".to_string(), create_test_card("2"));
+            legacy.save_cards(&cards).unwrap();
+            let settings = AppSettings {
+                exponential_base: 3.0,
+                ..Default::default()
+            };
+            legacy.save_settings(&settings).unwrap();
+            legacy.append_review_log(&create_test_review_log("1")).unwrap();
+        }
+
+        let storage = SqliteStorage::in_dir(temp_dir.path()).unwrap();
+        assert_eq!(storage.load_cards().unwrap().len(), 2);
+        assert_eq!(storage.load_settings().unwrap().exponential_base, 3.0);
+        assert_eq!(storage.load_review_log().unwrap().len(), 1);
+
+        assert!(!temp_dir.path().join("cards.json").exists());
+        assert!(temp_dir.path().join("cards.json.migrated").exists());
+        assert!(temp_dir.path().join("settings.json.migrated").exists());
+        assert!(temp_dir.path().join("review_log.jsonl.migrated").exists());
+    }
+
+    /// Ensures a migration interrupted before the JSON files were renamed is not repeated.
+    #[test]
+    fn test_interrupted_migration_is_not_repeated() {
+        let temp_dir = TempDir::new().unwrap();
+        let legacy = JsonStorage::in_dir(temp_dir.path());
+        let mut cards = HashMap::new();
+        cards.insert("1".to_string(), create_test_card("1"));
+        legacy.save_cards(&cards).unwrap();
+        legacy.append_review_log(&create_test_review_log("1")).unwrap();
+
+        // The first launch stops right after the import committed
+        let conn = Connection::open(temp_dir.path().join("cards.db")).unwrap();
+        SqliteStorage::from_connection(conn, temp_dir.path())
+            .unwrap()
+            .migrate_from(&legacy)
+            .unwrap();
+        assert!(temp_dir.path().join("review_log.jsonl").exists());
+
+        let storage = SqliteStorage::in_dir(temp_dir.path()).unwrap();
+        assert_eq!(storage.load_cards().unwrap().len(), 1);
+        assert_eq!(storage.load_review_log().unwrap().len(), 1);
+    }
+
+    /// Verifies that JSON files appearing after the first launch are ignored.
+    #[test]
+    fn test_migration_runs_once() {
+        let temp_dir = TempDir::new().unwrap();
+        drop(SqliteStorage::in_dir(temp_dir.path()).unwrap());
+
+        let mut cards = HashMap::new();
+        cards.insert("1".to_string(), create_test_card("1"));
+        JsonStorage::in_dir(temp_dir.path()).save_cards(&cards).unwrap();
+
+        let storage = SqliteStorage::in_dir(temp_dir.path()).unwrap();
+        assert!(storage.load_cards().unwrap().is_empty());
+        assert!(temp_dir.path().join("cards.json").exists());
+    }
+
+    /// Tests that a damaged card row is skipped and reported without losing the others.
+    #[test]
+    fn test_unreadable_card_row_is_reported() {
+        let (storage, _temp_dir) = create_test_storage();
+
+        let mut cards = HashMap::new();
+        cards.insert("1".to_string(), create_test_card("1"));
+        storage.save_cards(&cards).unwrap();
+        storage
+            .lock()
+            .unwrap()
+            .execute("INSERT INTO cards (id, data) VALUES ('bad', '{oops')", [])
+            .unwrap();
+
+        let loaded = storage.load_cards().unwrap();
+        assert_eq!(loaded.len(), 1);
+
+        let reports = storage.take_recovery_reports();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].recovered_items, 1);
+        assert_eq!(reports[0].lost_items, 1);
+    }
+
+    /// Tests that only the entries reviewed at or after the given time are loaded.
+    #[test]
+    fn test_load_review_log_since() {
+        let (storage, _temp_dir) = create_test_storage();
+        let since = Utc::now() - Duration::hours(1);
+
+        let mut yesterday = create_test_review_log("a");
+        yesterday.reviewed_at = since - Duration::days(1);
+        let mut on_the_second = create_test_review_log("b");
+        on_the_second.reviewed_at = since;
+        let mut later = create_test_review_log("c");
+        later.reviewed_at = since + Duration::milliseconds(1500);
+        for entry in [&yesterday, &on_the_second, &later] {
+            storage.append_review_log(entry).unwrap();
+        }
+
+        let log = storage.load_review_log_since(since).unwrap();
+        assert_eq!(log.iter().map(|entry| entry.card_id.as_str()).collect::<Vec<_>>(), ["b", "c"]);
+    }
+
+    /// Ensures a database from before review times were indexed gets them filled in on open.
+    #[test]
+    fn test_upgrade_fills_in_review_times() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut entry = create_test_review_log("a");
+        entry.reviewed_at = Utc::now() - Duration::days(2);
+        {
+            let conn = Connection::open(temp_dir.path().join("cards.db")).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE review_log (
+                     seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                     id TEXT NOT NULL,
+                     card_id TEXT NOT NULL,
+                     data TEXT NOT NULL
+                 );
+                 PRAGMA user_version = 2;",
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO review_log (id, card_id, data) VALUES (?1, ?2, ?3)",
+                params![entry.id, entry.card_id, serde_json::to_string(&entry).unwrap()],
+            )
+            .unwrap();
+        }
+
+        let storage = SqliteStorage::in_dir(temp_dir.path()).unwrap();
+        assert_eq!(
+            storage.load_review_log_since(entry.reviewed_at - Duration::days(1)).unwrap().len(),
+            1
+        );
+        assert!(storage.load_review_log_since(Utc::now() - Duration::days(1)).unwrap().is_empty());
+    }
+}
@@ -1,28 +1,93 @@
-use crate::models::{AppSettings, Card};
+use crate::models::{AppSettings, Card, DeckCollection, ReviewLog, StorageRecoveryReport};
+use chrono::{DateTime, Utc};
+use serde::de::{DeserializeSeed, Deserializer, MapAccess, Visitor};
+use serde::Serialize;
+use serde_json::Value;
 use std::collections::HashMap;
+use std::fmt;
 use std::fs::{File, OpenOptions};
-use std::io::{BufReader, BufWriter};
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 use tauri::{AppHandle, Manager};
 
-pub struct Storage {
+/// Persistence backend used by `CardService`
+pub trait Storage: Send + Sync {
+    fn load_cards(&self) -> Result<HashMap<String, Card>, Box<dyn std::error::Error>>;
+
+    /// Replace the stored collection with `cards`
+    fn save_cards(&self, cards: &HashMap<String, Card>) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Persist the cards with the given ids after they were created or changed.
+    /// `cards` is the full collection, for backends that can only write everything at once.
+    fn upsert_cards(&self, cards: &HashMap<String, Card>, _ids: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+        self.save_cards(cards)
+    }
+
+    /// Remove the cards with the given ids. `cards` is the collection left after the removal.
+    fn delete_cards(&self, cards: &HashMap<String, Card>, _ids: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+        self.save_cards(cards)
+    }
+
+    fn load_settings(&self) -> Result<AppSettings, Box<dyn std::error::Error>>;
+    fn save_settings(&self, settings: &AppSettings) -> Result<(), Box<dyn std::error::Error>>;
+
+    fn load_decks(&self) -> Result<DeckCollection, Box<dyn std::error::Error>>;
+    fn save_decks(&self, decks: &DeckCollection) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Append a single entry to the review log
+    fn append_review_log(&self, entry: &ReviewLog) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Load the full review log in the order entries were recorded
+    fn load_review_log(&self) -> Result<Vec<ReviewLog>, Box<dyn std::error::Error>>;
+
+    /// Load the review log entries recorded at or after `since`, e.g. today's reviews
+    fn load_review_log_since(&self, since: DateTime<Utc>) -> Result<Vec<ReviewLog>, Box<dyn std::error::Error>> {
+        let mut entries = self.load_review_log()?;
+        entries.retain(|entry| entry.reviewed_at >= since);
+        Ok(entries)
+    }
+
+    /// Remove a single entry from the review log, e.g. when a review is undone
+    fn remove_review_log_entry(&self, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut entries = self.load_review_log()?;
+        entries.retain(|entry| entry.id != id);
+        self.replace_review_log(&entries)
+    }
+
+    /// Replace the whole review log, e.g. when restoring a backup
+    fn replace_review_log(&self, entries: &[ReviewLog]) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Directory that holds backups of this storage's data
+    fn backup_dir(&self) -> PathBuf;
+
+    /// Take the reports of data that had to be recovered since the last call
+    fn take_recovery_reports(&self) -> Vec<StorageRecoveryReport> {
+        Vec::new()
+    }
+}
+
+/// Stores cards, settings and decks as pretty-printed JSON files and the review log as JSON lines
+pub struct JsonStorage {
     data_file: PathBuf,
     settings_file: PathBuf,
+    decks_file: PathBuf,
+    review_log_file: PathBuf,
+    // Set when a file failed to parse and could not be quarantined, so a later save cannot destroy it
+    cards_unreadable: AtomicBool,
+    settings_unreadable: AtomicBool,
+    decks_unreadable: AtomicBool,
+    recovery_reports: Mutex<Vec<StorageRecoveryReport>>,
 }
 
-impl Storage {
-    pub fn new(app_handle: AppHandle) -> Result<Self, Box<dyn std::error::Error>> {
-        // Use Tauri's app data directory (cross-platform)
-        let data_dir = app_handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| format!("Failed to get app data directory: {}", e))?;
-
-        std::fs::create_dir_all(&data_dir)?;
+impl JsonStorage {
+    /// Storage using the default file names inside `data_dir`
+    pub fn in_dir(data_dir: &Path) -> Self {
         let data_file = data_dir.join("cards.json");
         let settings_file = data_dir.join("settings.json");
-
-        Ok(Storage { data_file, settings_file })
+        let review_log_file = data_dir.join("review_log.jsonl");
+        Self::from_paths(data_file, settings_file, review_log_file)
     }
 
     // Constructor for testing
@@ -30,86 +95,407 @@ impl Storage {
     pub fn new_with_path(data_file: PathBuf) -> Self {
         let mut settings_file = data_file.clone();
         settings_file.set_file_name("settings.json");
-        Storage { data_file, settings_file }
+        let mut review_log_file = data_file.clone();
+        review_log_file.set_file_name("review_log.jsonl");
+        Self::from_paths(data_file, settings_file, review_log_file)
+    }
+
+    fn from_paths(data_file: PathBuf, settings_file: PathBuf, review_log_file: PathBuf) -> Self {
+        JsonStorage {
+            decks_file: data_file.with_file_name("decks.json"),
+            data_file,
+            settings_file,
+            review_log_file,
+            cards_unreadable: AtomicBool::new(false),
+            settings_unreadable: AtomicBool::new(false),
+            decks_unreadable: AtomicBool::new(false),
+            recovery_reports: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Copy a corrupted file to a timestamped `.corrupt` sibling so it can be inspected or restored later.
+    /// Overwriting the original is only allowed once that copy exists.
+    fn quarantine(&self, path: &Path, unreadable: &AtomicBool) -> Option<PathBuf> {
+        let mut quarantine_name = path.as_os_str().to_owned();
+        quarantine_name.push(format!(".{}.corrupt", Utc::now().format("%Y%m%d-%H%M%S")));
+        let quarantine_path = PathBuf::from(quarantine_name);
+
+        match std::fs::copy(path, &quarantine_path) {
+            Ok(_) => {
+                unreadable.store(false, Ordering::SeqCst);
+                Some(quarantine_path)
+            }
+            Err(e) => {
+                log::error!("Failed to quarantine {}: {}", path.display(), e);
+                unreadable.store(true, Ordering::SeqCst);
+                None
+            }
+        }
+    }
+
+    fn record_recovery(
+        &self,
+        path: &Path,
+        error: serde_json::Error,
+        quarantined_to: Option<PathBuf>,
+        recovered_items: usize,
+        lost_items: usize,
+    ) {
+        let report = StorageRecoveryReport {
+            file: path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default(),
+            error: error.to_string(),
+            quarantined_to: quarantined_to.map(|p| p.display().to_string()),
+            recovered_items,
+            lost_items,
+            detected_at: Utc::now(),
+        };
+        log::warn!(
+            "Recovered {} item(s) from {}, {} could not be read",
+            report.recovered_items,
+            report.file,
+            report.lost_items
+        );
+
+        if let Ok(mut reports) = self.recovery_reports.lock() {
+            reports.push(report);
+        }
+    }
+
+    fn refuse_overwrite(path: &Path) -> Box<dyn std::error::Error> {
+        format!(
+            "Refusing to overwrite {}: it could not be parsed when it was loaded",
+            path.display()
+        )
+        .into()
+    }
+
+    /// Whether any of the data files exist
+    pub fn has_data(&self) -> bool {
+        [&self.data_file, &self.settings_file, &self.decks_file, &self.review_log_file]
+            .iter()
+            .any(|path| path.exists())
+    }
+
+    /// Move the data files out of the way once their contents live in another backend
+    pub fn mark_migrated(&self) {
+        for path in [&self.data_file, &self.settings_file, &self.decks_file, &self.review_log_file] {
+            if !path.exists() {
+                continue;
+            }
+            let mut migrated_name = path.as_os_str().to_owned();
+            migrated_name.push(".migrated");
+            if let Err(e) = std::fs::rename(path, PathBuf::from(migrated_name)) {
+                log::warn!("Failed to rename migrated file {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+impl Storage for JsonStorage {
+    fn load_cards(&self) -> Result<HashMap<String, Card>, Box<dyn std::error::Error>> {
+        if !self.data_file.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let contents = std::fs::read(&self.data_file)?;
+        match serde_json::from_slice(&contents) {
+            Ok(cards) => {
+                self.cards_unreadable.store(false, Ordering::SeqCst);
+                Ok(cards)
+            }
+            Err(e) => {
+                log::error!("Failed to parse {}: {}", self.data_file.display(), e);
+                let (cards, lost_items) = salvage_cards(&contents);
+                let quarantined_to = self.quarantine(&self.data_file, &self.cards_unreadable);
+                self.record_recovery(&self.data_file, e, quarantined_to, cards.len(), lost_items);
+                Ok(cards)
+            }
+        }
+    }
+
+    fn save_cards(&self, cards: &HashMap<String, Card>) -> Result<(), Box<dyn std::error::Error>> {
+        if self.cards_unreadable.load(Ordering::SeqCst) {
+            return Err(Self::refuse_overwrite(&self.data_file));
+        }
+        write_json_atomic(&self.data_file, cards)
+    }
+
+    fn load_settings(&self) -> Result<AppSettings, Box<dyn std::error::Error>> {
+        if !self.settings_file.exists() {
+            return Ok(AppSettings::default());
+        }
+
+        let contents = std::fs::read(&self.settings_file)?;
+        match serde_json::from_slice(&contents) {
+            Ok(settings) => {
+                self.settings_unreadable.store(false, Ordering::SeqCst);
+                Ok(settings)
+            }
+            Err(e) => {
+                log::error!("Failed to parse {}: {}", self.settings_file.display(), e);
+                let (settings, recovered_items, lost_items) = salvage_settings(&contents);
+                let quarantined_to = self.quarantine(&self.settings_file, &self.settings_unreadable);
+                self.record_recovery(&self.settings_file, e, quarantined_to, recovered_items, lost_items);
+                Ok(settings)
+            }
+        }
     }
 
-    pub fn load_cards(&self) -> Result<HashMap<String, Card>, Box<dyn std::error::Error>> {
-        if self.data_file.exists() {
-            let file = File::open(&self.data_file)?;
-            let reader = BufReader::new(file);
-            let cards: HashMap<String, Card> = serde_json::from_reader(reader).unwrap_or_default();
-            Ok(cards)
-        } else {
-            Ok(HashMap::new())
+    fn save_settings(&self, settings: &AppSettings) -> Result<(), Box<dyn std::error::Error>> {
+        if self.settings_unreadable.load(Ordering::SeqCst) {
+            return Err(Self::refuse_overwrite(&self.settings_file));
         }
+        write_json_atomic(&self.settings_file, settings)
     }
 
-    pub fn save_cards(&self, cards: &HashMap<String, Card>) -> Result<(), Box<dyn std::error::Error>> {
-        let file = OpenOptions::new().write(true).create(true).truncate(true).open(&self.data_file)?;
+    fn load_decks(&self) -> Result<DeckCollection, Box<dyn std::error::Error>> {
+        if !self.decks_file.exists() {
+            return Ok(DeckCollection::default());
+        }
 
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, cards)?;
+        let contents = std::fs::read(&self.decks_file)?;
+        match serde_json::from_slice(&contents) {
+            Ok(decks) => {
+                self.decks_unreadable.store(false, Ordering::SeqCst);
+                Ok(decks)
+            }
+            Err(e) => {
+                // Cards keep their deck ids, so only the deck names and presets are lost
+                log::error!("Failed to parse {}: {}", self.decks_file.display(), e);
+                let quarantined_to = self.quarantine(&self.decks_file, &self.decks_unreadable);
+                self.record_recovery(&self.decks_file, e, quarantined_to, 0, 1);
+                Ok(DeckCollection::default())
+            }
+        }
+    }
+
+    fn save_decks(&self, decks: &DeckCollection) -> Result<(), Box<dyn std::error::Error>> {
+        if self.decks_unreadable.load(Ordering::SeqCst) {
+            return Err(Self::refuse_overwrite(&self.decks_file));
+        }
+        write_json_atomic(&self.decks_file, decks)
+    }
+
+    // One JSON object per line
+    fn append_review_log(&self, entry: &ReviewLog) -> Result<(), Box<dyn std::error::Error>> {
+        let file = OpenOptions::new().create(true).append(true).open(&self.review_log_file)?;
+
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, entry)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        writer.get_ref().sync_data()?;
         Ok(())
     }
 
-    pub fn load_settings(&self) -> Result<AppSettings, Box<dyn std::error::Error>> {
-        if self.settings_file.exists() {
-            let file = File::open(&self.settings_file)?;
-            let reader = BufReader::new(file);
-            let settings: AppSettings = serde_json::from_reader(reader).unwrap_or_default();
-            Ok(settings)
-        } else {
-            Ok(AppSettings::default())
+    fn load_review_log(&self) -> Result<Vec<ReviewLog>, Box<dyn std::error::Error>> {
+        if !self.review_log_file.exists() {
+            return Ok(Vec::new());
+        }
+
+        let file = File::open(&self.review_log_file)?;
+        let reader = BufReader::new(file);
+        let mut entries = Vec::new();
+
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // A torn final line from an interrupted append must not hide the rest of the history
+            match serde_json::from_str::<ReviewLog>(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => log::warn!("Skipping unreadable review log entry: {}", e),
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn replace_review_log(&self, entries: &[ReviewLog]) -> Result<(), Box<dyn std::error::Error>> {
+        write_file_atomic(&self.review_log_file, |writer| {
+            for entry in entries {
+                serde_json::to_writer(&mut *writer, entry)?;
+                writer.write_all(b"\n")?;
+            }
+            Ok(())
+        })
+    }
+
+    fn backup_dir(&self) -> PathBuf {
+        let data_dir = self.data_file.parent().map(Path::to_path_buf).unwrap_or_default();
+        data_dir.join("backups")
+    }
+
+    fn take_recovery_reports(&self) -> Vec<StorageRecoveryReport> {
+        self.recovery_reports
+            .lock()
+            .map(|mut reports| std::mem::take(&mut *reports))
+            .unwrap_or_default()
+    }
+}
+
+/// Resolve (and create) Tauri's app data directory, which is cross-platform
+pub fn app_data_dir(app_handle: &AppHandle) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+
+    std::fs::create_dir_all(&data_dir)?;
+    Ok(data_dir)
+}
+
+/// Recover every card that still parses from a damaged cards file.
+/// Returns the salvaged cards and the number of entries that had to be dropped.
+fn salvage_cards(contents: &[u8]) -> (HashMap<String, Card>, usize) {
+    let mut entries = Vec::new();
+    let mut deserializer = serde_json::Deserializer::from_slice(contents);
+    // Stops at the first syntax error (e.g. a truncated file) but keeps every entry read before it
+    let _ = SalvageEntries(&mut entries).deserialize(&mut deserializer);
+
+    let mut cards = HashMap::new();
+    let mut lost_items = 0;
+    for (id, value) in entries {
+        match serde_json::from_value::<Card>(value) {
+            Ok(card) => {
+                cards.insert(id, card);
+            }
+            Err(_) => lost_items += 1,
+        }
+    }
+
+    (cards, lost_items)
+}
+
+/// Keep every settings field that is still valid, falling back to defaults for the rest.
+/// Returns the settings with the number of fields kept and dropped.
+pub(crate) fn salvage_settings(contents: &[u8]) -> (AppSettings, usize, usize) {
+    let mut entries = Vec::new();
+    let mut deserializer = serde_json::Deserializer::from_slice(contents);
+    let _ = SalvageEntries(&mut entries).deserialize(&mut deserializer);
+
+    let mut settings = AppSettings::default();
+    let mut recovered_items = 0;
+    let mut lost_items = 0;
+    for (key, value) in entries {
+        let mut candidate = match serde_json::to_value(&settings) {
+            Ok(candidate) => candidate,
+            Err(_) => break,
+        };
+        candidate[key.as_str()] = value;
+        match serde_json::from_value::<AppSettings>(candidate) {
+            Ok(updated) => {
+                settings = updated;
+                recovered_items += 1;
+            }
+            Err(_) => lost_items += 1,
         }
     }
 
-    pub fn save_settings(&self, settings: &AppSettings) -> Result<(), Box<dyn std::error::Error>> {
-        let file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&self.settings_file)?;
+    (settings, recovered_items, lost_items)
+}
+
+/// Collects the top-level entries of a JSON object one at a time, so entries read before
+/// a syntax error are still available to the caller
+struct SalvageEntries<'a>(&'a mut Vec<(String, Value)>);
+
+impl<'de> DeserializeSeed<'de> for SalvageEntries<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
 
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, settings)?;
+impl<'de> Visitor<'de> for SalvageEntries<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some((key, value)) = map.next_entry::<String, Value>()? {
+            self.0.push((key, value));
+        }
         Ok(())
     }
 }
 
+/// Write `value` as JSON to a temporary sibling file, fsync it, then rename it over `path`,
+/// so a crash or full disk leaves either the old file or the new one, never a partial write
+pub(crate) fn write_json_atomic<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), Box<dyn std::error::Error>> {
+    write_file_atomic(path, |writer| Ok(serde_json::to_writer_pretty(writer, value)?))
+}
+
+fn write_file_atomic<F>(path: &Path, write: F) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<(), Box<dyn std::error::Error>>,
+{
+    let mut temp_name = path.as_os_str().to_owned();
+    temp_name.push(".tmp");
+    let temp_path = PathBuf::from(temp_name);
+
+    if let Err(e) = write_synced(&temp_path, write) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    std::fs::rename(&temp_path, path)?;
+
+    // Make the rename itself durable
+    #[cfg(unix)]
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        File::open(parent)?.sync_all()?;
+    }
+
+    Ok(())
+}
+
+fn write_synced<F>(path: &Path, write: F) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<(), Box<dyn std::error::Error>>,
+{
+    let file = OpenOptions::new().write(true).create(true).truncate(true).open(path)?;
+    let mut writer = BufWriter::new(file);
+    write(&mut writer)?;
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::Card;
-    use chrono::Utc;
-    use std::collections::HashMap;
+    use crate::models::{Card, ReviewDifficulty};
+    use std::collections::{BTreeSet, HashMap};
     use tempfile::TempDir;
 
     fn create_test_card(id: &str) -> Card {
         Card {
-            id: id.to_string(),
-            front: format!("Question {}", id),
-            back: format!("Answer {}", id),
-            tag: Some("Test".to_string()),
-            created_at: Utc::now(),
-            last_reviewed: None,
-            next_review: Utc::now(),
-            interval: 0,
-            ease_factor: 2.5,
-            review_count: 0,
-            correct_count: 0,
-            leitner_box: 0,
-            exponential_factor: 1.0,
+            tags: BTreeSet::from(["Test".to_string()]),
+            ..Card::new_for_test(id)
         }
     }
 
-    fn create_test_storage() -> (Storage, TempDir) {
+    fn create_test_storage() -> (JsonStorage, TempDir) {
         let temp_dir = TempDir::new().unwrap();
         let data_file = temp_dir.path().join("cards.json");
         let settings_file = temp_dir.path().join("settings.json");
-        let storage = Storage { data_file, settings_file };
+        let review_log_file = temp_dir.path().join("review_log.jsonl");
+        let storage = JsonStorage::from_paths(data_file, settings_file, review_log_file);
         (storage, temp_dir)
     }
 
+    fn create_test_review_log(card_id: &str, difficulty: ReviewDifficulty) -> ReviewLog {
+        ReviewLog {
+            difficulty,
+            previous_interval: 1,
+            new_interval: 6,
+            ..ReviewLog::new_for_test(card_id)
+        }
+    }
+
     /// Ensures loading cards from a non-existent file returns an empty collection
     /// instead of crashing, providing graceful initialization for new users.
     #[test]
@@ -146,7 +532,7 @@ mod tests {
         assert_eq!(loaded_card1.id, "1");
         assert_eq!(loaded_card1.front, "Question 1");
         assert_eq!(loaded_card1.back, "Answer 1");
-        assert_eq!(loaded_card1.tag, Some("Test".to_string()));
+        assert_eq!(loaded_card1.tags, BTreeSet::from(["Test".to_string()]));
     }
 
     /// Tests saving and loading empty card collections to ensure
@@ -187,8 +573,8 @@ mod tests {
         assert!(loaded_cards.contains_key("3"));
     }
 
-    /// Tests recovery from corrupted JSON files by returning empty data
-    /// instead of crashing, protecting users from losing access to the app.
+    /// Tests recovery from corrupted JSON files: the app starts with an empty deck,
+    /// the damaged file is preserved in a `.corrupt` copy and the problem is reported.
     #[test]
     fn test_corrupted_file_handling() {
         let (storage, temp_dir) = create_test_storage();
@@ -200,6 +586,141 @@ mod tests {
         // Should return empty HashMap instead of crashing
         let cards = storage.load_cards().unwrap();
         assert!(cards.is_empty());
+
+        let reports = storage.take_recovery_reports();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].file, "cards.json");
+        assert_eq!(reports[0].recovered_items, 0);
+
+        let quarantine_path = PathBuf::from(reports[0].quarantined_to.clone().unwrap());
+        assert!(quarantine_path.file_name().unwrap().to_string_lossy().ends_with(".corrupt"));
+        assert_eq!(std::fs::read_to_string(quarantine_path).unwrap(), "invalid json");
+
+        // Reports are handed out once
+        assert!(storage.take_recovery_reports().is_empty());
+    }
+
+    /// Ensures cards that still parse are salvaged when other entries in the file are damaged.
+    #[test]
+    fn test_corrupted_file_salvages_valid_cards() {
+        let (storage, temp_dir) = create_test_storage();
+
+        let mut cards = HashMap::new();
+        cards.insert("good".to_string(), create_test_card("good"));
+        let mut contents: serde_json::Map<String, Value> = serde_json::from_value(serde_json::to_value(&cards).unwrap()).unwrap();
+        contents.insert("bad".to_string(), serde_json::json!({"id": "bad", "front": 42}));
+        std::fs::write(temp_dir.path().join("cards.json"), serde_json::to_string(&contents).unwrap()).unwrap();
+
+        let loaded_cards = storage.load_cards().unwrap();
+        assert_eq!(loaded_cards.len(), 1);
+        assert!(loaded_cards.contains_key("good"));
+
+        let reports = storage.take_recovery_reports();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].recovered_items, 1);
+        assert_eq!(reports[0].lost_items, 1);
+    }
+
+    /// Tests salvage of a file cut off mid-write, keeping every card written before the cut.
+    #[test]
+    fn test_truncated_file_salvages_leading_cards() {
+        let (storage, temp_dir) = create_test_storage();
+
+        let mut cards = HashMap::new();
+        cards.insert("1".to_string(), create_test_card("1"));
+        cards.insert("2".to_string(), create_test_card("2"));
+        storage.save_cards(&cards).unwrap();
+
+        let data_file_path = temp_dir.path().join("cards.json");
+        let contents = std::fs::read_to_string(&data_file_path).unwrap();
+        let second_entry = contents.rfind("\"Question ").unwrap();
+        std::fs::write(&data_file_path, &contents[..second_entry]).unwrap();
+
+        let loaded_cards = storage.load_cards().unwrap();
+        assert_eq!(loaded_cards.len(), 1);
+
+        let reports = storage.take_recovery_reports();
+        assert_eq!(reports[0].recovered_items, 1);
+    }
+
+    /// Ensures that once the damaged file is quarantined, saving the salvaged deck is allowed.
+    #[test]
+    fn test_save_after_quarantine() {
+        let (storage, temp_dir) = create_test_storage();
+
+        let data_file_path = temp_dir.path().join("cards.json");
+        std::fs::write(&data_file_path, "{\"truncated\": ").unwrap();
+        storage.load_cards().unwrap();
+        let reports = storage.take_recovery_reports();
+
+        let mut cards = HashMap::new();
+        cards.insert("1".to_string(), create_test_card("1"));
+        storage.save_cards(&cards).unwrap();
+
+        assert_eq!(storage.load_cards().unwrap().len(), 1);
+        let quarantine_path = reports[0].quarantined_to.clone().unwrap();
+        assert_eq!(std::fs::read_to_string(quarantine_path).unwrap(), "{\"truncated\": ");
+    }
+
+    /// Ensures a damaged file that could not be quarantined is never overwritten,
+    /// so the next save cannot destroy data that might still be recoverable.
+    #[test]
+    fn test_refuses_to_overwrite_unquarantined_files() {
+        let (storage, temp_dir) = create_test_storage();
+        std::fs::write(temp_dir.path().join("cards.json"), "not json").unwrap();
+        std::fs::write(temp_dir.path().join("settings.json"), "not json").unwrap();
+
+        // Simulate a failed quarantine copy
+        storage.cards_unreadable.store(true, Ordering::SeqCst);
+        storage.settings_unreadable.store(true, Ordering::SeqCst);
+
+        let result = storage.save_cards(&HashMap::new());
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("Refusing to overwrite"));
+        assert!(storage.save_settings(&AppSettings::default()).is_err());
+
+        // Original bytes are untouched
+        assert_eq!(std::fs::read_to_string(temp_dir.path().join("cards.json")).unwrap(), "not json");
+        assert_eq!(std::fs::read_to_string(temp_dir.path().join("settings.json")).unwrap(), "not json");
+    }
+
+    /// Verifies that saving goes through a temporary file that is renamed into place,
+    /// leaving no stray temporary files and replacing any left over from a crash.
+    #[test]
+    fn test_atomic_save_leaves_no_temp_file() {
+        let (storage, temp_dir) = create_test_storage();
+        let temp_file = temp_dir.path().join("cards.json.tmp");
+
+        // Leftover from a write that was interrupted before the rename
+        std::fs::write(&temp_file, "partial").unwrap();
+
+        let mut cards = HashMap::new();
+        cards.insert("1".to_string(), create_test_card("1"));
+        storage.save_cards(&cards).unwrap();
+        storage.save_settings(&AppSettings::default()).unwrap();
+
+        assert!(!temp_file.exists());
+        assert!(!temp_dir.path().join("settings.json.tmp").exists());
+        assert_eq!(storage.load_cards().unwrap().len(), 1);
+    }
+
+    /// Tests that a failed write leaves the previous file intact.
+    #[test]
+    fn test_failed_save_keeps_previous_file() {
+        let (storage, temp_dir) = create_test_storage();
+
+        let mut cards = HashMap::new();
+        cards.insert("1".to_string(), create_test_card("1"));
+        storage.save_cards(&cards).unwrap();
+
+        // A directory in place of the temp file makes the write fail before the rename
+        std::fs::create_dir(temp_dir.path().join("cards.json.tmp")).unwrap();
+        cards.insert("2".to_string(), create_test_card("2"));
+        assert!(storage.save_cards(&cards).is_err());
+
+        let loaded_cards = storage.load_cards().unwrap();
+        assert_eq!(loaded_cards.len(), 1);
+        assert!(loaded_cards.contains_key("1"));
     }
 
     /// Verifies that saved data actually persists to disk as valid JSON files,
@@ -315,6 +836,89 @@ mod tests {
         assert_eq!(settings.algorithm, AppSettings::default().algorithm);
         assert_eq!(settings.leitner_intervals, AppSettings::default().leitner_intervals);
         assert_eq!(settings.exponential_base, AppSettings::default().exponential_base);
+
+        let reports = storage.take_recovery_reports();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].file, "settings.json");
+        assert!(reports[0].quarantined_to.is_some());
+    }
+
+    /// Verifies that valid settings survive when another field in the file is damaged.
+    #[test]
+    fn test_corrupted_settings_salvages_valid_fields() {
+        let (storage, temp_dir) = create_test_storage();
+
+        let settings_file_path = temp_dir.path().join("settings.json");
+        std::fs::write(
+            &settings_file_path,
+            r#"{"algorithm": "Leitner", "leitner_intervals": "oops", "exponential_base": 3.0}"#,
+        )
+        .unwrap();
+
+        let settings = storage.load_settings().unwrap();
+        assert_eq!(settings.algorithm, crate::models::SpacedRepetitionAlgorithm::Leitner);
+        assert_eq!(settings.leitner_intervals, AppSettings::default().leitner_intervals);
+        assert_eq!(settings.exponential_base, 3.0);
+
+        let reports = storage.take_recovery_reports();
+        assert_eq!(reports[0].recovered_items, 2);
+        assert_eq!(reports[0].lost_items, 1);
+    }
+
+    // Review log persistence tests
+    /// Ensures a missing review log is treated as an empty history
+    /// so fresh installs and upgrades from older versions start cleanly.
+    #[test]
+    fn test_load_review_log_missing_file() {
+        let (storage, _temp_dir) = create_test_storage();
+        let entries = storage.load_review_log().unwrap();
+        assert!(entries.is_empty());
+    }
+
+    /// Verifies review log entries are appended in order and read back intact,
+    /// preserving the full review history next to the card data.
+    #[test]
+    fn test_append_and_load_review_log() {
+        let (storage, _temp_dir) = create_test_storage();
+
+        storage
+            .append_review_log(&create_test_review_log("1", ReviewDifficulty::Good))
+            .unwrap();
+        storage
+            .append_review_log(&create_test_review_log("2", ReviewDifficulty::Again))
+            .unwrap();
+
+        let entries = storage.load_review_log().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].card_id, "1");
+        assert!(matches!(entries[0].difficulty, ReviewDifficulty::Good));
+        assert_eq!(entries[1].card_id, "2");
+        assert!(matches!(entries[1].difficulty, ReviewDifficulty::Again));
+        assert_eq!(entries[1].new_interval, 6);
+    }
+
+    /// Tests that a torn or corrupted line only loses that entry,
+    /// keeping the rest of the review history readable.
+    #[test]
+    fn test_review_log_skips_corrupted_lines() {
+        let (storage, _temp_dir) = create_test_storage();
+
+        storage
+            .append_review_log(&create_test_review_log("1", ReviewDifficulty::Good))
+            .unwrap();
+
+        // Simulate a partially written line from an interrupted append
+        let mut file = OpenOptions::new().append(true).open(&storage.review_log_file).unwrap();
+        file.write_all(b"{\"id\":\"broken\n").unwrap();
+
+        storage
+            .append_review_log(&create_test_review_log("2", ReviewDifficulty::Easy))
+            .unwrap();
+
+        let entries = storage.load_review_log().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].card_id, "1");
+        assert_eq!(entries[1].card_id, "2");
     }
 
     // Tests for Storage::new() behavior and directory creation
@@ -325,13 +929,16 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let data_file = temp_dir.path().join("test_cards.json");
 
-        let storage = Storage::new_with_path(data_file.clone());
+        let storage = JsonStorage::new_with_path(data_file.clone());
 
         // Verify the file paths are set correctly
         assert_eq!(storage.data_file, data_file);
 
         let expected_settings_file = temp_dir.path().join("settings.json");
         assert_eq!(storage.settings_file, expected_settings_file);
+
+        let expected_review_log_file = temp_dir.path().join("review_log.jsonl");
+        assert_eq!(storage.review_log_file, expected_review_log_file);
     }
 
     /// Verifies correct file path derivation and directory structure,
@@ -341,7 +948,7 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let cards_file = temp_dir.path().join("custom_cards.json");
 
-        let storage = Storage::new_with_path(cards_file.clone());
+        let storage = JsonStorage::new_with_path(cards_file.clone());
 
         // Test that file paths are correctly derived
         assert_eq!(storage.data_file.file_name().unwrap(), "custom_cards.json");
@@ -358,7 +965,7 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let data_file = temp_dir.path().join("subdir").join("cards.json");
 
-        let storage = Storage::new_with_path(data_file.clone());
+        let storage = JsonStorage::new_with_path(data_file.clone());
 
         // Verify directory structure is preserved
         let expected_settings = temp_dir.path().join("subdir").join("settings.json");
@@ -385,8 +992,8 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let data_file = temp_dir.path().join("concurrent_cards.json");
 
-        let storage1 = Storage::new_with_path(data_file.clone());
-        let storage2 = Storage::new_with_path(data_file.clone());
+        let storage1 = JsonStorage::new_with_path(data_file.clone());
+        let storage2 = JsonStorage::new_with_path(data_file.clone());
 
         // Both storages should be able to access the same files
         let mut cards1 = HashMap::new();
@@ -419,7 +1026,7 @@ mod tests {
         // Create a file first
         std::fs::write(&readonly_file, "{}").unwrap();
 
-        let storage = Storage::new_with_path(readonly_file);
+        let storage = JsonStorage::new_with_path(readonly_file);
 
         // Test graceful handling of write operations to existing files
         let cards = HashMap::new();
@@ -438,17 +1045,17 @@ mod tests {
 
         // Test with file that has no extension
         let no_ext_file = temp_dir.path().join("cards_no_extension");
-        let storage1 = Storage::new_with_path(no_ext_file);
+        let storage1 = JsonStorage::new_with_path(no_ext_file);
         assert_eq!(storage1.settings_file.file_name().unwrap(), "settings.json");
 
         // Test with file that has multiple extensions
         let multi_ext_file = temp_dir.path().join("cards.backup.json");
-        let storage2 = Storage::new_with_path(multi_ext_file);
+        let storage2 = JsonStorage::new_with_path(multi_ext_file);
         assert_eq!(storage2.settings_file.file_name().unwrap(), "settings.json");
 
         // Test with deeply nested path
         let deep_path = temp_dir.path().join("a").join("b").join("c").join("deep.json");
-        let storage3 = Storage::new_with_path(deep_path.clone());
+        let storage3 = JsonStorage::new_with_path(deep_path.clone());
 
         // Create the directory structure first (simulating what Storage::new() does)
         std::fs::create_dir_all(deep_path.parent().unwrap()).unwrap();
@@ -474,10 +1081,7 @@ mod tests {
         let settings_file = app_data_dir.join("settings.json");
 
         // Manually create storage with the same logic as Storage::new()
-        let storage = Storage {
-            data_file: data_file.clone(),
-            settings_file: settings_file.clone(),
-        };
+        let storage = JsonStorage::from_paths(data_file.clone(), settings_file.clone(), app_data_dir.join("review_log.jsonl"));
 
         // Test that the directory exists (simulating successful Storage::new())
         assert!(app_data_dir.exists());
@@ -519,10 +1123,7 @@ mod tests {
         assert_eq!(cards_path.parent().unwrap(), settings_path.parent().unwrap());
 
         // Test that Storage created with these paths works correctly
-        let storage = Storage {
-            data_file: cards_path.clone(),
-            settings_file: settings_path.clone(),
-        };
+        let storage = JsonStorage::from_paths(cards_path.clone(), settings_path.clone(), base_dir.join("review_log.jsonl"));
 
         // Should be able to perform all normal operations
         let mut test_cards = HashMap::new();
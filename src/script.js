@@ -11,9 +11,8 @@ const CONFIG = {
 
 // Application state
 let currentSection = 'review';
-let currentReviewCards = [];
-let currentCardIndex = 0;
-let currentCard = null;
+let currentCard = null; // Card shown in the review session
+let cardShownAt = 0; // When the current card was shown, for its answer time
 let lastDeletedCard = null; // Store last deleted card for undo
 let lastDeletedCards = []; // Store multiple deleted cards for bulk undo
 
@@ -37,6 +36,8 @@ let notificationTimeouts = {
 let allCards = []; // Cache of all cards for filtering
 let selectedCards = new Set(); // Selected card IDs for bulk operations
 let tags = []; // Available tags
+let decks = []; // Available decks, sorted by name
+let presets = []; // Deck option presets
 
 document.addEventListener('DOMContentLoaded', () => {
     initializeApp();
@@ -49,12 +50,33 @@ async function initializeApp() {
     setupNavigation();
     setupEventListeners();
     await loadReviewStats();
+    await loadDecks();
     await loadCards();
+    await reportStorageRecovery();
 
     // Always start on the review section
     showSection('review');
 }
 
+// Tell the user if any data file was corrupted and had to be recovered at startup
+async function reportStorageRecovery() {
+    try {
+        const reports = await invoke('get_storage_recovery_reports');
+        if (reports.length === 0) {
+            return;
+        }
+
+        const details = reports.map(report => {
+            const backup = report.quarantined_to ? ` The damaged file was saved to ${report.quarantined_to}.` : '';
+            return `${report.file} was damaged: recovered ${report.recovered_items}, lost ${report.lost_items}.${backup}`;
+        });
+        showError(details.join(' '));
+        await invoke('dismiss_storage_recovery_reports');
+    } catch (error) {
+        console.error('Error checking storage recovery:', error);
+    }
+}
+
 async function setupWindowSize() {
     try {
         // Check if we're running in Tauri (desktop) environment
@@ -178,6 +200,7 @@ function showSection(sectionName) {
         loadTags();
     } else if (sectionName === 'tags') {
         loadTagStats();
+        loadDeckSettings();
     } else if (sectionName === 'stats') {
         loadDetailedStats();
     } else if (sectionName === 'review') {
@@ -191,6 +214,9 @@ function setupEventListeners() {
     // Review section
     document.getElementById('start-review').addEventListener('click', startReview);
     document.getElementById('show-answer-btn').addEventListener('click', showAnswer);
+    document.getElementById('review-undo-btn').addEventListener('click', undoLastRating);
+    document.getElementById('review-bury-btn').addEventListener('click', () => setAsideCurrentReviewCard('bury_card', 'Card buried until tomorrow'));
+    document.getElementById('review-suspend-btn').addEventListener('click', () => setAsideCurrentReviewCard('suspend_card', 'Card suspended'));
     document.getElementById('review-edit-btn').addEventListener('click', editCurrentReviewCard);
     document.getElementById('review-delete-btn').addEventListener('click', deleteCurrentReviewCard);
 
@@ -218,10 +244,23 @@ function setupEventListeners() {
     // Organization features
     document.getElementById('search-input').addEventListener('input', debounce(filterCards, 300));
     document.getElementById('tag-filter').addEventListener('change', filterCards);
+    document.getElementById('deck-filter').addEventListener('change', filterCards);
+    document.getElementById('create-preset-btn').addEventListener('click', createPresetFromSettings);
+    document.getElementById('simulate-btn').addEventListener('click', simulateSchedules);
+    ['forecast-tag', 'forecast-deck', 'forecast-days'].forEach(id => {
+        document.getElementById(id).addEventListener('change', loadForecast);
+    });
     document.getElementById('select-all').addEventListener('change', toggleSelectAll);
     document.getElementById('bulk-actions-btn').addEventListener('click', toggleBulkMode);
     document.getElementById('bulk-delete-btn').addEventListener('click', bulkDeleteCards);
-    document.getElementById('bulk-tag-apply').addEventListener('click', bulkUpdateTag);
+    document.getElementById('bulk-state-apply').addEventListener('click', bulkSetAside);
+    document.getElementById('bulk-reschedule-apply').addEventListener('click', bulkReschedule);
+    document.getElementById('bulk-reschedule-select').addEventListener('change', (e) => {
+        // Forgetting and resetting take no days
+        document.getElementById('bulk-days-input').classList.toggle('hidden', ['forget_cards', 'reset_cards'].includes(e.target.value));
+    });
+    document.getElementById('bulk-tag-apply').addEventListener('click', () => bulkUpdateTag('add'));
+    document.getElementById('bulk-tag-remove').addEventListener('click', () => bulkUpdateTag('remove'));
 
     // Handle tag input/select interaction
     document.getElementById('bulk-tag-select').addEventListener('change', (e) => {
@@ -769,6 +808,7 @@ async function loadAlgorithmSettings() {
                     const currentSettings = await invoke('get_settings');
                     const tempSettings = { ...currentSettings, algorithm: radio.value };
                     updateAlgorithmSpecificSettings(tempSettings);
+                    await showMigrationPreview(tempSettings);
                     // Indicate unsaved changes when algorithm is changed
                     indicateUnsavedChanges();
                 }
@@ -776,8 +816,41 @@ async function loadAlgorithmSettings() {
             radio.setAttribute('data-listener-added', 'true');
         });
 
+        // Show daily limits and learning steps
+        const dailyFields = [
+            ['new-cards-per-day', settings.new_cards_per_day],
+            ['reviews-per-day', settings.reviews_per_day],
+            ['learning-steps', settings.learning_steps.join(' ')],
+            ['relearning-steps', settings.relearning_steps.join(' ')],
+            ['overdue-bonus', Math.round(settings.overdue_bonus * 100)],
+            ['slow-answer-seconds', settings.slow_answer_seconds],
+            ['leech-threshold', settings.leech_threshold],
+            ['leech-action', settings.leech_action]
+        ];
+        for (const [id, value] of dailyFields) {
+            const input = document.getElementById(id);
+            input.value = value;
+            if (!input.hasAttribute('data-change-listener-added')) {
+                input.addEventListener('input', indicateUnsavedChanges);
+                input.setAttribute('data-change-listener-added', 'true');
+            }
+        }
+
+        // Show fuzz and load balancing, the tolerance as a percentage
+        document.getElementById('interval-fuzz').checked = settings.interval_fuzz;
+        document.getElementById('load-balancing').checked = settings.load_balancing;
+        document.getElementById('fuzz-tolerance').value = Math.round(settings.fuzz_tolerance * 100);
+        for (const id of ['interval-fuzz', 'load-balancing', 'fuzz-tolerance']) {
+            const input = document.getElementById(id);
+            if (!input.hasAttribute('data-change-listener-added')) {
+                input.addEventListener('change', indicateUnsavedChanges);
+                input.setAttribute('data-change-listener-added', 'true');
+            }
+        }
+
         // Show algorithm-specific settings
         updateAlgorithmSpecificSettings(settings);
+        await showMigrationPreview(settings);
 
         // Reset save button to clean state when settings are loaded
         const saveButton = document.getElementById('save-algorithm-settings');
@@ -828,6 +901,18 @@ function updateAlgorithmSpecificSettings(settings) {
                 </div>
             `;
             break;
+        case 'FSRS':
+            content = `
+                <h4 class="font-medium mb-3">FSRS Settings</h4>
+                <div class="mb-4">
+                    <label class="block text-sm text-zinc-400 mb-2">Desired Retention</label>
+                    <input type="number" value="${settings.desired_retention}" min="0.7" max="0.99" step="0.01"
+                           id="desired-retention"
+                           class="w-full px-3 py-2 bg-zinc-800 border border-zinc-600 rounded focus:ring-blue-500 focus:border-blue-500">
+                    <p class="text-xs text-zinc-400 mt-1">Probability of remembering a card when it comes due. Higher values mean more reviews (default: 0.9)</p>
+                </div>
+            `;
+            break;
         case 'SM2':
         default:
             content = `
@@ -862,6 +947,13 @@ function addSettingsChangeListeners() {
         exponentialInput.addEventListener('input', indicateUnsavedChanges);
         exponentialInput.setAttribute('data-change-listener-added', 'true');
     }
+
+    // Add listener to desired retention input
+    const retentionInput = document.getElementById('desired-retention');
+    if (retentionInput && !retentionInput.hasAttribute('data-change-listener-added')) {
+        retentionInput.addEventListener('input', indicateUnsavedChanges);
+        retentionInput.setAttribute('data-change-listener-added', 'true');
+    }
 }
 
 function indicateUnsavedChanges() {
@@ -874,6 +966,39 @@ function indicateUnsavedChanges() {
     }
 }
 
+// Tell how saving would reschedule existing cards when the algorithm changes
+async function showMigrationPreview(settings) {
+    const previewEl = document.getElementById('migration-preview');
+    try {
+        const preview = await invoke('preview_settings_update', { settings });
+        if (preview.cards_migrated === 0) {
+            previewEl.classList.add('hidden');
+            return;
+        }
+        previewEl.textContent = `Saving converts the scheduling of ${preview.cards_migrated} reviewed cards: ` +
+            `${preview.due_earlier} will be due earlier and ${preview.due_later} later, ` +
+            `${preview.cards_due_after} due now instead of ${preview.cards_due_before}.`;
+        previewEl.classList.remove('hidden');
+    } catch (error) {
+        console.error('Failed to preview settings update:', error);
+        previewEl.classList.add('hidden');
+    }
+}
+
+// Parse a non-negative integer input, falling back when it is empty or invalid
+function parseIntOrDefault(value, fallback) {
+    const parsed = parseInt(value, 10);
+    return Number.isNaN(parsed) || parsed < 0 ? fallback : parsed;
+}
+
+// Parse learning steps such as "1 10" or "1m, 10m" into whole minutes
+function parseSteps(value) {
+    return value
+        .split(/[\s,]+/)
+        .map(step => parseInt(step, 10))
+        .filter(step => !Number.isNaN(step) && step > 0);
+}
+
 async function saveAlgorithmSettings() {
     const saveButton = document.getElementById('save-algorithm-settings');
     const originalText = saveButton.textContent;
@@ -887,10 +1012,25 @@ async function saveAlgorithmSettings() {
 
         const selectedAlgorithm = document.querySelector('input[name="algorithm"]:checked').value;
 
+        // Start from the saved settings so options not shown on this screen are preserved
+        const currentSettings = await invoke('get_settings');
         let settings = {
+            ...currentSettings,
             algorithm: selectedAlgorithm,
             leitner_intervals: [1, 3, 7, 14, 30], // default
-            exponential_base: 2.0 // default
+            exponential_base: 2.0, // default
+            desired_retention: 0.9, // default
+            new_cards_per_day: parseIntOrDefault(document.getElementById('new-cards-per-day').value, 20),
+            reviews_per_day: parseIntOrDefault(document.getElementById('reviews-per-day').value, 200),
+            learning_steps: parseSteps(document.getElementById('learning-steps').value),
+            relearning_steps: parseSteps(document.getElementById('relearning-steps').value),
+            interval_fuzz: document.getElementById('interval-fuzz').checked,
+            load_balancing: document.getElementById('load-balancing').checked,
+            fuzz_tolerance: parseIntOrDefault(document.getElementById('fuzz-tolerance').value, 5) / 100,
+            overdue_bonus: Math.min(parseIntOrDefault(document.getElementById('overdue-bonus').value, 50), 100) / 100,
+            slow_answer_seconds: parseIntOrDefault(document.getElementById('slow-answer-seconds').value, 0),
+            leech_threshold: parseIntOrDefault(document.getElementById('leech-threshold').value, 8),
+            leech_action: document.getElementById('leech-action').value
         };
 
         // Collect algorithm-specific settings
@@ -903,9 +1043,14 @@ async function saveAlgorithmSettings() {
                 const baseInput = document.getElementById('exponential-base');
                 settings.exponential_base = parseFloat(baseInput.value) || 2.0;
                 break;
+            case 'FSRS':
+                const retentionInput = document.getElementById('desired-retention');
+                settings.desired_retention = parseFloat(retentionInput.value) || 0.9;
+                break;
         }
 
         await invoke('update_settings', { settings });
+        document.getElementById('migration-preview').classList.add('hidden');
 
         // Show success state briefly
         saveButton.textContent = '✓ Saved!';
@@ -946,7 +1091,19 @@ async function saveAlgorithmSettings() {
         const defaultSettings = {
             algorithm: 'SM2',
             leitner_intervals: [1, 3, 7, 14, 30],
-            exponential_base: 2.0
+            exponential_base: 2.0,
+            desired_retention: 0.9,
+            new_cards_per_day: 20,
+            reviews_per_day: 200,
+            learning_steps: [1, 10],
+            relearning_steps: [10],
+            interval_fuzz: false,
+            load_balancing: false,
+            fuzz_tolerance: 0.05,
+            overdue_bonus: 0.5,
+            slow_answer_seconds: 0,
+            leech_threshold: 8,
+            leech_action: 'Tag'
         };
 
         console.log('Resetting to defaults:', defaultSettings);
@@ -1000,14 +1157,20 @@ async function loadReviewStats() {
         document.getElementById('cards-due').textContent = stats.cards_due;
         document.getElementById('total-cards').textContent = stats.total_cards;
 
+        // Cards left under today's new-card and review limits
+        const availableToday = stats.new_cards_remaining + stats.reviews_remaining;
+        document.getElementById('daily-remaining').textContent = stats.cards_due > 0
+            ? `Today: ${stats.new_cards_remaining} new, ${stats.reviews_remaining} reviews remaining`
+            : '';
+
         // Update start button
         const startBtn = document.getElementById('start-review');
-        if (stats.cards_due > 0) {
-            startBtn.textContent = `Review ${stats.cards_due} Cards`;
+        if (availableToday > 0) {
+            startBtn.textContent = `Review ${availableToday} Cards`;
             startBtn.disabled = false;
             startBtn.classList.remove('opacity-50', 'cursor-not-allowed');
         } else {
-            startBtn.textContent = 'No Cards Due';
+            startBtn.textContent = stats.cards_due > 0 ? 'Daily Limit Reached' : 'No Cards Due';
             startBtn.disabled = true;
             startBtn.classList.add('opacity-50', 'cursor-not-allowed');
         }
@@ -1019,40 +1182,42 @@ async function loadReviewStats() {
 
 async function startReview() {
     try {
-        const dueCards = await invoke('get_due_cards');
+        const order = document.getElementById('review-order').value;
+        const progress = await invoke('start_review_session', { order });
 
-        if (dueCards.length === 0) {
+        if (!progress.card) {
+            await invoke('finish_review_session');
             showError('No cards are due for review');
             return;
         }
 
-        currentReviewCards = dueCards;
-        currentCardIndex = 0;
-
         // Hide start button, show card interface
         document.getElementById('start-review').parentElement.classList.add('hidden');
         document.getElementById('review-card').classList.remove('hidden');
 
-        showCurrentCard();
+        showSessionProgress(progress);
     } catch (error) {
         console.error('Failed to start review:', error);
         showError('Failed to start review session');
     }
 }
 
-function showCurrentCard() {
-    if (currentCardIndex >= currentReviewCards.length) {
+// Show the card the session is at; the session keeps the queue, including cards that come back
+function showSessionProgress(progress) {
+    if (!progress.card) {
         finishReview();
         return;
     }
 
-    currentCard = currentReviewCards[currentCardIndex];
+    currentCard = progress.card;
+    cardShownAt = performance.now();
 
     // Update progress
-    document.getElementById('current-card-num').textContent = currentCardIndex + 1;
-    document.getElementById('total-review-cards').textContent = currentReviewCards.length;
-    const progress = ((currentCardIndex) / currentReviewCards.length) * 100;
-    document.getElementById('review-progress').style.width = `${progress}%`;
+    const total = progress.answered + progress.remaining;
+    document.getElementById('current-card-num').textContent = progress.answered + 1;
+    document.getElementById('total-review-cards').textContent = total;
+    document.getElementById('review-progress').style.width = `${(progress.answered / total) * 100}%`;
+    document.getElementById('review-undo-btn').classList.toggle('hidden', progress.answered === 0);
 
     // Show card front
     document.getElementById('card-front-text').textContent = currentCard.front;
@@ -1073,27 +1238,47 @@ async function rateCard(difficulty) {
     console.log('Rating card with difficulty:', difficulty, 'Card ID:', currentCard?.id);
 
     try {
-        const result = await invoke('review_card', {
-            id: currentCard.id,
-            difficulty: difficulty
+        const progress = await invoke('answer_session_card', {
+            cardId: currentCard.id,
+            difficulty: difficulty,
+            timeTakenMs: Math.round(performance.now() - cardShownAt)
         });
 
-        console.log('Rating successful, result:', result);
-
-        currentCardIndex++;
-        showCurrentCard();
+        console.log('Rating successful, progress:', progress);
+        showSessionProgress(progress);
     } catch (error) {
         console.error('Failed to rate card:', error);
         showError('Failed to save card rating');
     }
 }
 
-function finishReview() {
+async function undoLastRating() {
+    try {
+        // Reverts the previous rating, including its review log entry, and shows its card again
+        const progress = await invoke('undo_session_answer');
+        showSessionProgress(progress);
+        showSuccess('Last rating undone');
+    } catch (error) {
+        console.error('Failed to undo rating:', error);
+        showError('Failed to undo rating');
+    }
+}
+
+async function finishReview() {
+    currentCard = null;
+
     // Hide card interface, show completion message
     document.getElementById('review-card').classList.add('hidden');
     document.getElementById('start-review').parentElement.classList.remove('hidden');
 
-    showSuccess('Review session completed!');
+    try {
+        const summary = await invoke('finish_review_session');
+        const cards = summary.cards_studied === 1 ? 'card' : 'cards';
+        showSuccess(`Review session completed: ${summary.cards_studied} ${cards}, ${summary.answers} answers, ${summary.again} forgotten`);
+    } catch (error) {
+        console.error('Failed to finish review session:', error);
+        showSuccess('Review session completed!');
+    }
     loadReviewStats();
 }
 
@@ -1112,6 +1297,25 @@ function editCurrentReviewCard() {
     editCard(currentCard.id);
 }
 
+// Suspend or bury the card under review; the session passes over it and shows the next card
+async function setAsideCurrentReviewCard(command, message) {
+    if (!currentCard) {
+        showError('No card selected');
+        return;
+    }
+
+    try {
+        await invoke(command, { id: currentCard.id });
+        const progress = await invoke('next_session_card');
+        showSessionProgress(progress);
+        showSuccess(message);
+        await loadReviewStats();
+    } catch (error) {
+        console.error(`Failed to ${command}:`, error);
+        showError('Failed to set the card aside: ' + error);
+    }
+}
+
 async function deleteCurrentReviewCard() {
     if (!currentCard) {
         showError('No card selected');
@@ -1123,31 +1327,19 @@ async function deleteCurrentReviewCard() {
     try {
         // Store for undo
         lastDeletedCard = currentCard;
+        lastDeletedCards = []; // Clear bulk undo
 
-        // Delete the card
+        // Delete the card; the session skips deleted cards
         await invoke('delete_card', { id: currentCard.id });
-
-        // Remove from current review session
-        currentReviewCards.splice(currentCardIndex, 1);
+        const progress = await invoke('next_session_card');
 
         // Update the review session
-        if (currentReviewCards.length === 0) {
+        if (!progress.card) {
             // No more cards to review
             finishReview();
             showSuccessWithUndo('Card deleted. Review session complete.');
         } else {
-            // Adjust index if we're at the end
-            if (currentCardIndex >= currentReviewCards.length) {
-                currentCardIndex = currentReviewCards.length - 1;
-            }
-
-            // Show next card or finish if no more cards
-            if (currentCardIndex < currentReviewCards.length) {
-                showCurrentCard();
-            } else {
-                finishReview();
-            }
-
+            showSessionProgress(progress);
             showSuccessWithUndo('Card deleted');
         }
 
@@ -1166,7 +1358,8 @@ async function createCard(e) {
 
     const front = document.getElementById('card-front-input').value.trim();
     const back = document.getElementById('card-back-input').value.trim();
-    const tag = document.getElementById('card-tag-input').value.trim() || null;
+    const tags = parseTags(document.getElementById('card-tag-input').value);
+    const deckName = document.getElementById('card-deck-input').value.trim();
 
     if (!front || !back) {
         showError('Both front and back are required');
@@ -1174,6 +1367,8 @@ async function createCard(e) {
     }
 
     try {
+        const deckId = await deckIdForName(deckName);
+
         if (editMode && editingCardId) {
             // Update existing card
             const updatedCard = await invoke('update_card', {
@@ -1181,7 +1376,8 @@ async function createCard(e) {
                 request: {
                     front: front,
                     back: back,
-                    tag: tag
+                    tags: tags,
+                    deck_id: deckId
                 }
             });
 
@@ -1189,12 +1385,6 @@ async function createCard(e) {
 
             // If we were editing from review, update the current review session
             if (editingFromReview) {
-                // Update the card in the current review session
-                const cardIndex = currentReviewCards.findIndex(card => card.id === editingCardId);
-                if (cardIndex !== -1) {
-                    currentReviewCards[cardIndex] = updatedCard;
-                }
-
                 // Update the current card if it's the one we just edited
                 if (currentCard && currentCard.id === editingCardId) {
                     currentCard = updatedCard;
@@ -1231,7 +1421,8 @@ async function createCard(e) {
                 request: {
                     front: front,
                     back: back,
-                    tag: tag
+                    tags: tags,
+                    deck_id: deckId
                 }
             });
             showSuccess('Card created successfully!');
@@ -1286,7 +1477,8 @@ async function editCard(cardId) {
         // Populate form with existing data
         document.getElementById('card-front-input').value = card.front;
         document.getElementById('card-back-input').value = card.back;
-        document.getElementById('card-tag-input').value = card.tag || '';
+        document.getElementById('card-tag-input').value = card.tags.join(', ');
+        document.getElementById('card-deck-input').value = deckName(card.deck_id);
 
         // Switch to create section
         showSection('create');
@@ -1365,7 +1557,10 @@ function displayCards(cards) {
                 <div class="flex-1">
                     <div class="font-medium mb-1">${escapeHtml(card.front)}</div>
                     <div class="text-sm text-zinc-400 mb-2">${escapeHtml(card.back)}</div>
-                    ${card.tag ? `<span class="inline-block bg-zinc-700 text-xs px-2 py-1 rounded">${escapeHtml(card.tag)}</span>` : ''}
+                    ${card.deck_id ? `<span class="inline-block bg-emerald-900/60 text-xs px-2 py-1 rounded mr-1">${escapeHtml(deckName(card.deck_id))}</span>` : ''}
+                    ${card.tags.map(tag => `<span class="inline-block bg-zinc-700 text-xs px-2 py-1 rounded mr-1">${escapeHtml(tag)}</span>`).join('')}
+                    ${card.suspended ? '<span class="inline-block bg-amber-900/60 text-xs px-2 py-1 rounded mr-1">Suspended</span>' : ''}
+                    ${isBuried(card) ? '<span class="inline-block bg-zinc-600 text-xs px-2 py-1 rounded mr-1">Buried</span>' : ''}
                 </div>
                 <div class="flex space-x-1">
                     <button data-card-id="${card.id}" class="edit-card-btn text-blue-400 hover:text-blue-300 p-1 rounded hover:bg-blue-400/10 transition-colors" title="Edit card">
@@ -1411,6 +1606,7 @@ async function deleteCard(cardId) {
 
         // Store for undo
         lastDeletedCard = cardToDelete;
+        lastDeletedCards = []; // Clear bulk undo
 
         console.log('Calling Tauri delete_card command...');
         await invoke('delete_card', { id: cardId });
@@ -1438,13 +1634,91 @@ async function deleteCard(cardId) {
         document.getElementById('stat-due').textContent = stats.cards_due;
         document.getElementById('stat-new').textContent = stats.cards_new;
         document.getElementById('stat-mature').textContent = stats.cards_mature;
+        document.getElementById('stat-set-aside').textContent = stats.cards_suspended + stats.cards_buried > 0
+            ? `${stats.cards_suspended} suspended, ${stats.cards_buried} buried until tomorrow`
+            : '';
 
+        await loadForecast();
+        displayAnswerTimes(await invoke('get_answer_time_stats'));
+        displayLeeches(await invoke('get_leeches'));
     } catch (error) {
         console.error('Failed to load detailed stats:', error);
         showError('Failed to load statistics');
     }
 }
 
+async function loadForecast() {
+    try {
+        const forecast = await invoke('get_review_forecast', {
+            request: {
+                days: parseInt(document.getElementById('forecast-days').value, 10),
+                tag: document.getElementById('forecast-tag').value || null,
+                deck_id: document.getElementById('forecast-deck').value || null
+            }
+        });
+        displayForecast(forecast);
+    } catch (error) {
+        console.error('Failed to load forecast:', error);
+        showError('Failed to load review forecast');
+    }
+}
+
+// One bar per day, scaled to the busiest day
+function displayForecast(forecast) {
+    const peak = Math.max(1, ...forecast.map(day => day.cards_due));
+    const total = forecast.reduce((sum, day) => sum + day.cards_due, 0);
+
+    document.getElementById('forecast-chart').innerHTML = forecast.map(day => `
+        <div class="flex-1 bg-emerald-500/70 hover:bg-emerald-400 rounded-t transition-colors"
+             style="height: ${(day.cards_due / peak) * 100}%; min-height: 1px"
+             title="${day.date}: ${day.cards_due} due"></div>`
+    ).join('');
+    document.getElementById('forecast-summary').textContent = forecast.length > 0
+        ? `${total} reviews over the next ${forecast.length} days, ${(total / forecast.length).toFixed(1)} per day on average, busiest day ${peak}`
+        : '';
+}
+
+function formatSeconds(ms) {
+    return `${(ms / 1000).toFixed(1)}s`;
+}
+
+// Overall average and median, then the cards and tags that took the most time in total
+function displayAnswerTimes(report) {
+    document.getElementById('answer-time-summary').textContent = report.overall
+        ? `${report.overall.answers} timed answers, ${formatSeconds(report.overall.average_ms)} on average, median ${formatSeconds(report.overall.median_ms)}`
+        : 'No timed answers yet';
+
+    const rows = (items, label) => items.slice(0, 5).map(item => `
+        <li class="flex justify-between text-sm">
+            <span class="truncate mr-2">${escapeHtml(label(item))}</span>
+            <span class="text-zinc-400 whitespace-nowrap">${formatSeconds(item.average_ms)} avg, ${formatSeconds(item.median_ms)} median</span>
+        </li>`
+    ).join('');
+    document.getElementById('answer-time-cards').innerHTML = rows(report.cards, card => card.front);
+    document.getElementById('answer-time-tags').innerHTML = rows(report.tags, tag => tag.tag);
+}
+
+// Cards forgotten again and again, with a button to rewrite each
+function displayLeeches(leeches) {
+    const list = document.getElementById('leech-list');
+    if (leeches.length === 0) {
+        list.innerHTML = '<p class="text-sm text-zinc-400">No leeches.</p>';
+        return;
+    }
+
+    list.innerHTML = leeches.map(card => `
+        <div class="flex items-center justify-between bg-zinc-800/50 rounded-lg px-3 py-2">
+            <div class="min-w-0">
+                <div class="font-medium truncate">${escapeHtml(card.front)}</div>
+                <div class="text-xs text-zinc-400">${card.lapses} lapses${card.suspended ? ', suspended' : ''}</div>
+            </div>
+            <button data-card-id="${card.id}" class="edit-card-btn text-blue-400 hover:text-blue-300 p-1 rounded hover:bg-blue-400/10 transition-colors" title="Edit card">
+                ✏️
+            </button>
+        </div>`
+    ).join('');
+}
+
 function showSuccess(message) {
     const successEl = document.getElementById('success-message');
     const timeout = CONFIG.SUCCESS_TIMEOUT;
@@ -1572,41 +1846,27 @@ async function undoDelete() {
     document.getElementById('success-message').classList.add('hidden');
 
     try {
-        if (hasBulkDeletes) {
-            console.log('Undoing bulk delete for cards:', lastDeletedCards.length);
-
-            // Recreate all the deleted cards
-            for (const card of lastDeletedCards) {
-                await invoke('create_card', {
-                    request: {
-                        front: card.front,
-                        back: card.back,
-                        tag: card.tag
-                    }
-                });
-            }
+        // Undoing the delete restores the cards with their ids and scheduling intact, but only
+        // while it is still the latest operation; otherwise undo would revert something else
+        const deletedCount = hasBulkDeletes ? lastDeletedCards.length : 1;
+        const expected = deletedCount === 1 ? 'Delete card' : `Delete ${deletedCount} cards`;
+        const undoState = await invoke('get_undo_state');
+        if (undoState.undo_description !== expected) {
+            lastDeletedCards = [];
+            lastDeletedCard = null;
+            showError('The delete can no longer be undone because other changes were made since');
+            return;
+        }
+
+        await invoke('undo');
 
+        if (hasBulkDeletes) {
             showSuccess(`Restored ${lastDeletedCards.length} card${lastDeletedCards.length > 1 ? 's' : ''} successfully`);
             console.log('Bulk cards restored successfully');
-
-            // Clear the stored cards
             lastDeletedCards = [];
         } else {
-            console.log('Undoing delete for card:', lastDeletedCard.front);
-
-            // Recreate the single card
-            await invoke('create_card', {
-                request: {
-                    front: lastDeletedCard.front,
-                    back: lastDeletedCard.back,
-                    tag: lastDeletedCard.tag
-                }
-            });
-
             showSuccess('Card restored successfully');
             console.log('Card restored successfully');
-
-            // Clear the stored card
             lastDeletedCard = null;
         }
 
@@ -1721,18 +1981,83 @@ function updateTagDropdowns() {
     tags.forEach(tag => {
         bulkTagSelect.innerHTML += `<option value="${escapeHtml(tag)}">${escapeHtml(tag)}</option>`;
     });
+
+    // Update forecast tag filter, keeping the selection
+    const forecastTag = document.getElementById('forecast-tag');
+    const forecastSelected = forecastTag.value;
+    forecastTag.innerHTML = tagFilter.innerHTML;
+    forecastTag.value = tags.includes(forecastSelected) ? forecastSelected : '';
+}
+
+// Load decks for the deck filter, the card form and the card list
+async function loadDecks() {
+    try {
+        decks = await invoke('get_decks');
+        updateDeckDropdowns();
+    } catch (error) {
+        console.error('Failed to load decks:', error);
+    }
+}
+
+function updateDeckDropdowns() {
+    const deckFilter = document.getElementById('deck-filter');
+    const selected = deckFilter.value;
+
+    // Nested decks are indented below their parent
+    deckFilter.innerHTML = '<option value="">All Decks</option>';
+    decks.forEach(deck => {
+        const levels = deck.name.split('::');
+        const indent = '&nbsp;&nbsp;'.repeat(levels.length - 1);
+        deckFilter.innerHTML += `<option value="${deck.id}">${indent}${escapeHtml(levels[levels.length - 1])}</option>`;
+    });
+    deckFilter.value = decks.some(deck => deck.id === selected) ? selected : '';
+
+    ['simulation-deck', 'forecast-deck'].forEach(id => {
+        const select = document.getElementById(id);
+        const current = select.value;
+        select.innerHTML = deckFilter.innerHTML;
+        select.value = decks.some(deck => deck.id === current) ? current : '';
+    });
+
+    document.getElementById('deck-options').innerHTML = decks
+        .map(deck => `<option value="${escapeHtml(deck.name)}"></option>`)
+        .join('');
+}
+
+function deckName(deckId) {
+    const deck = decks.find(deck => deck.id === deckId);
+    return deck ? deck.name : '';
+}
+
+// Id of the deck with the given name, creating the deck when it does not exist yet
+async function deckIdForName(name) {
+    if (!name) {
+        return null;
+    }
+
+    const normalized = name.split('::').map(level => level.trim()).join('::');
+    const existing = decks.find(deck => deck.name === normalized);
+    if (existing) {
+        return existing.id;
+    }
+
+    const deck = await invoke('create_deck', { request: { name: normalized, preset_id: null } });
+    await loadDecks();
+    return deck.id;
 }
 
 // Search and filter cards
 async function filterCards() {
     const searchQuery = document.getElementById('search-input').value.trim();
     const tagFilter = document.getElementById('tag-filter').value;
+    const deckFilter = document.getElementById('deck-filter').value;
 
     try {
         const searchRequest = {
             query: searchQuery || null,
-            tag: tagFilter || null,
-            tags: null
+            tags: tagFilter ? [tagFilter] : null,
+            tag_match: 'Any',
+            deck_id: deckFilter || null
         };
 
         const filteredCards = await invoke('search_cards', { request: searchRequest });
@@ -1811,6 +2136,9 @@ function updateSelectionControls() {
     const selectAllCheckbox = document.getElementById('select-all');
     const bulkDeleteBtn = document.getElementById('bulk-delete-btn');
     const bulkTagApplyBtn = document.getElementById('bulk-tag-apply');
+    const bulkTagRemoveBtn = document.getElementById('bulk-tag-remove');
+    const bulkStateApplyBtn = document.getElementById('bulk-state-apply');
+    const bulkRescheduleApplyBtn = document.getElementById('bulk-reschedule-apply');
 
     selectedCount.textContent = `${selectedCards.size} selected`;
 
@@ -1832,6 +2160,9 @@ function updateSelectionControls() {
     const hasSelection = selectedCards.size > 0;
     bulkDeleteBtn.disabled = !hasSelection;
     bulkTagApplyBtn.disabled = !hasSelection;
+    bulkTagRemoveBtn.disabled = !hasSelection;
+    bulkStateApplyBtn.disabled = !hasSelection;
+    bulkRescheduleApplyBtn.disabled = !hasSelection;
 
     console.log('Selection controls updated:', {
         selectedCount: selectedCards.size,
@@ -1901,12 +2232,85 @@ async function bulkDeleteCards() {
     }
 }
 
-async function bulkUpdateTag() {
+function isBuried(card) {
+    return Boolean(card.buried_until) && new Date(card.buried_until) > new Date();
+}
+
+// Suspend, unsuspend, bury or unbury the selected cards
+async function bulkSetAside() {
+    if (selectedCards.size === 0) {
+        showError('Please select cards first');
+        return;
+    }
+
+    const select = document.getElementById('bulk-state-select');
+    const action = select.options[select.selectedIndex].text;
+    try {
+        const result = await invoke(select.value, { cardIds: Array.from(selectedCards) });
+        showSuccess(`${action}: ${result.length} card${result.length === 1 ? '' : 's'} changed`);
+        selectedCards.clear();
+
+        await loadCards();
+        await loadReviewStats();
+        updateSelectionControls();
+    } catch (error) {
+        console.error('Failed to update cards:', error);
+        showError('Failed to update cards: ' + error);
+    }
+}
+
+// Reset, forget, postpone or set the due date of the selected cards
+async function bulkReschedule() {
+    if (selectedCards.size === 0) {
+        showError('Please select cards first');
+        return;
+    }
+
+    const command = document.getElementById('bulk-reschedule-select').value;
+    const daysInput = document.getElementById('bulk-days-input').value.trim();
+    const cardIds = Array.from(selectedCards);
+    let args;
+    if (command === 'shift_due_dates') {
+        const days = parseInt(daysInput, 10);
+        if (isNaN(days)) {
+            showError('Enter the number of days to postpone by');
+            return;
+        }
+        args = { request: { card_ids: cardIds, days } };
+    } else if (command === 'set_due_date') {
+        // A single day, or a range of days to spread the cards over
+        const match = daysInput.match(/^(\d+)(?:\s*-\s*(\d+))?$/);
+        if (!match) {
+            showError('Enter the days from today, e.g. 7 or 0-7');
+            return;
+        }
+        const fromDays = parseInt(match[1], 10);
+        args = { request: { card_ids: cardIds, from_days: fromDays, to_days: match[2] ? parseInt(match[2], 10) : fromDays } };
+    } else {
+        args = { cardIds };
+    }
+
+    try {
+        const result = await invoke(command, args);
+        showSuccess(`Rescheduled ${result.length} card${result.length === 1 ? '' : 's'}`);
+        selectedCards.clear();
+
+        await loadCards();
+        await loadReviewStats();
+        updateSelectionControls();
+    } catch (error) {
+        console.error('Failed to reschedule cards:', error);
+        showError('Failed to reschedule cards: ' + error);
+    }
+}
+
+// Add the chosen tag to, or remove it from, the selected cards
+async function bulkUpdateTag(action) {
     const bulkTagSelect = document.getElementById('bulk-tag-select');
     const bulkTagInput = document.getElementById('bulk-tag-input');
-    const newTag = bulkTagInput.value.trim() || bulkTagSelect.value;
+    const tag = bulkTagInput.value.trim() || bulkTagSelect.value;
 
-    console.log('bulkUpdateTag called, selectedCards:', selectedCards, 'newTag:', newTag);
+    console.log('bulkUpdateTag called, action:', action, 'selectedCards:', selectedCards, 'tag:', tag);
 
     if (selectedCards.size === 0) {
         console.log('No cards selected for tag update');
@@ -1914,35 +2318,34 @@ async function bulkUpdateTag() {
         return;
     }
 
-    if (!newTag) {
+    if (!tag) {
         console.log('No tag specified');
         showError('Please select a tag or enter a new one');
         return;
     }
 
     const cardIds = Array.from(selectedCards);
-    console.log('Card IDs for tag update:', cardIds, 'Types:', cardIds.map(id => typeof id));
-    console.log('Attempting to update tag for cards:', cardIds, 'to:', newTag);
+    const command = action === 'remove' ? 'bulk_remove_tag' : 'bulk_add_tag';
 
     try {
         const request = {
             card_ids: cardIds,
-            tag: newTag
+            tag: tag
         };
 
-        console.log('Calling backend bulk_update_tag with:', request);
-        const result = await invoke('bulk_update_tag', { request });
+        console.log(`Calling backend ${command} with:`, request);
+        const result = await invoke(command, { request });
         console.log('Backend tag update result:', result);
 
-        showSuccess(`Updated tag for ${cardIds.length} cards to "${newTag}"`);
+        if (action === 'remove') {
+            showSuccess(`Removed tag "${tag}" from ${result.length} card${result.length === 1 ? '' : 's'}`);
+        } else {
+            showSuccess(`Added tag "${tag}" to ${result.length} card${result.length === 1 ? '' : 's'}`);
+        }
         selectedCards.clear();
 
-        // Force immediate UI update with delay
-        console.log('Refreshing UI after bulk tag update...');
-        await new Promise(resolve => setTimeout(resolve, 100));
-
         await loadCards();
-        await loadTags(); // Refresh tags in case it's a new one
+        await loadTags(); // Refresh tags in case one was added or no longer used
         bulkTagSelect.value = '';
         bulkTagInput.value = '';
         updateSelectionControls();
@@ -1954,7 +2357,185 @@ async function bulkUpdateTag() {
     }
 }
 
+// Split a comma-separated list of tags, dropping empty entries
+function parseTags(value) {
+    return value.split(',').map(tag => tag.trim()).filter(tag => tag.length > 0);
+}
+
 // Tag statistics
+// Deck option presets: each deck can use a preset or inherit from its parent deck
+async function loadDeckSettings() {
+    try {
+        await loadDecks();
+        presets = await invoke('get_presets');
+        displayDeckSettings();
+    } catch (error) {
+        console.error('Failed to load decks:', error);
+        showError('Failed to load decks');
+    }
+}
+
+function displayDeckSettings() {
+    const deckList = document.getElementById('deck-settings-list');
+
+    if (decks.length === 0) {
+        deckList.innerHTML = '<p class="text-zinc-400 text-center py-4">No decks yet. Enter a deck name when creating a card.</p>';
+        return;
+    }
+
+    deckList.innerHTML = decks.map(deck => {
+        const depth = deck.name.split('::').length - 1;
+        const options = presets.map(preset =>
+            `<option value="${preset.id}" ${deck.preset_id === preset.id ? 'selected' : ''}>${escapeHtml(preset.name)} (${preset.options.algorithm})</option>`
+        ).join('');
+        return `
+        <div class="flex items-center justify-between bg-zinc-800/50 rounded-lg p-3 border border-zinc-700" style="margin-left: ${depth * 1.5}rem">
+            <span class="font-medium">${escapeHtml(deck.name.split('::').pop())}</span>
+            <div class="flex items-center space-x-2">
+                <select data-deck-id="${deck.id}" class="deck-preset-select rounded-lg bg-zinc-700 border border-zinc-600 p-2 text-sm text-zinc-100">
+                    <option value="">${depth > 0 ? 'Inherit from parent' : 'Global settings'}</option>
+                    ${options}
+                </select>
+                <button data-deck-id="${deck.id}" class="delete-deck-btn text-red-400 hover:text-red-300 p-1 rounded hover:bg-red-400/10 transition-colors" title="Delete deck (cards are kept)">
+                    🗑️
+                </button>
+            </div>
+        </div>
+        <div data-deck-id="${deck.id}" class="deck-preset-preview hidden bg-amber-900/30 rounded-lg p-3 text-sm text-amber-200" style="margin-left: ${depth * 1.5}rem">
+            <div class="flex items-center justify-between">
+                <span class="deck-preset-preview-text"></span>
+                <div class="flex items-center space-x-2">
+                    <button class="deck-preset-apply px-3 py-1 bg-blue-600 hover:bg-blue-700 rounded text-white">Apply</button>
+                    <button class="deck-preset-cancel px-3 py-1 bg-zinc-600 hover:bg-zinc-500 rounded text-white">Cancel</button>
+                </div>
+            </div>
+        </div>`;
+    }).join('');
+
+    deckList.querySelectorAll('.deck-preset-select').forEach(select => {
+        select.addEventListener('change', async (e) => {
+            const id = e.target.dataset.deckId;
+            const presetId = e.target.value || null;
+            try {
+                // A preset with another algorithm converts the scheduling of the deck's cards, so
+                // tell how before applying it
+                const preview = await invoke('preview_deck_preset', { id, presetId });
+                if (preview.cards_migrated === 0) {
+                    await setDeckPreset(id, presetId);
+                    return;
+                }
+
+                const previewEl = deckList.querySelector(`.deck-preset-preview[data-deck-id="${id}"]`);
+                previewEl.querySelector('.deck-preset-preview-text').textContent =
+                    `This converts the scheduling of ${preview.cards_migrated} reviewed cards: ` +
+                    `${preview.due_earlier} will be due earlier and ${preview.due_later} later, ` +
+                    `${preview.cards_due_after} due now instead of ${preview.cards_due_before}.`;
+                previewEl.querySelector('.deck-preset-apply').onclick = () => setDeckPreset(id, presetId);
+                previewEl.querySelector('.deck-preset-cancel').onclick = () => loadDeckSettings();
+                previewEl.classList.remove('hidden');
+            } catch (error) {
+                console.error('Failed to preview deck preset:', error);
+                showError(`Failed to set deck preset: ${error}`);
+            }
+        });
+    });
+
+    deckList.querySelectorAll('.delete-deck-btn').forEach(button => {
+        button.addEventListener('click', async (e) => {
+            try {
+                await invoke('delete_deck', { id: e.currentTarget.dataset.deckId });
+                showSuccess('Deck deleted; its cards moved to the parent deck');
+                await loadDeckSettings();
+                await loadCards();
+            } catch (error) {
+                console.error('Failed to delete deck:', error);
+                showError(`Failed to delete deck: ${error}`);
+            }
+        });
+    });
+}
+
+async function setDeckPreset(id, presetId) {
+    try {
+        await invoke('set_deck_preset', { id, presetId });
+        showSuccess('Deck options updated');
+        await loadDeckSettings();
+        await loadCards();
+    } catch (error) {
+        console.error('Failed to set deck preset:', error);
+        showError(`Failed to set deck preset: ${error}`);
+    }
+}
+
+// Save the current algorithm settings as a named preset that decks can use
+async function createPresetFromSettings() {
+    const nameInput = document.getElementById('preset-name-input');
+    const name = nameInput.value.trim();
+    if (!name) {
+        showError('Please enter a preset name');
+        return;
+    }
+
+    try {
+        const options = await invoke('get_settings');
+        await invoke('create_preset', { request: { name: name, options: options } });
+        nameInput.value = '';
+        showSuccess(`Preset "${name}" created from the current settings`);
+        await loadDeckSettings();
+    } catch (error) {
+        console.error('Failed to create preset:', error);
+        showError(`Failed to create preset: ${error}`);
+    }
+}
+
+// Compare the projected workload of every algorithm on the selected deck
+async function simulateSchedules() {
+    const days = parseIntOrDefault(document.getElementById('simulation-days').value, 30);
+    const deckId = document.getElementById('simulation-deck').value || null;
+    const results = document.getElementById('simulation-results');
+
+    try {
+        results.innerHTML = '<p class="text-zinc-400 text-center py-4">Simulating...</p>';
+        const simulations = await invoke('simulate_schedules', { request: { days: days, deck_id: deckId } });
+        displaySimulationResults(simulations);
+    } catch (error) {
+        console.error('Failed to simulate schedules:', error);
+        results.innerHTML = '';
+        showError(`Failed to simulate schedules: ${error}`);
+    }
+}
+
+function displaySimulationResults(simulations) {
+    const rows = simulations.map(simulation => {
+        const days = simulation.daily_reviews.length;
+        const average = days > 0 ? simulation.total_reviews / days : 0;
+        const peak = Math.max(0, ...simulation.daily_reviews);
+        const retention = simulation.retention === null ? '–' : `${Math.round(simulation.retention * 100)}%`;
+        return `
+        <tr class="border-t border-zinc-700">
+            <td class="py-2 font-medium">${simulation.algorithm}</td>
+            <td class="py-2 text-right">${average.toFixed(1)}</td>
+            <td class="py-2 text-right">${peak}</td>
+            <td class="py-2 text-right">${retention}</td>
+            <td class="py-2 text-right">${Math.round(simulation.total_minutes)} min</td>
+        </tr>`;
+    }).join('');
+
+    document.getElementById('simulation-results').innerHTML = `
+        <table class="w-full text-sm">
+            <thead class="text-zinc-400">
+                <tr>
+                    <th class="py-2 text-left font-normal">Algorithm</th>
+                    <th class="py-2 text-right font-normal">Reviews/day</th>
+                    <th class="py-2 text-right font-normal">Peak</th>
+                    <th class="py-2 text-right font-normal">Retention</th>
+                    <th class="py-2 text-right font-normal">Total time</th>
+                </tr>
+            </thead>
+            <tbody>${rows}</tbody>
+        </table>`;
+}
+
 async function loadTagStats() {
     try {
         const tagStats = await invoke('get_tag_stats');